@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::revaultd::config::default_datadir;
+use super::menu::Menu;
+use crate::{
+    conversion::{Denomination, FiatConfig},
+    revault::Role,
+    revaultd::config::{default_datadir, merge_toml, update_toml, write_atomic},
+};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -13,6 +20,19 @@ pub struct Config {
     pub log_level: Option<String>,
     /// Use iced debug feature if true.
     pub debug: Option<bool>,
+    /// Denomination used to display and input the amounts, default is btc.
+    pub display_unit: Option<Denomination>,
+    /// Fiat valuation of the amounts (optional).
+    pub fiat: Option<FiatConfig>,
+    /// Preferences of the user, edited from the settings panel.
+    #[serde(default)]
+    pub preferences: Preferences,
+    /// Labels of the deposit slips by deposit address, searched in the vaults.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Options of the command line, never written to the file.
+    #[serde(skip)]
+    pub overrides: Overrides,
 }
 
 pub const DEFAULT_FILE_NAME: &str = "revault_gui.toml";
@@ -24,9 +44,22 @@ impl Config {
             revaultd_path: None,
             log_level: None,
             debug: None,
+            display_unit: None,
+            fiat: None,
+            preferences: Preferences::default(),
+            labels: BTreeMap::new(),
+            overrides: Overrides::default(),
         }
     }
 
+    /// Path to revaultd binary, the one of the command line first.
+    pub fn revaultd_path(&self) -> Option<PathBuf> {
+        self.overrides
+            .revaultd_path
+            .clone()
+            .or_else(|| self.revaultd_path.clone())
+    }
+
     pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
         let config = std::fs::read(path)
             .map_err(|e| match e.kind() {
@@ -41,6 +74,40 @@ impl Config {
         Ok(config)
     }
 
+    /// Writes the configuration to the file. The comments and the keys unknown
+    /// to the GUI of the existing file are kept. If the file cannot be updated
+    /// line by line, it is rewritten and the previous one is kept as a backup.
+    pub fn to_file(&self, path: &Path) -> Result<(), ConfigError> {
+        let value = toml::Value::try_from(&self).map_err(|e| {
+            ConfigError::WritingFile(format!("Serializing configuration file: {}", e))
+        })?;
+        let original = std::fs::read_to_string(path).unwrap_or_default();
+        let updated = toml::from_str::<toml::value::Table>(&original)
+            .ok()
+            .and_then(|document| {
+                let previous = toml::from_str::<Config>(&original)
+                    .ok()
+                    .and_then(|c| toml::Value::try_from(c).ok())?;
+                let merged = merge_toml(&document, previous.as_table()?, value.as_table()?);
+                update_toml(&original, &merged)
+            });
+        let content = match updated {
+            Some(content) => content,
+            None => {
+                if path.exists() {
+                    std::fs::copy(path, path.with_extension("toml.bak")).map_err(|e| {
+                        ConfigError::WritingFile(format!("Backing up configuration file: {}", e))
+                    })?;
+                }
+                toml::to_string(&self).map_err(|e| {
+                    ConfigError::WritingFile(format!("Serializing configuration file: {}", e))
+                })?
+            }
+        };
+        write_atomic(path, content.as_bytes())
+            .map_err(|e| ConfigError::WritingFile(format!("Writing configuration file: {}", e)))
+    }
+
     pub fn default_path() -> Result<PathBuf, ConfigError> {
         let mut datadir = default_datadir().map_err(|_| {
             ConfigError::Unexpected("Could not locate the default datadir directory.".to_owned())
@@ -50,10 +117,213 @@ impl Config {
     }
 }
 
+/// Overrides are the options of the command line applied over the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub revaultd_path: Option<PathBuf>,
+    /// Do not start revaultd if it is not running.
+    pub no_daemon_start: bool,
+}
+
+/// Profile is a GUI configuration file found in the datadir,
+/// like the configuration of one network or of one deployment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// profiles lists the GUI configuration files of the datadir named like
+/// `revault_gui.toml` or `revault_gui_<name>.toml`, the default one first.
+/// The files that cannot be parsed are ignored.
+pub fn profiles(datadir: &Path) -> Vec<Profile> {
+    let prefix = DEFAULT_FILE_NAME.trim_end_matches(".toml");
+    let entries = match std::fs::read_dir(datadir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut profiles: Vec<Profile> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            let name = if file_name == DEFAULT_FILE_NAME {
+                "default".to_string()
+            } else {
+                file_name
+                    .strip_prefix(prefix)?
+                    .strip_prefix('_')?
+                    .strip_suffix(".toml")?
+                    .to_string()
+            };
+            if let Err(e) = Config::from_file(&path) {
+                tracing::warn!("Ignoring configuration {}: {}", path.to_string_lossy(), e);
+                return None;
+            }
+            Some(Profile { name, path })
+        })
+        .collect();
+    profiles.sort_by(|a, b| match (a.name.as_str(), b.name.as_str()) {
+        ("default", _) => std::cmp::Ordering::Less,
+        (_, "default") => std::cmp::Ordering::Greater,
+        (a, b) => a.cmp(b),
+    });
+    profiles
+}
+
+/// Minimum interval in seconds between two checks of the network.
+pub const MIN_REFRESH_INTERVAL_SECS: u64 = 10;
+
+/// Preferences are the display settings of the GUI, persisted in the
+/// [preferences] table of the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Preferences {
+    /// Color theme of the interface, default is light.
+    pub theme: Option<Theme>,
+    /// Role displayed at startup if the user is both a stakeholder and a manager.
+    pub default_role: Option<Role>,
+    /// Last menu visited, displayed at startup.
+    pub last_menu: Option<Menu>,
+    /// Size of the window at startup.
+    pub window_size: Option<WindowSize>,
+    /// Interval in seconds between two checks of the network.
+    pub refresh_interval_secs: Option<u64>,
+    /// Notifications displayed by the interface.
+    pub notifications: Option<Notifications>,
+}
+
+impl Preferences {
+    pub fn theme(&self) -> Theme {
+        self.theme.unwrap_or_default()
+    }
+
+    /// Interval between two checks of the network, at least MIN_REFRESH_INTERVAL_SECS.
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh_interval_secs
+            .map(|secs| Duration::from_secs(secs.max(MIN_REFRESH_INTERVAL_SECS)))
+    }
+
+    pub fn notifications(&self) -> Notifications {
+        self.notifications.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Light, Theme::Dark];
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::Light
+    }
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Light => write!(f, "Light"),
+            Self::Dark => write!(f, "Dark"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WindowSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowSize {
+    pub const ALL: [WindowSize; 4] = [
+        WindowSize {
+            width: 1024,
+            height: 768,
+        },
+        WindowSize {
+            width: 1280,
+            height: 800,
+        },
+        WindowSize {
+            width: 1440,
+            height: 900,
+        },
+        WindowSize {
+            width: 1920,
+            height: 1080,
+        },
+    ];
+}
+
+impl std::fmt::Display for WindowSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} x {}", self.width, self.height)
+    }
+}
+
+/// RefreshInterval is an interval in seconds between two checks of the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshInterval(pub u64);
+
+impl RefreshInterval {
+    pub const ALL: [RefreshInterval; 4] = [
+        RefreshInterval(15),
+        RefreshInterval(30),
+        RefreshInterval(60),
+        RefreshInterval(300),
+    ];
+}
+
+impl std::fmt::Display for RefreshInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.0 % 60 == 0 {
+            write!(f, "{} min", self.0 / 60)
+        } else {
+            write!(f, "{} s", self.0)
+        }
+    }
+}
+
+/// Notifications enables the alerts of the interface, all are enabled by default.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Notifications {
+    /// Display the status of the network in the sidebar.
+    #[serde(default = "enabled")]
+    pub network: bool,
+    /// Display the emergency tracking at startup if an emergency is in progress.
+    #[serde(default = "enabled")]
+    pub emergency: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            network: true,
+            emergency: true,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum ConfigError {
     NotFound,
     ReadingFile(String),
+    WritingFile(String),
     Unexpected(String),
 }
 
@@ -62,9 +332,105 @@ impl std::fmt::Display for ConfigError {
         match self {
             Self::NotFound => write!(f, "Config file not found"),
             Self::ReadingFile(e) => write!(f, "Error while reading file: {}", e),
+            Self::WritingFile(e) => write!(f, "Error while writing file: {}", e),
             Self::Unexpected(e) => write!(f, "Unexpected error: {}", e),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_profiles() {
+        let datadir =
+            std::env::temp_dir().join(format!("revault_gui_profiles_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&datadir);
+        std::fs::create_dir_all(&datadir).unwrap();
+        for name in &[
+            "revault_gui_testnet.toml",
+            "revault_gui.toml",
+            "revault_gui_regtest.toml",
+        ] {
+            Config::new(PathBuf::from("revaultd.toml"))
+                .to_file(&datadir.join(name))
+                .unwrap();
+        }
+        std::fs::write(datadir.join("revault_gui_broken.toml"), "debug = ").unwrap();
+        std::fs::write(datadir.join("revaultd.toml"), "").unwrap();
+
+        let names: Vec<String> = profiles(&datadir).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["default", "regtest", "testnet"]);
+        std::fs::remove_dir_all(&datadir).unwrap();
+    }
+
+    #[test]
+    fn update_toml_keeps_comments() {
+        let original = "# revault gui configuration\n\
+            revaultd_config_path = \"/revaultd.toml\" # revaultd\n\
+            debug = true\n\
+            \n\
+            # fiat valuation\n\
+            [fiat]\n\
+            currency = \"USD\"\n\
+            source = \"manual\"\n\
+            rates = { USD = 30000.0 }\n";
+        let mut config: Config = toml::from_str(original).unwrap();
+        config.debug = None;
+        config.display_unit = Some(Denomination::Satoshi);
+        config.preferences.theme = Some(Theme::Dark);
+        config.preferences.window_size = Some(WindowSize::ALL[1]);
+
+        let value = toml::Value::try_from(&config).unwrap();
+        let content = update_toml(original, value.as_table().unwrap()).unwrap();
+        assert_eq!(
+            content,
+            "# revault gui configuration\n\
+            revaultd_config_path = \"/revaultd.toml\" # revaultd\n\
+            display_unit = \"sats\"\n\
+            \n\
+            # fiat valuation\n\
+            [fiat]\n\
+            currency = \"USD\"\n\
+            source = \"manual\"\n\
+            rates = { USD = 30000.0 }\n\
+            \n\
+            [preferences]\n\
+            theme = \"dark\"\n\
+            \n\
+            [preferences.window_size]\n\
+            height = 800\n\
+            width = 1280\n"
+        );
+        let updated: Config = toml::from_str(&content).unwrap();
+        assert_eq!(updated.preferences, config.preferences);
+
+        config.fiat = None;
+        let value = toml::Value::try_from(&config).unwrap();
+        let content = update_toml(&content, value.as_table().unwrap()).unwrap();
+        let updated: Config = toml::from_str(&content).unwrap();
+        assert!(updated.fiat.is_none());
+        assert!(content.starts_with("# revault gui configuration\n"));
+    }
+
+    #[test]
+    fn update_toml_edits_values() {
+        let original = "[preferences]\n  \
+            theme = \"light\" # color theme\n  \
+            \"last_menu\" = \"home\"\n";
+        let mut config = Config::new(PathBuf::from("/revaultd.toml"));
+        config.preferences.theme = Some(Theme::Dark);
+        config.preferences.last_menu = Some(Menu::Home);
+        let value = toml::Value::try_from(&config).unwrap();
+        let content = update_toml(original, value.as_table().unwrap()).unwrap();
+        assert!(content.contains("\n  theme = \"dark\" # color theme\n"));
+        assert!(content.contains("\n  \"last_menu\" = \"home\"\n"));
+
+        // Dotted keys cannot be updated line by line.
+        let original = "preferences.theme = \"light\"\n";
+        assert!(update_toml(original, value.as_table().unwrap()).is_none());
+    }
+}
@@ -1,10 +1,14 @@
-use crate::revaultd::{config::ConfigError, RevaultDError};
+use crate::{
+    export,
+    revaultd::{config::ConfigError, RevaultDError},
+};
 use std::convert::From;
 
 #[derive(Debug, Clone)]
 pub enum Error {
     ConfigError(ConfigError),
     RevaultDError(RevaultDError),
+    ExportError(export::Error),
     UnexpectedError(String),
 }
 
@@ -13,6 +17,7 @@ impl std::fmt::Display for Error {
         match self {
             Self::ConfigError(e) => write!(f, "Config error: {}", e),
             Self::RevaultDError(e) => write!(f, "RevaultD error: {}", e),
+            Self::ExportError(e) => write!(f, "Export error: {}", e),
             Self::UnexpectedError(e) => write!(f, "Unexpected error: {}", e),
         }
     }
@@ -29,3 +34,9 @@ impl From<RevaultDError> for Error {
         Error::RevaultDError(error)
     }
 }
+
+impl From<export::Error> for Error {
+    fn from(error: export::Error) -> Self {
+        Error::ExportError(error)
+    }
+}
@@ -0,0 +1,233 @@
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::revaultd::{config::Config, GetInfoResponse, RevaultD, RevaultDError};
+
+/// Timeout of a TCP reachability probe.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Interval between two checks of the network.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of outages kept in the history of a component.
+const MAX_OUTAGES: usize = 20;
+
+/// Component is a remote service needed by revaultd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Component {
+    Bitcoind,
+    Coordinator,
+    Cosigner,
+    Watchtower,
+}
+
+impl std::fmt::Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Bitcoind => write!(f, "Bitcoin Core"),
+            Self::Coordinator => write!(f, "Coordinator"),
+            Self::Cosigner => write!(f, "Cosigning server"),
+            Self::Watchtower => write!(f, "Watchtower"),
+        }
+    }
+}
+
+/// Probe is the result of a TCP connection attempt to a component.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub component: Component,
+    pub host: String,
+    pub latency: Result<Duration, String>,
+}
+
+/// NetworkReport is the result of one check of all the components.
+#[derive(Debug, Clone)]
+pub struct NetworkReport {
+    /// unix timestamp of the check.
+    pub timestamp: i64,
+    /// getinfo answer of revaultd, it gives the bitcoind sync progress.
+    pub info: Result<GetInfoResponse, RevaultDError>,
+    pub probes: Vec<Probe>,
+}
+
+/// check asks revaultd for its status and probes the components of the configuration.
+/// It is blocking and must be called from an async command.
+pub fn check(revaultd: &RevaultD) -> NetworkReport {
+    let info = revaultd.get_info();
+    let probes = components(&revaultd.config)
+        .into_iter()
+        .map(|(component, host)| Probe {
+            latency: probe(&host),
+            component,
+            host,
+        })
+        .collect();
+
+    NetworkReport {
+        timestamp: chrono::Utc::now().timestamp(),
+        info,
+        probes,
+    }
+}
+
+/// check_network checks the network, the periodic checks are triggered
+/// by a timer subscription of the app.
+pub async fn check_network(revaultd: Arc<RevaultD>) -> NetworkReport {
+    check(&revaultd)
+}
+
+/// components lists the components of the configuration with their host.
+fn components(config: &Config) -> Vec<(Component, String)> {
+    let mut components = vec![
+        (Component::Bitcoind, config.bitcoind_config.addr.to_string()),
+        (Component::Coordinator, config.coordinator_host.clone()),
+    ];
+    if let Some(manager) = &config.manager_config {
+        for cosigner in &manager.cosigners {
+            components.push((Component::Cosigner, cosigner.host.clone()));
+        }
+    }
+    if let Some(stakeholder) = &config.stakeholder_config {
+        for watchtower in &stakeholder.watchtowers {
+            components.push((Component::Watchtower, watchtower.host.clone()));
+        }
+    }
+    components
+}
+
+/// probe opens a TCP connection to the host and returns the time it took.
+fn probe(host: &str) -> Result<Duration, String> {
+    let addrs: Vec<SocketAddr> = host
+        .to_socket_addrs()
+        .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+        .collect();
+    let mut error = format!("No address found for {}", host);
+    for addr in addrs {
+        let start = Instant::now();
+        match TcpStream::connect_timeout(&addr, PROBE_TIMEOUT) {
+            Ok(_) => return Ok(start.elapsed()),
+            Err(e) => error = format!("Cannot connect to {}: {}", addr, e),
+        }
+    }
+    Err(error)
+}
+
+/// Outage is a period during which a component was unreachable.
+#[derive(Debug, Clone)]
+pub struct Outage {
+    pub start: i64,
+    /// end is None if the component is still unreachable.
+    pub end: Option<i64>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentHealth {
+    pub component: Component,
+    pub host: String,
+    /// reachable is None until the first check.
+    pub reachable: Option<bool>,
+    pub latency: Option<Duration>,
+    /// unix timestamp of the last successful probe.
+    pub last_contact: Option<i64>,
+    pub error: Option<String>,
+    /// outages, the most recent first.
+    pub outages: Vec<Outage>,
+}
+
+impl ComponentHealth {
+    fn new(component: Component, host: String) -> Self {
+        Self {
+            component,
+            host,
+            reachable: None,
+            latency: None,
+            last_contact: None,
+            error: None,
+            outages: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, timestamp: i64, result: Result<Duration, String>) {
+        match result {
+            Ok(latency) => {
+                if let Some(outage) = self.outages.first_mut() {
+                    if outage.end.is_none() {
+                        outage.end = Some(timestamp);
+                    }
+                }
+                self.reachable = Some(true);
+                self.latency = Some(latency);
+                self.last_contact = Some(timestamp);
+                self.error = None;
+            }
+            Err(e) => {
+                if self.reachable != Some(false) {
+                    self.outages.insert(
+                        0,
+                        Outage {
+                            start: timestamp,
+                            end: None,
+                            error: e.clone(),
+                        },
+                    );
+                    self.outages.truncate(MAX_OUTAGES);
+                }
+                self.reachable = Some(false);
+                self.latency = None;
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// NetworkHealth keeps the state of the components across the checks,
+/// it lives in the app context to keep the history while the user navigates.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkHealth {
+    /// bitcoind blockheight and sync progress given by revaultd.
+    pub blockheight: Option<u64>,
+    pub sync: Option<f64>,
+    /// error of the last getinfo call to revaultd.
+    pub info_error: Option<String>,
+    /// unix timestamp of the last check.
+    pub last_check: Option<i64>,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl NetworkHealth {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            blockheight: None,
+            sync: None,
+            info_error: None,
+            last_check: None,
+            components: components(config)
+                .into_iter()
+                .map(|(component, host)| ComponentHealth::new(component, host))
+                .collect(),
+        }
+    }
+
+    pub fn record(&mut self, report: NetworkReport) {
+        self.last_check = Some(report.timestamp);
+        match report.info {
+            Ok(info) => {
+                self.blockheight = Some(info.blockheight);
+                self.sync = Some(info.sync);
+                self.info_error = None;
+            }
+            Err(e) => self.info_error = Some(e.to_string()),
+        }
+        for probe in report.probes {
+            if let Some(health) = self
+                .components
+                .iter_mut()
+                .find(|c| c.component == probe.component && c.host == probe.host)
+            {
+                health.record(report.timestamp, probe.latency);
+            }
+        }
+    }
+}
@@ -1,12 +1,50 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+use crate::revault::Role;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Menu {
     Deposit,
     Emergency,
+    EmergencyTracking,
     Home,
+    Logs,
     Network,
+    Review,
     Send,
     CreateVaults,
     DelegateFunds,
     Settings,
     Vaults,
 }
+
+impl Menu {
+    /// restorable returns true if the menu can be displayed at startup
+    /// for the given role.
+    pub fn restorable(&self, role: Role) -> bool {
+        match self {
+            Menu::Home
+            | Menu::Vaults
+            | Menu::Network
+            | Menu::Settings
+            | Menu::Logs
+            | Menu::Deposit
+            | Menu::DelegateFunds
+            | Menu::Review => true,
+            Menu::Send => role == Role::Manager,
+            Menu::CreateVaults => role == Role::Stakeholder,
+            // Emergency panels are only displayed on purpose.
+            Menu::Emergency | Menu::EmergencyTracking => false,
+        }
+    }
+
+    /// read_only returns true if the menu does not lead to any action and
+    /// can be displayed before the end of the sync.
+    pub fn read_only(&self) -> bool {
+        matches!(
+            self,
+            Menu::Home | Menu::Vaults | Menu::Network | Menu::Settings | Menu::Logs
+        )
+    }
+}
@@ -1,32 +1,71 @@
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use super::{error::Error, menu::Menu};
+use super::{
+    config::{Profile, Theme, WindowSize},
+    error::Error,
+    health::NetworkReport,
+    menu::Menu,
+};
+use crate::conversion::{Denomination, FiatRate, PriceError};
+use crate::export::AmountFormat;
 use crate::revault::Role;
 use crate::revaultd::{
     model::{
         RevocationTransactions, SpendTransaction, SpendTx, UnvaultTransaction, Vault, VaultStatus,
         VaultTransactions,
     },
-    RevaultD, RevaultDError,
+    GetInfoResponse, RevaultD, RevaultDError,
 };
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Clipboard(String),
     ChangeRole(Role),
-    Syncing(Result<f64, RevaultDError>),
+    Syncing(Result<GetInfoResponse, RevaultDError>),
     Synced(Arc<RevaultD>),
+    /// asks to open the app before the end of the sync.
+    OpenReadOnly,
+    /// app opened in read-only mode with the sync progress.
+    ReadOnly(Arc<RevaultD>, f64),
     DaemonStarted(Result<Arc<RevaultD>, Error>),
+    /// end of the revaultd log file, read once revaultd failed.
+    ErrorLogs(Vec<String>),
     Vaults(Result<Vec<Vault>, RevaultDError>),
     Vault(String, VaultMessage),
     FilterVaults(VaultFilterMessage),
+    /// the vault list was scrolled, the displayed window is updated.
+    VaultsScrolled,
+    ExportVaults(AmountFormat),
+    VaultsExported(Result<Vec<PathBuf>, Error>),
     BlockHeight(Result<u64, RevaultDError>),
+    /// onchain transactions of a list of vaults.
+    VaultsOnChainTransactions(Result<Vec<VaultTransactions>, RevaultDError>),
     Connected(Result<Arc<RevaultD>, Error>),
     Menu(Menu),
     Next,
     Previous,
-    DepositAddress(Result<bitcoin::Address, RevaultDError>),
+    /// deposit address of revaultd with its derivation index.
+    DepositAddress(Result<(u32, bitcoin::Address), RevaultDError>),
+    /// deposit addresses derived from the descriptor with their derivation index.
+    DepositAddresses(Vec<(u32, bitcoin::Address)>),
+    /// displays the next unused address watched by revaultd.
+    NewDepositAddress,
+    Settings(SettingsMessage),
+    /// stops revaultd to start it again with the saved configuration.
+    RestartDaemon,
+    /// revaultd was asked to stop.
+    DaemonStopping(Result<(), RevaultDError>),
+    /// checks that revaultd stopped, sent by a timer.
+    CheckDaemonStopped,
+    DaemonStopped(Result<(), RevaultDError>),
+    DepositAmountEdited(String),
+    DepositLabelEdited(String),
+    ExportDepositSlip,
+    DepositSlipExported(Result<PathBuf, Error>),
+    /// label of the deposit slip of the address, saved in the configuration.
+    AddressLabel(String, String),
     Recipient(usize, RecipientMessage),
     Input(usize, InputMessage),
     AddRecipient,
@@ -34,7 +73,100 @@ pub enum Message {
     SpendTransactions(Result<Vec<SpendTx>, RevaultDError>),
     SpendTx(SpendTxMessage),
     Emergency,
+    EmergencyConfirmation(String),
+    /// fees of the emergency transaction of each vault.
+    EmergencyFees(Vec<(String, Result<u64, RevaultDError>)>),
+    /// reloads the vaults swept by the emergency to follow the broadcast.
+    RefreshEmergency,
     EmergencyBroadcasted(Result<(), RevaultDError>),
+    /// updates the emergency tracking, sent by its timer.
+    TrackEmergency,
+    /// vaults in an emergency status with their onchain transactions.
+    EmergencyTracked(Result<(Vec<Vault>, Vec<VaultTransactions>), RevaultDError>),
+    /// true if an emergency is in progress when the GUI starts.
+    EmergencyInProgress(Result<bool, RevaultDError>),
+    DisplayUnit(Denomination),
+    FiatCurrency(String),
+    Preferences(PreferencesMessage),
+    /// connects to the revaultd of another profile.
+    SwitchProfile(Profile),
+    /// fetches the current fiat rate again.
+    RefreshFiatRate,
+    FiatRate(Result<FiatRate, PriceError>),
+    /// checks the network again, sent by the timer of the app.
+    CheckNetwork,
+    NetworkChecked(NetworkReport),
+    /// checks the sync progress in read-only mode, sent by the timer of the app.
+    CheckSync,
+    SyncChecked(Result<GetInfoResponse, RevaultDError>),
+    /// writes the last menu visited, sent by the timer of the app.
+    SaveMenu,
+    /// Acknowledge the unvault of the vault with the given outpoint.
+    AcknowledgeUnvault(String),
+    AcknowledgedUnvaults(Result<Vec<String>, Error>),
+    Logs(LogsMessage),
+    /// reconnects to revaultd, sent by the timer of the app.
+    Reconnect,
+    /// reconnects again after the app gave up.
+    RetryReconnect,
+    /// connection to revaultd after it was lost.
+    Reconnected(Result<Arc<RevaultD>, Error>),
+}
+
+#[derive(Debug, Clone)]
+pub enum LogsMessage {
+    /// last lines of the revaultd log file.
+    Loaded(Result<Vec<String>, Error>),
+    Refresh,
+    Level(LogLevel),
+    Search(String),
+    CopyDiagnostics,
+    Diagnostics(Result<String, Error>),
+}
+
+/// LogLevel filters the log lines at the level or more severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    /// of returns the level written in the log line, like `[INFO]` or ` INFO `.
+    pub fn of(line: &str) -> Option<LogLevel> {
+        line.split(|c: char| c == '[' || c == ']' || c.is_whitespace())
+            .find_map(|word| match word {
+                "ERROR" => Some(LogLevel::Error),
+                "WARN" => Some(LogLevel::Warn),
+                "INFO" => Some(LogLevel::Info),
+                "DEBUG" => Some(LogLevel::Debug),
+                "TRACE" => Some(LogLevel::Trace),
+                _ => None,
+            })
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "Error"),
+            Self::Warn => write!(f, "Warning"),
+            Self::Info => write!(f, "Info"),
+            Self::Debug => write!(f, "Debug"),
+            Self::Trace => write!(f, "Trace"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -64,7 +196,10 @@ pub enum SpendTxMessage {
 #[derive(Debug, Clone)]
 pub enum VaultMessage {
     ListOnchainTransaction,
-    RevocationTransactions(Result<RevocationTransactions, RevaultDError>),
+    /// revocation transactions of the vault and its unvault transaction if known.
+    RevocationTransactions(
+        Result<(RevocationTransactions, Result<Psbt, RevaultDError>), RevaultDError>,
+    ),
     OnChainTransactions(Result<VaultTransactions, RevaultDError>),
     UnvaultTransaction(Result<UnvaultTransaction, RevaultDError>),
     Sign(SignMessage),
@@ -80,7 +215,74 @@ pub enum VaultMessage {
 
 #[derive(Debug, Clone)]
 pub enum VaultFilterMessage {
+    /// Replace the status filter by a predefined set of statuses.
     Status(&'static [VaultStatus]),
+    /// Add or remove a status from the status filter.
+    ToggleStatus(VaultStatus, bool),
+    Search(String),
+    Sort(VaultSort),
+    MinAmount(String),
+    MaxAmount(String),
+    Date(VaultDate),
+    After(String),
+    Before(String),
+    /// Show or hide the advanced filter criteria.
+    ToggleCriteria,
+    Reset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultSort {
+    Newest,
+    Oldest,
+    LargestAmount,
+    SmallestAmount,
+    Status,
+    DerivationIndex,
+}
+
+impl VaultSort {
+    pub const ALL: [VaultSort; 6] = [
+        VaultSort::Newest,
+        VaultSort::Oldest,
+        VaultSort::LargestAmount,
+        VaultSort::SmallestAmount,
+        VaultSort::Status,
+        VaultSort::DerivationIndex,
+    ];
+}
+
+impl std::fmt::Display for VaultSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Newest => write!(f, "Newest first"),
+            Self::Oldest => write!(f, "Oldest first"),
+            Self::LargestAmount => write!(f, "Largest amount"),
+            Self::SmallestAmount => write!(f, "Smallest amount"),
+            Self::Status => write!(f, "Status"),
+            Self::DerivationIndex => write!(f, "Derivation index"),
+        }
+    }
+}
+
+/// VaultDate is the vault timestamp the date range filter applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultDate {
+    Received,
+    Updated,
+}
+
+impl VaultDate {
+    pub const ALL: [VaultDate; 2] = [VaultDate::Received, VaultDate::Updated];
+}
+
+impl std::fmt::Display for VaultDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Received => write!(f, "Received"),
+            Self::Updated => write!(f, "Updated"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -111,3 +313,34 @@ pub enum RecipientMessage {
     AddressEdited(String),
     AmountEdited(String),
 }
+
+#[derive(Debug, Clone)]
+pub enum PreferencesMessage {
+    Theme(Theme),
+    DefaultRole(Role),
+    WindowSize(WindowSize),
+    /// interval in seconds between two checks of the network.
+    RefreshInterval(u64),
+    NetworkNotifications(bool),
+    EmergencyNotifications(bool),
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    Edit,
+    CancelEdit,
+    Save,
+    Saved(Result<(), Error>),
+    BitcoindAddressEdited(String),
+    CookiePathEdited(String),
+    BitcoindPollEdited(String),
+    CoordinatorHostEdited(String),
+    CoordinatorNoiseKeyEdited(String),
+    CoordinatorPollEdited(String),
+    LogLevelEdited(String),
+    /// cosigners of a manager or watchtowers of a stakeholder.
+    AddPeer,
+    RemovePeer(usize),
+    PeerHostEdited(usize, String),
+    PeerNoiseKeyEdited(usize, String),
+}
@@ -1,61 +1,162 @@
 pub mod config;
 mod error;
+mod health;
 mod menu;
 mod message;
 mod state;
 mod view;
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use iced::{Clipboard, Color, Command, Element, Subscription};
+use iced::{button, Clipboard, Color, Column, Command, Element, Subscription};
 
 pub use config::Config;
+use config::Profile;
+use error::Error;
 pub use message::Message;
+pub use state::export_vaults;
 
 use menu::Menu;
-use message::{SignMessage, SpendTxMessage, VaultMessage};
+use message::{PreferencesMessage, SignMessage, SpendTxMessage, VaultMessage};
 use state::{
-    ChargingState, DepositState, EmergencyState, ManagerHomeState, ManagerNetworkState,
-    ManagerSendState, SettingsState, StakeholderCreateVaultsState, StakeholderDelegateFundsState,
-    StakeholderHomeState, StakeholderNetworkState, State, VaultsState,
+    check_daemon_stopped, emergency_in_progress, get_info, reconnect, reconnect_delay, stop_daemon,
+    ChargingState, DepositState, EmergencyState, EmergencyTrackingState, LogsState,
+    ManagerHomeState, ManagerNetworkState, ManagerSendState, ReviewState, SettingsState,
+    StakeholderCreateVaultsState, StakeholderDelegateFundsState, StakeholderHomeState,
+    StakeholderNetworkState, State, VaultsState, RECONNECT_ATTEMPTS,
 };
 
-use crate::{app::view::Context, conversion::Converter, revault::Role, revaultd::RevaultD};
+use health::{check_network, NetworkHealth, CHECK_INTERVAL};
+
+use crate::{
+    app::view::Context,
+    conversion::{get_fiat_rate, Converter, Denomination, FiatSource, RATE_REFRESH_INTERVAL},
+    revault::Role,
+    revaultd::{supervisor::Supervisor, RevaultD, RevaultDError},
+};
+
+/// Interval between two checks that revaultd stopped.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of checks after which revaultd failed to stop.
+const STOP_CHECKS: u32 = 10;
+
+/// Interval between two checks of the sync progress in read-only mode,
+/// the cadence of the charging state.
+const SYNC_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay before the last menu visited is written to the configuration file,
+/// the file is not written on every click.
+const SAVE_MENU_DELAY: Duration = Duration::from_secs(5);
 
 pub struct App {
     config: Config,
+    config_path: PathBuf,
     revaultd: Option<Arc<RevaultD>>,
     state: Box<dyn State>,
     context: Context,
+    fiat: Option<FiatSource>,
+    /// profiles found next to the configuration file.
+    profiles: Vec<Profile>,
+    /// supervisor of revaultd if the GUI started it, revaultd is stopped
+    /// when the App is dropped.
+    supervisor: Supervisor,
+    /// supervisors of the previous profiles, stopping their revaultd in the
+    /// background. They are kept so that the GUI waits for the stops on exit.
+    previous_supervisors: Vec<Supervisor>,
+    /// number of the reconnection attempt while the connection to revaultd is lost,
+    /// the attempts stop at RECONNECT_ATTEMPTS until the user retries.
+    reconnecting: Option<u32>,
+    /// reconnect_running is true while a reconnection attempt is running.
+    reconnect_running: bool,
+    retry_button: button::State,
+    /// checking_network is true while a check of the network is running,
+    /// the ticks of the check timer are ignored meanwhile.
+    checking_network: bool,
+    /// number of checks that revaultd stopped, Some while revaultd
+    /// stops to be restarted.
+    stopping: Option<u32>,
+    /// menu_unsaved is true while the last menu visited is not written to the file.
+    menu_unsaved: bool,
+    /// generation of the App, incremented by a switch of profile.
+    generation: u64,
 }
 
 impl App {
     #[allow(unreachable_patterns)]
     pub fn load_state(&mut self, role: Role, menu: Menu) -> Command<Message> {
         self.context.role = role;
-        self.context.menu = menu;
+        // Panels leading to actions are not available in read-only mode.
+        self.context.menu = if self.context.syncing.is_some() && !menu.read_only() {
+            Menu::Home
+        } else {
+            menu
+        };
         let revaultd = self.revaultd.clone().unwrap();
+        let read_only = self.context.syncing.is_some();
         self.state = match self.context.role {
             Role::Manager => match self.context.menu {
-                Menu::Deposit => DepositState::new(revaultd).into(),
-                Menu::Home => ManagerHomeState::new(revaultd).into(),
-                Menu::Vaults => VaultsState::new(revaultd).into(),
+                Menu::Deposit => {
+                    DepositState::new(revaultd, self.context.converter.unit.denomination).into()
+                }
+                Menu::Home => ManagerHomeState::new(revaultd, read_only).into(),
+                Menu::Vaults => VaultsState::new(
+                    revaultd,
+                    self.fiat.clone(),
+                    self.context.converter.unit.denomination,
+                    self.config.labels.clone(),
+                    read_only,
+                )
+                .into(),
                 Menu::Network => ManagerNetworkState::new(revaultd).into(),
-                Menu::Send => ManagerSendState::new(revaultd).into(),
+                Menu::Send => ManagerSendState::new(
+                    revaultd,
+                    self.context.converter.unit.denomination,
+                    read_only,
+                )
+                .into(),
                 // Manager cannot delegate funds, the user is redirected to the home.
-                Menu::DelegateFunds => ManagerHomeState::new(revaultd).into(),
-                Menu::Settings => SettingsState::new(revaultd.config.clone()).into(),
+                Menu::DelegateFunds => ManagerHomeState::new(revaultd, read_only).into(),
+                // Unvault attempts are reviewed by the stakeholders.
+                Menu::Review => ManagerHomeState::new(revaultd, read_only).into(),
+                Menu::EmergencyTracking => EmergencyTrackingState::new(revaultd).into(),
+                Menu::Settings => SettingsState::new(
+                    revaultd,
+                    self.config.revaultd_config_path.clone(),
+                    self.fiat.clone(),
+                )
+                .into(),
+                Menu::Logs => LogsState::new(revaultd, self.supervisor.clone()).into(),
                 _ => unreachable!(),
             },
             Role::Stakeholder => match self.context.menu {
-                Menu::Deposit => StakeholderHomeState::new(revaultd).into(),
-                Menu::Home => StakeholderHomeState::new(revaultd).into(),
-                Menu::Vaults => VaultsState::new(revaultd).into(),
+                Menu::Deposit => StakeholderHomeState::new(revaultd, read_only).into(),
+                Menu::Home => StakeholderHomeState::new(revaultd, read_only).into(),
+                Menu::Vaults => VaultsState::new(
+                    revaultd,
+                    self.fiat.clone(),
+                    self.context.converter.unit.denomination,
+                    self.config.labels.clone(),
+                    read_only,
+                )
+                .into(),
                 Menu::Network => StakeholderNetworkState::new(revaultd).into(),
-                Menu::CreateVaults => StakeholderCreateVaultsState::new(revaultd).into(),
-                Menu::DelegateFunds => StakeholderDelegateFundsState::new(revaultd).into(),
-                Menu::Settings => SettingsState::new(revaultd.config.clone()).into(),
+                Menu::CreateVaults => StakeholderCreateVaultsState::new(revaultd, read_only).into(),
+                Menu::DelegateFunds => {
+                    StakeholderDelegateFundsState::new(revaultd, read_only).into()
+                }
+                Menu::Settings => SettingsState::new(
+                    revaultd,
+                    self.config.revaultd_config_path.clone(),
+                    self.fiat.clone(),
+                )
+                .into(),
+                Menu::Logs => LogsState::new(revaultd, self.supervisor.clone()).into(),
                 Menu::Emergency => EmergencyState::new(revaultd).into(),
+                Menu::Review => ReviewState::new(revaultd).into(),
+                Menu::EmergencyTracking => EmergencyTrackingState::new(revaultd).into(),
                 _ => unreachable!(),
             },
         };
@@ -64,55 +165,488 @@ impl App {
 
     /// After the synchronisation process, the UI displays the home panel to the user
     /// according to the role specified in the revaultd configuration.
-    fn on_synced(&mut self, revaultd: Arc<RevaultD>) -> Command<Message> {
-        let role = if revaultd.config.stakeholder_config.is_some() {
+    /// on_synced opens the app, syncing is the sync progress if the app is
+    /// opened in read-only mode before the end of the sync.
+    fn on_synced(&mut self, revaultd: Arc<RevaultD>, syncing: Option<f64>) -> Command<Message> {
+        // The user is both a manager and a stakholder, then role can be modified.
+        let edit_role = revaultd.config.stakeholder_config.is_some()
+            && revaultd.config.manager_config.is_some();
+
+        let role = if edit_role {
+            self.config
+                .preferences
+                .default_role
+                .unwrap_or(Role::Stakeholder)
+        } else if revaultd.config.stakeholder_config.is_some() {
             Role::Stakeholder
         } else {
             Role::Manager
         };
 
-        // The user is both a manager and a stakholder, then role can be modified.
-        let edit_role = revaultd.config.stakeholder_config.is_some()
-            && revaultd.config.manager_config.is_some();
+        let menu = self
+            .config
+            .preferences
+            .last_menu
+            .clone()
+            .filter(|menu| menu.restorable(role))
+            .unwrap_or(Menu::Home);
 
-        self.context = Context::new(
-            Converter::new(revaultd.network()),
+        // The fiat rate already fetched is kept until it is refreshed.
+        let mut converter = Converter::new(
             revaultd.network(),
-            edit_role,
-            role,
-            Menu::Home,
+            self.config.display_unit.unwrap_or_default(),
         );
+        converter.fiat = self.context.converter.fiat.take();
+        self.context = Context::new(converter, revaultd.network(), edit_role, role, menu.clone());
+        self.context.preferences = self.config.preferences.clone();
+        self.context.profiles = self.profiles.clone();
+        self.context.profile = self
+            .profiles
+            .iter()
+            .find(|p| p.path == self.config_path)
+            .cloned();
         self.context.network_up = true;
-        self.revaultd = Some(revaultd);
-        self.load_state(role, Menu::Home)
+        self.context.syncing = syncing;
+        self.context.health = NetworkHealth::new(&revaultd.config);
+        self.context.unvault_csv = revaultd.config.scripts_config.unvault_csv();
+        self.revaultd = Some(revaultd.clone());
+        Command::batch(vec![
+            self.load_state(role, menu),
+            self.load_fiat_rate(),
+            self.check_network(),
+            Command::perform(
+                emergency_in_progress(revaultd),
+                Message::EmergencyInProgress,
+            ),
+        ])
+    }
+
+    /// on_sync_progress records the sync progress in read-only mode. The read-only
+    /// mode ends once revaultd is synced, the current panel is reloaded with its actions.
+    fn on_sync_progress(&mut self, sync: f64) -> Command<Message> {
+        if self.context.syncing.is_none() {
+            return Command::none();
+        }
+        if (sync - 1.0_f64).abs() < f64::EPSILON {
+            self.context.syncing = None;
+            self.load_state(self.context.role, self.context.menu.to_owned())
+        } else {
+            self.context.syncing = Some(sync);
+            Command::none()
+        }
+    }
+
+    /// Writes the GUI configuration, the errors are only logged.
+    fn save_config(&mut self) {
+        self.menu_unsaved = false;
+        if let Err(e) = self.config.to_file(&self.config_path) {
+            tracing::warn!("Failed to save the configuration: {}", e);
+        }
+    }
+
+    /// check_network checks the network now, the next checks are sent
+    /// by the timer of the subscription.
+    fn check_network(&mut self) -> Command<Message> {
+        match &self.revaultd {
+            Some(revaultd) if !self.checking_network => {
+                self.checking_network = true;
+                Command::perform(check_network(revaultd.clone()), Message::NetworkChecked)
+            }
+            _ => Command::none(),
+        }
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        self.config
+            .preferences
+            .refresh_interval()
+            .unwrap_or(CHECK_INTERVAL)
+    }
+
+    fn on_preferences(&mut self, message: PreferencesMessage) -> Command<Message> {
+        let preferences = &mut self.config.preferences;
+        match message {
+            PreferencesMessage::Theme(theme) => preferences.theme = Some(theme),
+            PreferencesMessage::DefaultRole(role) => preferences.default_role = Some(role),
+            PreferencesMessage::WindowSize(size) => preferences.window_size = Some(size),
+            PreferencesMessage::RefreshInterval(secs) => {
+                preferences.refresh_interval_secs = Some(secs)
+            }
+            PreferencesMessage::NetworkNotifications(enabled) => {
+                let mut notifications = preferences.notifications();
+                notifications.network = enabled;
+                preferences.notifications = Some(notifications);
+            }
+            PreferencesMessage::EmergencyNotifications(enabled) => {
+                let mut notifications = preferences.notifications();
+                notifications.emergency = enabled;
+                preferences.notifications = Some(notifications);
+            }
+        }
+        self.context.preferences = self.config.preferences.clone();
+        self.save_config();
+        Command::none()
     }
 
-    pub fn new(config: Config) -> (App, Command<Message>) {
+    /// Fetches the fiat rate if the user configured a fiat source.
+    fn load_fiat_rate(&self) -> Command<Message> {
+        if let Some(fiat) = &self.fiat {
+            Command::perform(get_fiat_rate(fiat.clone()), Message::FiatRate)
+        } else {
+            Command::none()
+        }
+    }
+
+    pub fn new(config: Config, config_path: PathBuf) -> (App, Command<Message>) {
+        let supervisor = Supervisor::new(
+            config.revaultd_config_path.to_owned(),
+            config
+                .revaultd_path()
+                .unwrap_or_else(|| PathBuf::from("revaultd")),
+        );
         let state = ChargingState::new(
             config.revaultd_config_path.to_owned(),
-            config.revaultd_path.to_owned(),
+            if config.overrides.no_daemon_start {
+                None
+            } else {
+                Some(supervisor.clone())
+            },
         );
         let cmd = state.load();
+        let fiat = config
+            .fiat
+            .as_ref()
+            .and_then(|fiat| match FiatSource::from_config(fiat) {
+                Ok(source) => Some(source),
+                Err(e) => {
+                    tracing::warn!("Fiat valuation disabled: {}", e);
+                    None
+                }
+            });
+        let profiles = config_path
+            .parent()
+            .map(config::profiles)
+            .unwrap_or_default();
         (
             App {
                 config,
+                config_path,
                 state: std::boxed::Box::new(state),
                 revaultd: None,
                 context: Context::default(),
+                fiat,
+                profiles,
+                supervisor,
+                previous_supervisors: Vec::new(),
+                reconnecting: None,
+                reconnect_running: false,
+                retry_button: button::State::new(),
+                checking_network: false,
+                stopping: None,
+                menu_unsaved: false,
+                generation: 0,
             },
             cmd,
         )
     }
 
+    /// generation of the App, the messages of a previous generation must be dropped.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
-        self.state.subscription()
+        let mut subscriptions = vec![self.state.subscription()];
+        if self.revaultd.is_some() {
+            subscriptions
+                .push(iced::time::every(self.refresh_interval()).map(|_| Message::CheckNetwork));
+        }
+        if let Some(attempt) = self.reconnecting {
+            if !self.reconnect_running && attempt < RECONNECT_ATTEMPTS {
+                subscriptions
+                    .push(iced::time::every(reconnect_delay(attempt)).map(|_| Message::Reconnect));
+            }
+        }
+        if self.context.syncing.is_some() && self.revaultd.is_some() {
+            subscriptions.push(iced::time::every(SYNC_CHECK_INTERVAL).map(|_| Message::CheckSync));
+        }
+        if self.menu_unsaved {
+            subscriptions.push(iced::time::every(SAVE_MENU_DELAY).map(|_| Message::SaveMenu));
+        }
+        if self.stopping.is_some() {
+            subscriptions
+                .push(iced::time::every(STOP_CHECK_INTERVAL).map(|_| Message::CheckDaemonStopped));
+        }
+        if self.fiat.is_some() && self.revaultd.is_some() {
+            subscriptions
+                .push(iced::time::every(RATE_REFRESH_INTERVAL).map(|_| Message::RefreshFiatRate));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    /// on_connection_lost starts to reconnect in the background if a request
+    /// to revaultd failed with a transport error. The current state stays
+    /// displayed under a banner.
+    fn on_connection_lost(&mut self) -> Command<Message> {
+        if self.reconnecting.is_some()
+            || self.stopping.is_some()
+            || !self
+                .revaultd
+                .as_ref()
+                .map(|revaultd| revaultd.connection_lost())
+                .unwrap_or(false)
+        {
+            return Command::none();
+        }
+        tracing::warn!("Lost connection to revaultd, reconnecting");
+        self.context.network_up = false;
+        self.reconnecting = Some(0);
+        Command::none()
+    }
+
+    /// reconnect runs a reconnection attempt, the next ones are sent
+    /// by the timer of the subscription.
+    fn reconnect(&mut self) -> Command<Message> {
+        if self.reconnecting.is_none() || self.reconnect_running {
+            return Command::none();
+        }
+        self.reconnect_running = true;
+        Command::perform(
+            reconnect(
+                self.config.revaultd_config_path.clone(),
+                if self.config.overrides.no_daemon_start {
+                    None
+                } else {
+                    Some(self.supervisor.clone())
+                },
+            ),
+            Message::Reconnected,
+        )
     }
 
     pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        let cmd = self.on_message(message, clipboard);
+        Command::batch(vec![self.on_connection_lost(), cmd])
+    }
+
+    fn on_message(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
         match message {
-            Message::Synced(revaultd) => self.on_synced(revaultd),
+            Message::Reconnect => self.reconnect(),
+            Message::RetryReconnect => {
+                self.reconnecting = Some(0);
+                self.reconnect()
+            }
+            Message::Reconnected(res) => match res {
+                // The state is built again with the new connection, which
+                // encodes the requests for the version of the new revaultd.
+                Ok(revaultd) => {
+                    tracing::info!("Connection to revaultd restored");
+                    self.reconnecting = None;
+                    self.reconnect_running = false;
+                    self.context.network_up = true;
+                    self.revaultd = Some(revaultd);
+                    self.load_state(self.context.role, self.context.menu.to_owned())
+                }
+                // revaultd was replaced by an unsupported version, the charging
+                // state displays the incompatibility.
+                Err(Error::RevaultDError(RevaultDError::IncompatibleVersion(version))) => {
+                    tracing::error!("revaultd {} is not supported", version);
+                    self.reconnecting = None;
+                    self.reconnect_running = false;
+                    self.revaultd = None;
+                    // The supervisor is given to stop the revaultd started by the GUI.
+                    let state = ChargingState::new(
+                        self.config.revaultd_config_path.to_owned(),
+                        if self.config.overrides.no_daemon_start {
+                            None
+                        } else {
+                            Some(self.supervisor.clone())
+                        },
+                    );
+                    let cmd = state.load();
+                    self.state = state.into();
+                    cmd
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to reconnect to revaultd: {}", e);
+                    self.reconnect_running = false;
+                    self.reconnecting = self.reconnecting.map(|attempt| attempt + 1);
+                    if self.reconnecting == Some(RECONNECT_ATTEMPTS) {
+                        tracing::error!(
+                            "Failed to reconnect to revaultd after {} attempts",
+                            RECONNECT_ATTEMPTS
+                        );
+                    }
+                    Command::none()
+                }
+            },
+            Message::Synced(revaultd) => self.on_synced(revaultd, None),
+            Message::ReadOnly(revaultd, progress) => self.on_synced(revaultd, Some(progress)),
             Message::ChangeRole(role) => self.load_state(role, self.context.menu.to_owned()),
-            Message::Menu(menu) => self.load_state(self.context.role, menu),
+            Message::Menu(menu) => {
+                if menu.restorable(self.context.role)
+                    && self.config.preferences.last_menu.as_ref() != Some(&menu)
+                {
+                    self.config.preferences.last_menu = Some(menu.clone());
+                    self.context.preferences.last_menu = Some(menu.clone());
+                    self.menu_unsaved = true;
+                }
+                self.load_state(self.context.role, menu)
+            }
+            Message::SaveMenu => {
+                if self.menu_unsaved {
+                    self.save_config();
+                }
+                Command::none()
+            }
+            Message::Preferences(msg) => self.on_preferences(msg),
+            // The connection to the current revaultd is dropped, the new profile
+            // is loaded as if the GUI was started with it. The revaultd started
+            // for the previous profile is stopped with its supervisor.
+            Message::SwitchProfile(profile) => {
+                if profile.path == self.config_path {
+                    return Command::none();
+                }
+                if self.menu_unsaved {
+                    self.save_config();
+                }
+                match Config::from_file(&profile.path) {
+                    Ok(mut config) => {
+                        config.overrides = self.config.overrides.clone();
+                        let (mut app, cmd) = App::new(config, profile.path);
+                        // The messages of the commands still running for this
+                        // profile are dropped by the generation check.
+                        app.generation = self.generation + 1;
+                        // The revaultd of this profile is stopped without blocking the GUI.
+                        self.supervisor.stop();
+                        app.previous_supervisors = std::mem::take(&mut self.previous_supervisors);
+                        app.previous_supervisors.push(self.supervisor.clone());
+                        *self = app;
+                        cmd
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to load the profile {}: {}", profile, e);
+                        Command::none()
+                    }
+                }
+            }
+            Message::FiatCurrency(currency) => {
+                if let Some(fiat) = &mut self.config.fiat {
+                    fiat.currency = currency.clone();
+                }
+                if self.config.fiat.is_some() {
+                    self.save_config();
+                }
+                if let Some(fiat) = &mut self.fiat {
+                    fiat.currency = currency;
+                }
+                Command::batch(vec![
+                    self.load_state(self.context.role, self.context.menu.to_owned()),
+                    self.load_fiat_rate(),
+                ])
+            }
+            Message::AddressLabel(address, label) => {
+                if self.config.labels.get(&address) != Some(&label) {
+                    self.config.labels.insert(address, label);
+                    self.save_config();
+                }
+                Command::none()
+            }
+            Message::DisplayUnit(denomination) => {
+                self.context.converter.unit.denomination = denomination;
+                self.config.display_unit = Some(denomination);
+                self.save_config();
+                Command::none()
+            }
+            Message::CheckNetwork => self.check_network(),
+            Message::NetworkChecked(report) => {
+                self.checking_network = false;
+                let cmd = match &report.info {
+                    Ok(info) => self.on_sync_progress(info.sync),
+                    Err(_) => Command::none(),
+                };
+                self.context.health.record(report);
+                cmd
+            }
+            Message::CheckSync => match &self.revaultd {
+                Some(revaultd) if self.context.syncing.is_some() => {
+                    Command::perform(get_info(revaultd.clone()), Message::SyncChecked)
+                }
+                _ => Command::none(),
+            },
+            Message::SyncChecked(res) => match res {
+                Ok(info) => self.on_sync_progress(info.sync),
+                Err(e) => {
+                    tracing::warn!("Failed to check the sync progress: {}", e);
+                    Command::none()
+                }
+            },
+            // The emergency tracking is displayed at startup until the emergency
+            // transactions are confirmed.
+            Message::EmergencyInProgress(res) => match res {
+                Ok(true) if self.config.preferences.notifications().emergency => {
+                    self.load_state(self.context.role, Menu::EmergencyTracking)
+                }
+                Ok(_) => Command::none(),
+                Err(e) => {
+                    tracing::warn!("Failed to check the emergency status: {}", e);
+                    Command::none()
+                }
+            },
+            Message::RefreshFiatRate => self.load_fiat_rate(),
+            Message::FiatRate(res) => {
+                match res {
+                    Ok(rate) => self.context.converter.fiat = Some(rate),
+                    Err(e) => tracing::warn!("Failed to get fiat rate: {}", e),
+                }
+                Command::none()
+            }
+            Message::RestartDaemon => {
+                if let Some(revaultd) = &self.revaultd {
+                    return Command::perform(
+                        stop_daemon(revaultd.clone()),
+                        Message::DaemonStopping,
+                    );
+                }
+                Command::none()
+            }
+            Message::DaemonStopping(Ok(())) => {
+                self.stopping = Some(0);
+                Command::none()
+            }
+            Message::DaemonStopping(Err(e)) => self.state.update(Message::DaemonStopped(Err(e))),
+            Message::CheckDaemonStopped => match &self.revaultd {
+                Some(revaultd) if self.stopping.is_some() => Command::perform(
+                    check_daemon_stopped(revaultd.clone()),
+                    Message::DaemonStopped,
+                ),
+                _ => Command::none(),
+            },
+            Message::DaemonStopped(Err(e)) => match self.stopping {
+                Some(checks) if checks + 1 < STOP_CHECKS => {
+                    self.stopping = Some(checks + 1);
+                    Command::none()
+                }
+                _ => {
+                    self.stopping = None;
+                    self.state.update(Message::DaemonStopped(Err(e)))
+                }
+            },
+            // Once revaultd is stopped, the charging state starts it again with the
+            // new configuration and synchronizes the GUI.
+            Message::DaemonStopped(Ok(())) => {
+                self.stopping = None;
+                self.revaultd = None;
+                // The user asked for the restart, revaultd is started even with
+                // the --no-daemon-start option.
+                let state = ChargingState::new(
+                    self.config.revaultd_config_path.to_owned(),
+                    Some(self.supervisor.clone()),
+                );
+                let cmd = state.load();
+                self.state = state.into();
+                cmd
+            }
             Message::Clipboard(text)
             | Message::SpendTx(SpendTxMessage::Sign(SignMessage::Clipboard(text)))
             | Message::Vault(_, VaultMessage::Sign(SignMessage::Clipboard(text))) => {
@@ -124,7 +658,29 @@ impl App {
     }
 
     pub fn view(&mut self) -> Element<Message> {
-        let content = self.state.view(&self.context);
+        let mut content = self.state.view(&self.context);
+        if let Some(progress) = self.context.syncing {
+            content = Column::new()
+                .push(view::read_only_banner(progress))
+                .push(content)
+                .into();
+        }
+        if let Some(attempt) = self.reconnecting {
+            content = Column::new()
+                .push(view::reconnecting_banner(
+                    &mut self.retry_button,
+                    attempt,
+                    self.supervisor.status(),
+                    if attempt < RECONNECT_ATTEMPTS {
+                        None
+                    } else {
+                        Some(Message::RetryReconnect)
+                    },
+                ))
+                .push(content)
+                .into();
+        }
+        let content = view::themed(self.config.preferences.theme(), content);
         if let Some(true) = self.config.debug {
             return content.explain(Color::BLACK);
         }
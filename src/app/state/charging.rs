@@ -1,10 +1,12 @@
+use std::collections::VecDeque;
 use std::io::ErrorKind;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use iced::{Command, Element};
+use iced::{button, scrollable, Command, Element};
 
-use super::State;
+use super::{cmd::read_error_logs, State};
 use crate::app::{
     error::Error,
     message::Message,
@@ -12,43 +14,138 @@ use crate::app::{
 };
 use crate::revaultd::{
     config::{Config, ConfigError},
-    start_daemon, RevaultD, RevaultDError,
+    supervisor::{DaemonStatus, Supervisor, START_TIMEOUT},
+    GetInfoResponse, RevaultD, RevaultDError,
 };
 
+/// Number of lines of the revaultd log file displayed with an error.
+const ERROR_LOG_LINES: usize = 50;
+
+/// Number of sync progress samples used to estimate the remaining time,
+/// revaultd is polled once a second.
+const SYNC_SAMPLES: usize = 60;
+
 #[derive(Debug, Clone)]
 pub struct ChargingState {
     revaultd_config_path: PathBuf,
-    revaultd_path: Option<PathBuf>,
+    /// supervisor is None if revaultd must not be started when it is not running.
+    supervisor: Option<Supervisor>,
     revaultd: Option<Arc<RevaultD>>,
     step: ChargingStep,
+    /// end of the revaultd log file, read once an error occured.
+    file_logs: Vec<String>,
+    logs_scroll: scrollable::State,
+    read_only_button: button::State,
 }
 
 #[derive(Debug, Clone)]
 enum ChargingStep {
     Connecting,
     StartingDaemon,
-    Syncing { progress: f64 },
-    Error { error: String },
+    Syncing {
+        progress: SyncProgress,
+    },
+    /// stopped is true if the incompatible revaultd was started by the GUI,
+    /// which stopped it.
+    Incompatible {
+        version: String,
+        stopped: bool,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// SyncProgress keeps the last getinfo answers of revaultd during the sync
+/// to estimate the remaining time.
+#[derive(Debug, Clone, Default)]
+struct SyncProgress {
+    info: Option<GetInfoResponse>,
+    /// block height of the first answer.
+    start_height: Option<u64>,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl SyncProgress {
+    fn record(&mut self, at: Instant, info: GetInfoResponse) {
+        if self.start_height.is_none() {
+            self.start_height = Some(info.blockheight);
+        }
+        if self.samples.len() == SYNC_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, info.sync));
+        self.info = Some(info);
+    }
+
+    fn progress(&self) -> f64 {
+        self.info.as_ref().map(|info| info.sync).unwrap_or(0.0)
+    }
+
+    /// synced_blocks returns the number of blocks synced since the first answer.
+    fn synced_blocks(&self) -> u64 {
+        match (&self.info, self.start_height) {
+            (Some(info), Some(start)) => info.blockheight.saturating_sub(start),
+            _ => 0,
+        }
+    }
+
+    /// eta extrapolates the remaining time from the speed of the sync over
+    /// the recorded samples, it is None until the progress moved.
+    fn eta(&self) -> Option<Duration> {
+        let (first_at, first) = self.samples.front()?;
+        let (last_at, last) = self.samples.back()?;
+        if last <= first {
+            return None;
+        }
+        let elapsed = last_at.duration_since(*first_at).as_secs_f64();
+        Some(Duration::from_secs_f64(
+            elapsed * (1.0 - last).max(0.0) / (last - first),
+        ))
+    }
 }
 
 impl ChargingState {
-    pub fn new(revaultd_config_path: PathBuf, revaultd_path: Option<PathBuf>) -> Self {
+    pub fn new(revaultd_config_path: PathBuf, supervisor: Option<Supervisor>) -> Self {
         ChargingState {
             revaultd_config_path,
-            revaultd_path,
+            supervisor,
             revaultd: None,
             step: ChargingStep::Connecting,
+            file_logs: Vec::new(),
+            logs_scroll: scrollable::State::new(),
+            read_only_button: button::State::new(),
+        }
+    }
+
+    /// logs returns the output of the revaultd started by the GUI, or else
+    /// the end of the revaultd log file once an error occured.
+    fn logs(&self) -> Vec<String> {
+        let logs = self
+            .supervisor
+            .as_ref()
+            .map(|supervisor| supervisor.logs())
+            .unwrap_or_default();
+        if logs.is_empty() {
+            self.file_logs.clone()
+        } else {
+            logs
         }
     }
 
     fn on_connect(&mut self, res: Result<Arc<RevaultD>, Error>) -> Command<Message> {
         match res {
             Ok(revaultd) => {
-                self.step = ChargingStep::Syncing { progress: 0.0 };
+                self.step = ChargingStep::Syncing {
+                    progress: SyncProgress::default(),
+                };
                 self.revaultd = Some(revaultd.clone());
                 return Command::perform(sync(revaultd, false), Message::Syncing);
             }
             Err(e) => match e {
+                Error::RevaultDError(RevaultDError::IncompatibleVersion(version)) => {
+                    self.on_incompatible(version);
+                }
                 Error::ConfigError(ConfigError::NotFound) => {
                     self.step = ChargingStep::Error {
                         error: format!("config not found at path: {:?}", self.revaultd_config_path),
@@ -56,14 +153,14 @@ impl ChargingState {
                 }
                 Error::RevaultDError(RevaultDError::IOError(ErrorKind::ConnectionRefused))
                 | Error::RevaultDError(RevaultDError::IOError(ErrorKind::NotFound)) => {
-                    self.step = ChargingStep::StartingDaemon;
-                    return Command::perform(
-                        start_daemon_and_connect(
-                            self.revaultd_config_path.to_owned(),
-                            self.revaultd_path.to_owned(),
-                        ),
-                        Message::DaemonStarted,
-                    );
+                    if let Some(supervisor) = &self.supervisor {
+                        self.step = ChargingStep::StartingDaemon;
+                        return Command::perform(
+                            start_daemon_and_connect(supervisor.clone()),
+                            Message::DaemonStarted,
+                        );
+                    }
+                    return self.on_error(&"revaultd is not running and must not be started");
                 }
                 _ => return self.on_error(&e),
             },
@@ -74,35 +171,55 @@ impl ChargingState {
     fn on_daemon_started(&mut self, res: Result<Arc<RevaultD>, Error>) -> Command<Message> {
         match res {
             Ok(revaultd) => {
-                self.step = ChargingStep::Syncing { progress: 0.0 };
+                self.step = ChargingStep::Syncing {
+                    progress: SyncProgress::default(),
+                };
                 self.revaultd = Some(revaultd.clone());
                 Command::perform(sync(revaultd, false), Message::Syncing)
             }
+            Err(Error::RevaultDError(RevaultDError::IncompatibleVersion(version))) => {
+                self.on_incompatible(version);
+                Command::none()
+            }
             Err(e) => self.on_error(&e),
         }
     }
 
+    /// on_incompatible stops the revaultd started by the GUI, the GUI cannot use it.
+    fn on_incompatible(&mut self, version: String) {
+        let stopped = match &self.supervisor {
+            Some(supervisor) if supervisor.status() == DaemonStatus::Running => {
+                supervisor.stop();
+                true
+            }
+            _ => false,
+        };
+        self.step = ChargingStep::Incompatible { version, stopped };
+    }
+
     fn on_error(&mut self, e: &dyn std::fmt::Display) -> Command<Message> {
         self.step = ChargingStep::Error {
             error: format!("error: {}", e),
         };
-        Command::none()
+        Command::perform(
+            read_error_logs(self.revaultd_config_path.clone(), ERROR_LOG_LINES),
+            Message::ErrorLogs,
+        )
     }
 
-    #[allow(unused_variables, unused_assignments)]
-    fn on_sync(&mut self, res: Result<f64, RevaultDError>) -> Command<Message> {
-        match self.step {
-            ChargingStep::Syncing { mut progress } => {
+    fn on_sync(&mut self, res: Result<GetInfoResponse, RevaultDError>) -> Command<Message> {
+        match &mut self.step {
+            ChargingStep::Syncing { progress } => {
                 match res {
                     Err(e) => return self.on_error(&e),
-                    Ok(p) => {
-                        if (p - 1.0_f64).abs() < f64::EPSILON {
+                    Ok(info) => {
+                        if (info.sync - 1.0_f64).abs() < f64::EPSILON {
                             return Command::perform(
                                 synced(self.revaultd.as_ref().unwrap().clone()),
                                 Message::Synced,
                             );
                         } else {
-                            progress = p
+                            progress.record(Instant::now(), info)
                         }
                     }
                 };
@@ -114,6 +231,20 @@ impl ChargingState {
             _ => Command::none(),
         }
     }
+
+    /// on_read_only opens the app before the end of the sync, the app
+    /// leaves the read-only mode once revaultd is synced.
+    fn on_read_only(&self) -> Command<Message> {
+        match (&self.step, &self.revaultd) {
+            (ChargingStep::Syncing { progress }, Some(revaultd)) => {
+                let progress = progress.progress();
+                Command::perform(synced(revaultd.clone()), move |revaultd| {
+                    Message::ReadOnly(revaultd, progress)
+                })
+            }
+            _ => Command::none(),
+        }
+    }
 }
 
 impl State for ChargingState {
@@ -122,16 +253,34 @@ impl State for ChargingState {
             Message::Connected(res) => self.on_connect(res),
             Message::Syncing(res) => self.on_sync(res),
             Message::DaemonStarted(res) => self.on_daemon_started(res),
+            Message::OpenReadOnly => self.on_read_only(),
+            Message::ErrorLogs(lines) => {
+                self.file_logs = lines;
+                Command::none()
+            }
             _ => Command::none(),
         }
     }
 
     fn view(&mut self, _ctx: &Context) -> Element<Message> {
+        let logs = self.logs();
         match &mut self.step {
-            ChargingStep::StartingDaemon => charging_starting_daemon_view(),
+            ChargingStep::StartingDaemon => {
+                charging_starting_daemon_view(&mut self.logs_scroll, &logs)
+            }
             ChargingStep::Connecting => charging_connect_view(),
-            ChargingStep::Syncing { progress, .. } => charging_syncing_view(&progress),
-            ChargingStep::Error { error } => charging_error_view(&error),
+            ChargingStep::Syncing { progress } => charging_syncing_view(
+                progress.info.as_ref(),
+                progress.synced_blocks(),
+                progress.eta(),
+                &mut self.read_only_button,
+            ),
+            ChargingStep::Incompatible { version, stopped } => {
+                charging_incompatible_view(version, *stopped)
+            }
+            ChargingStep::Error { error } => {
+                charging_error_view(&error, &mut self.logs_scroll, &logs)
+            }
         }
     }
 
@@ -160,36 +309,52 @@ async fn connect(revaultd_config_path: PathBuf) -> Result<Arc<RevaultD>, Error>
     Ok(Arc::new(revaultd))
 }
 
-async fn sync(revaultd: Arc<RevaultD>, sleep: bool) -> Result<f64, RevaultDError> {
+async fn sync(revaultd: Arc<RevaultD>, sleep: bool) -> Result<GetInfoResponse, RevaultDError> {
     if sleep {
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        std::thread::sleep(Duration::from_secs(1));
     }
-    let resp = revaultd.get_info()?;
-    Ok(resp.sync)
+    revaultd.get_info()
 }
 
-async fn start_daemon_and_connect(
-    revaultd_config_path: PathBuf,
-    revaultd_path: Option<PathBuf>,
-) -> Result<Arc<RevaultD>, Error> {
-    let revaultd_path = revaultd_path.unwrap_or_else(|| PathBuf::from("revaultd"));
-
-    start_daemon(&revaultd_config_path, &revaultd_path).await?;
+/// start_daemon_and_connect starts revaultd as a child of the GUI and connects
+/// to it once it answers.
+async fn start_daemon_and_connect(supervisor: Supervisor) -> Result<Arc<RevaultD>, Error> {
+    supervisor.start()?;
+    let revaultd = supervisor.wait_ready(START_TIMEOUT)?;
+    Ok(Arc::new(revaultd))
+}
 
-    let cfg = Config::from_file(&revaultd_config_path)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn try_connect_to_revault(cfg: &Config, i: i32) -> Result<Arc<RevaultD>, Error> {
-        std::thread::sleep(std::time::Duration::from_secs(3));
-        RevaultD::new(cfg).map(Arc::new).map_err(|e| {
-            tracing::warn!("Failed to connect to revaultd ({} more try): {}", i, e);
-            e.into()
-        })
+    fn info(blockheight: u64, sync: f64) -> GetInfoResponse {
+        GetInfoResponse {
+            blockheight,
+            network: "regtest".to_string(),
+            sync,
+            version: "0.1.0".to_string(),
+        }
     }
 
-    try_connect_to_revault(&cfg, 5)
-        .or_else(|_| try_connect_to_revault(&cfg, 4))
-        .or_else(|_| try_connect_to_revault(&cfg, 3))
-        .or_else(|_| try_connect_to_revault(&cfg, 2))
-        .or_else(|_| try_connect_to_revault(&cfg, 1))
-        .or_else(|_| try_connect_to_revault(&cfg, 0))
+    #[test]
+    fn sync_eta() {
+        let start = Instant::now();
+        let mut progress = SyncProgress::default();
+        progress.record(start, info(100, 0.25));
+        assert_eq!(progress.eta(), None);
+
+        progress.record(start + Duration::from_secs(10), info(150, 0.25));
+        assert_eq!(progress.eta(), None);
+
+        progress.record(start + Duration::from_secs(20), info(200, 0.5));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(40)));
+        assert_eq!(progress.synced_blocks(), 100);
+
+        for i in 0..SYNC_SAMPLES as u64 {
+            progress.record(start + Duration::from_secs(30 + i), info(300, 0.75));
+        }
+        assert_eq!(progress.samples.len(), SYNC_SAMPLES);
+        assert_eq!(progress.eta(), None);
+    }
 }
@@ -1,20 +1,75 @@
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
 use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::app::error::Error;
+use crate::app::state::GAP_LIMIT;
+use crate::conversion::{Converter, Denomination, FiatSource};
+use crate::export::{self, AmountFormat, DepositSlip, Exporter};
+use crate::revault::{self, TransactionKind};
 use crate::revaultd::{
+    config::{write_atomic, Config},
     model::{
         RevocationTransactions, SpendTransaction, SpendTx, SpendTxStatus, UnvaultTransaction,
         Vault, VaultStatus, VaultTransactions,
     },
-    RevaultD, RevaultDError,
+    supervisor::{DaemonStatus, Supervisor, START_TIMEOUT},
+    GetInfoResponse, RevaultD, RevaultDError,
 };
 
-/// retrieves a bitcoin address for deposit.
+/// retrieves a bitcoin address for deposit with its derivation index, once checked
+/// that the deposit descriptor derives the same address at this index.
+/// A compromised daemon cannot redirect the deposits to an address
+/// not controlled by the stakeholders.
+/// Older revaultd versions do not report the index: the address is the first
+/// unused one, so it is searched up to the gap limit after the highest
+/// derivation index of the vaults known by the daemon.
 pub async fn get_deposit_address(
     revaultd: Arc<RevaultD>,
-) -> Result<bitcoin::Address, RevaultDError> {
-    revaultd.get_deposit_address().map(|res| res.address)
+) -> Result<(u32, bitcoin::Address), RevaultDError> {
+    let res = revaultd.get_deposit_address()?;
+    let scripts = &revaultd.config.scripts_config;
+    let network = revaultd.network();
+    let index = match res.index {
+        Some(index) => Some(index)
+            .filter(|i| scripts.deposit_address(*i, network).as_ref() == Some(&res.address)),
+        None => {
+            let vaults = revaultd.list_vaults(None, None)?.vaults;
+            let limit = vaults
+                .iter()
+                .map(|vault| vault.derivation_index + 1)
+                .max()
+                .unwrap_or(0)
+                .saturating_add(GAP_LIMIT);
+            scripts.deposit_address_index(&res.address, network, limit)
+        }
+    };
+    match index {
+        Some(index) => Ok((index, res.address)),
+        None => Err(RevaultDError::UnexpectedError(format!(
+            "the deposit address {} is not derived from the deposit descriptor",
+            res.address
+        ))),
+    }
+}
+
+/// derives the deposit addresses at the given indexes.
+pub async fn derive_deposit_addresses(
+    revaultd: Arc<RevaultD>,
+    indexes: std::ops::Range<u32>,
+) -> Vec<(u32, bitcoin::Address)> {
+    revaultd
+        .config
+        .scripts_config
+        .deposit_addresses(indexes, revaultd.network())
+        .unwrap_or_default()
+}
+
+pub async fn get_info(revaultd: Arc<RevaultD>) -> Result<GetInfoResponse, RevaultDError> {
+    revaultd.get_info()
 }
 
 pub async fn get_blockheight(revaultd: Arc<RevaultD>) -> Result<u64, RevaultDError> {
@@ -31,6 +86,14 @@ pub async fn list_vaults(
         .map(|res| res.vaults)
 }
 
+/// list the vaults with one of the given statuses.
+pub async fn list_vaults_with_statuses(
+    revaultd: Arc<RevaultD>,
+    statuses: Vec<VaultStatus>,
+) -> Result<Vec<Vault>, RevaultDError> {
+    list_vaults(revaultd, Some(&statuses), None).await
+}
+
 pub async fn get_onchain_txs(
     revaultd: Arc<RevaultD>,
     outpoint: String,
@@ -45,11 +108,25 @@ pub async fn get_onchain_txs(
     Ok(list.onchain_transactions[0].to_owned())
 }
 
+/// list the onchain transactions of the vaults with the given outpoints.
+pub async fn list_onchain_txs(
+    revaultd: Arc<RevaultD>,
+    outpoints: Vec<String>,
+) -> Result<Vec<VaultTransactions>, RevaultDError> {
+    revaultd
+        .list_onchain_transactions(Some(outpoints))
+        .map(|res| res.onchain_transactions)
+}
+
+/// get the revocation transactions of the vault and its unvault transaction,
+/// the error of the unvault transaction is kept to fail the revocation checks.
 pub async fn get_revocation_txs(
     revaultd: Arc<RevaultD>,
     outpoint: String,
-) -> Result<RevocationTransactions, RevaultDError> {
-    revaultd.get_revocation_txs(&outpoint)
+) -> Result<(RevocationTransactions, Result<Psbt, RevaultDError>), RevaultDError> {
+    let txs = revaultd.get_revocation_txs(&outpoint)?;
+    let unvault_tx = revaultd.get_unvault_tx(&outpoint).map(|res| res.unvault_tx);
+    Ok((txs, unvault_tx))
 }
 
 pub async fn set_revocation_txs(
@@ -112,6 +189,296 @@ pub async fn revault(revaultd: Arc<RevaultD>, outpoint: String) -> Result<(), Re
     revaultd.revault(&outpoint)
 }
 
+/// path of a file of the gui in the revaultd datadir of the network.
+fn data_path(revaultd: &RevaultD, name: &str) -> Result<PathBuf, Error> {
+    let mut path = revaultd.config.data_dir()?;
+    path.push(revaultd.network().to_string());
+    path.push(name);
+    Ok(path)
+}
+
+/// path of the file listing the unvaults acknowledged by the stakeholder,
+/// one vault outpoint per line.
+fn acknowledged_unvaults_path(revaultd: &RevaultD) -> Result<PathBuf, Error> {
+    data_path(revaultd, "acknowledged_unvaults")
+}
+
+/// lists the outpoints of the vaults whose unvault was acknowledged by the stakeholder.
+pub async fn list_acknowledged_unvaults(revaultd: Arc<RevaultD>) -> Result<Vec<String>, Error> {
+    let path = acknowledged_unvaults_path(&revaultd)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| Error::UnexpectedError(format!("Failed to read {:?}: {}", path, e)))?;
+    Ok(content
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// marks the unvault of the vault as acknowledged and returns the acknowledged outpoints.
+pub async fn acknowledge_unvault(
+    revaultd: Arc<RevaultD>,
+    outpoint: String,
+) -> Result<Vec<String>, Error> {
+    let mut outpoints = list_acknowledged_unvaults(revaultd.clone()).await?;
+    if !outpoints.contains(&outpoint) {
+        outpoints.push(outpoint);
+    }
+    let path = acknowledged_unvaults_path(&revaultd)?;
+    let mut content = outpoints.join("\n");
+    content.push('\n');
+    write_atomic(&path, content.as_bytes())
+        .map_err(|e| Error::UnexpectedError(format!("Failed to write {:?}: {}", path, e)))?;
+    Ok(outpoints)
+}
+
+/// returns the fees of the emergency transaction of each vault, the emergency
+/// transaction of an unvaulting vault is the unvault emergency transaction.
+/// A vault failing does not prevent the computation of the fees of the others.
+pub async fn get_emergency_fees(
+    revaultd: Arc<RevaultD>,
+    vaults: Vec<(String, TransactionKind)>,
+) -> Vec<(String, Result<u64, RevaultDError>)> {
+    vaults
+        .into_iter()
+        .map(|(outpoint, kind)| {
+            let fee = revaultd.get_revocation_txs(&outpoint).and_then(|txs| {
+                let psbt = if kind == TransactionKind::EmergencyUnvault {
+                    &txs.emergency_unvault_tx
+                } else {
+                    &txs.emergency_tx
+                };
+                revault::fees(psbt).ok_or_else(|| {
+                    RevaultDError::UnexpectedError(
+                        "Missing input value of the emergency transaction".to_string(),
+                    )
+                })
+            });
+            (outpoint, fee)
+        })
+        .collect()
+}
+
+/// lists the vaults in an emergency status with their onchain transactions.
+pub async fn track_emergency(
+    revaultd: Arc<RevaultD>,
+) -> Result<(Vec<Vault>, Vec<VaultTransactions>), RevaultDError> {
+    let vaults = revaultd
+        .list_vaults(Some(&VaultStatus::EMERGENCY), None)?
+        .vaults;
+    if vaults.is_empty() {
+        return Ok((vaults, Vec::new()));
+    }
+    let txs = revaultd
+        .list_onchain_transactions(Some(vaults.iter().map(|v| v.outpoint()).collect()))?
+        .onchain_transactions;
+    Ok((vaults, txs))
+}
+
+/// returns true if the emergency transaction of a vault is waiting to be confirmed.
+pub async fn emergency_in_progress(revaultd: Arc<RevaultD>) -> Result<bool, RevaultDError> {
+    revaultd
+        .list_vaults(
+            Some(&[
+                VaultStatus::EmergencyVaulting,
+                VaultStatus::UnvaultEmergencyVaulting,
+            ]),
+            None,
+        )
+        .map(|res| !res.vaults.is_empty())
+}
+
 pub async fn emergency(revaultd: Arc<RevaultD>) -> Result<(), RevaultDError> {
     revaultd.emergency()
 }
+
+/// exports the given vaults and their onchain transactions
+/// in the exports directory of the revaultd datadir.
+pub async fn export_vaults(
+    revaultd: Arc<RevaultD>,
+    vaults: Vec<Vault>,
+    format: AmountFormat,
+    fiat: Option<FiatSource>,
+) -> Result<Vec<PathBuf>, Error> {
+    let txs = if vaults.is_empty() {
+        Vec::new()
+    } else {
+        revaultd
+            .list_onchain_transactions(Some(vaults.iter().map(|v| v.outpoint()).collect()))?
+            .onchain_transactions
+    };
+
+    let exporter = Exporter::new(
+        Converter::new(revaultd.network(), Denomination::Bitcoin),
+        format,
+        fiat,
+    );
+    let records = exporter.records(&vaults, &txs);
+
+    let mut dir = revaultd.config.data_dir()?;
+    dir.push("exports");
+    export::export(&dir, &records).map_err(|e| e.into())
+}
+
+/// exports the deposit slip in the exports directory of the revaultd datadir.
+pub async fn export_deposit_slip(
+    revaultd: Arc<RevaultD>,
+    slip: DepositSlip,
+) -> Result<PathBuf, Error> {
+    let mut dir = revaultd.config.data_dir()?;
+    dir.push("exports");
+    export::export_deposit_slip(&dir, &slip).map_err(|e| e.into())
+}
+
+/// writes the revaultd configuration to the file, the previous one is kept as a backup.
+pub async fn save_revaultd_config(path: PathBuf, config: Config) -> Result<(), Error> {
+    config.to_file(&path).map_err(|e| e.into())
+}
+
+/// asks revaultd to stop, the GUI then checks that it does not answer anymore.
+pub async fn stop_daemon(revaultd: Arc<RevaultD>) -> Result<(), RevaultDError> {
+    match revaultd.stop() {
+        // revaultd may close the connection before answering.
+        Ok(()) | Err(RevaultDError::NoAnswerError) | Err(RevaultDError::IOError(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// returns an error if revaultd still answers.
+pub async fn check_daemon_stopped(revaultd: Arc<RevaultD>) -> Result<(), RevaultDError> {
+    match revaultd.get_info() {
+        Ok(_) => Err(RevaultDError::UnexpectedError(
+            "revaultd is still running".to_string(),
+        )),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Number of lines read from the end of the revaultd log file.
+const LOG_TAIL_LINES: usize = 1000;
+
+/// reads the last lines of the revaultd log file. There is no file
+/// if revaultd never ran as a daemon.
+pub async fn read_logs(revaultd: Arc<RevaultD>) -> Result<Vec<String>, Error> {
+    let path = revaultd.config.log_path()?;
+    tail_log_file(&path, LOG_TAIL_LINES)
+}
+
+/// Size of the blocks read from the end of the log file.
+const LOG_TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Maximum number of bytes read from the end of the log file.
+const LOG_TAIL_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// returns the last lines of the log file, none if the file does not exist.
+/// The file is read backward by blocks until it has enough lines.
+pub fn tail_log_file(path: &Path, count: usize) -> Result<Vec<String>, Error> {
+    let read_error = |e: std::io::Error| {
+        Error::UnexpectedError(format!("Failed to read {}: {}", path.to_string_lossy(), e))
+    };
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(read_error(e)),
+    };
+    let len = file.metadata().map_err(read_error)?.len();
+    let mut start = len;
+    let mut content: Vec<u8> = Vec::new();
+    while start > 0
+        && len - start < LOG_TAIL_MAX_BYTES
+        && content.iter().filter(|b| **b == b'\n').count() <= count
+    {
+        let size = LOG_TAIL_CHUNK.min(start);
+        start -= size;
+        file.seek(SeekFrom::Start(start)).map_err(read_error)?;
+        let mut chunk = vec![0; size as usize];
+        file.read_exact(&mut chunk).map_err(read_error)?;
+        chunk.extend(content);
+        content = chunk;
+    }
+    let content = String::from_utf8_lossy(&content);
+    let mut lines: Vec<&str> = content.lines().collect();
+    // The first line is cut if the file was not read from its start.
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    Ok(lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// returns the last lines of the log file of the revaultd configuration,
+/// none if they cannot be read.
+pub async fn read_error_logs(revaultd_config_path: PathBuf, count: usize) -> Vec<String> {
+    Config::from_file(&revaultd_config_path)
+        .ok()
+        .and_then(|config| config.log_path().ok())
+        .and_then(|path| tail_log_file(&path, count).ok())
+        .unwrap_or_default()
+}
+
+/// gathers what the support needs to investigate an issue: the versions,
+/// the getinfo result, the redacted configuration and the logs.
+pub async fn diagnostics(revaultd: Arc<RevaultD>, logs: Vec<String>) -> Result<String, Error> {
+    let info = match revaultd.get_info() {
+        Ok(info) => format!(
+            "version: {}\nnetwork: {}\nblockheight: {}\nsync: {}",
+            info.version, info.network, info.blockheight, info.sync
+        ),
+        Err(e) => format!("getinfo failed: {}", e),
+    };
+    Ok(format!(
+        "# revault-gui {}\n\n\
+         # revaultd getinfo\n{}\n\n\
+         # revaultd configuration\n{}\n\
+         # logs\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        info,
+        revaultd.config.redacted()?,
+        logs.iter()
+            .map(|line| revaultd.config.redact_line(line))
+            .collect::<Vec<String>>()
+            .join("\n")
+    ))
+}
+
+/// Delay before a reconnection attempt, multiplied by the number of the attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const RECONNECT_DELAY_MAX: Duration = Duration::from_secs(30);
+
+/// Number of reconnection attempts after which the user has to retry.
+pub const RECONNECT_ATTEMPTS: u32 = 10;
+
+/// returns the delay before the given reconnection attempt.
+pub fn reconnect_delay(attempt: u32) -> Duration {
+    RECONNECT_DELAY
+        .checked_mul(attempt + 1)
+        .map(|delay| delay.min(RECONNECT_DELAY_MAX))
+        .unwrap_or(RECONNECT_DELAY_MAX)
+}
+
+/// connects again to revaultd after the connection was lost, the attempts are
+/// spaced by the timer of the app. If a supervisor is given, revaultd is started
+/// again unless it is being restarted by the supervisor or it failed too many times.
+pub async fn reconnect(
+    revaultd_config_path: PathBuf,
+    supervisor: Option<Supervisor>,
+) -> Result<Arc<RevaultD>, Error> {
+    let config = Config::from_file(&revaultd_config_path)?;
+    let err = match RevaultD::new(&config) {
+        Ok(revaultd) => return Ok(Arc::new(revaultd)),
+        Err(e @ RevaultDError::IOError(_)) => e,
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(supervisor) = supervisor {
+        if let DaemonStatus::NotStarted | DaemonStatus::Stopped = supervisor.status() {
+            supervisor.start()?;
+            return Ok(Arc::new(supervisor.wait_ready(START_TIMEOUT)?));
+        }
+    }
+    Err(err.into())
+}
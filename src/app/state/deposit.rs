@@ -1,11 +1,19 @@
+use std::collections::HashSet;
 use std::convert::From;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use iced::{Command, Element};
 
-use super::{cmd::get_deposit_address, State};
+use super::{
+    cmd::{derive_deposit_addresses, export_deposit_slip, get_deposit_address, list_vaults},
+    State,
+};
 
-use crate::revaultd::RevaultD;
+use crate::{
+    conversion::Denomination, export::DepositSlip, revault::bip21_uri, revaultd::RevaultD,
+    ui::component::form,
+};
 
 use crate::app::{
     error::Error,
@@ -13,55 +21,282 @@ use crate::app::{
     view::{Context, DepositView},
 };
 
+/// Number of consecutive unused deposit addresses after which funds sent
+/// to the next addresses may not be detected: revaultd watches the addresses
+/// up to this gap after the last funded one.
+pub const GAP_LIMIT: u32 = 20;
+
+/// Number of unused addresses before the gap limit from which the user is warned.
+pub const GAP_LIMIT_WARNING: u32 = 5;
+
+/// DepositAddress is a deposit address derived from the deposit descriptor.
+#[derive(Debug, Clone)]
+pub struct DepositAddress {
+    pub derivation_index: u32,
+    pub address: bitcoin::Address,
+    /// funded is true if a vault was created with the address.
+    pub funded: bool,
+}
+
 /// DepositState handles the deposit process.
-/// It gets a deposit address from the revault daemon and
-/// give it to its view in order to be rendered.
+/// It displays the address issued by the revault daemon, or one of the next
+/// unused addresses it watches, and lists the previous addresses derived from
+/// the deposit descriptor.
 #[derive(Debug)]
 pub struct DepositState {
     revaultd: Arc<RevaultD>,
-    address: Option<bitcoin::Address>,
+    /// address returned by the revault daemon with its derivation index,
+    /// checked against the deposit descriptor.
+    revaultd_address: Option<(u32, bitcoin::Address)>,
+    /// number of addresses generated by the user after the address of the daemon.
+    generated: u32,
+    /// addresses derived from the deposit descriptor from the index 0,
+    /// derived in a command and only extended.
+    derived: Vec<(u32, bitcoin::Address)>,
+    /// derivation indexes of the vaults.
+    funded: HashSet<u32>,
+    addresses: Vec<DepositAddress>,
+    /// derivation paths of the stakeholders keys for the displayed address.
+    derivation_paths: Vec<String>,
+
+    /// amount and label of the BIP21 payment request.
+    amount: form::Value<String>,
+    label: String,
+    denomination: Denomination,
+    /// path of the last exported deposit slip.
+    exported: Option<PathBuf>,
     warning: Option<Error>,
 
-    /// The deposit view is rendering the address.
+    /// The deposit view is rendering the addresses.
     view: DepositView,
 }
 
 impl DepositState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, denomination: Denomination) -> Self {
         DepositState {
             revaultd,
             view: DepositView::new(),
             warning: None,
-            address: None,
+            revaultd_address: None,
+            generated: 0,
+            derived: Vec::new(),
+            funded: HashSet::new(),
+            addresses: Vec::new(),
+            derivation_paths: Vec::new(),
+            amount: form::Value::default(),
+            label: String::new(),
+            denomination,
+            exported: None,
         }
     }
+
+    /// current_index is the derivation index of the displayed address.
+    fn current_index(&self) -> Option<u32> {
+        self.revaultd_address
+            .as_ref()
+            .map(|(index, _)| index + self.generated)
+    }
+
+    /// derive derives the addresses missing up to the displayed one.
+    fn derive(&self) -> Command<Message> {
+        match self.current_index() {
+            Some(index) if self.derived.len() as u32 <= index => Command::perform(
+                derive_deposit_addresses(
+                    self.revaultd.clone(),
+                    self.derived.len() as u32..index + 1,
+                ),
+                Message::DepositAddresses,
+            ),
+            _ => Command::none(),
+        }
+    }
+
+    /// update_addresses lists the derived addresses before the displayed one.
+    fn update_addresses(&mut self) {
+        let index = match self.current_index() {
+            Some(index) => index,
+            None => return,
+        };
+        let funded = &self.funded;
+        self.addresses = self
+            .derived
+            .iter()
+            .take_while(|(i, _)| *i < index)
+            .map(|(i, address)| DepositAddress {
+                derivation_index: *i,
+                address: address.clone(),
+                funded: funded.contains(i),
+            })
+            .collect();
+        self.derivation_paths = self
+            .revaultd
+            .config
+            .scripts_config
+            .deposit_derivation_paths(index);
+
+        self.load_qr_code();
+    }
+
+    /// gap returns the number of addresses displayed after the last funded one.
+    fn gap(&self) -> u32 {
+        let first_unused = self.funded.iter().max().map(|index| index + 1).unwrap_or(0);
+        self.current_index()
+            .map(|index| (index + 1).saturating_sub(first_unused))
+            .unwrap_or(0)
+    }
+
+    /// load_qr_code loads the payment request of the current address in the view.
+    fn load_qr_code(&mut self) {
+        // Payment request is loaded directly in the view in order to cache the created qrcode.
+        if let Some(uri) = self.uri() {
+            self.view.load(&uri);
+        }
+    }
+
+    /// uri returns the BIP21 payment request of the current address,
+    /// an invalid amount is ignored.
+    fn uri(&self) -> Option<String> {
+        let amount = self.denomination.parse(&self.amount.value).ok();
+        self.current_address()
+            .map(|(_, address)| bip21_uri(address, amount, Some(&self.label)))
+    }
+
+    /// current_address is the displayed address with its derivation index: the
+    /// address of the daemon or the last address generated by the user.
+    fn current_address(&self) -> Option<(u32, &bitcoin::Address)> {
+        if self.generated == 0 {
+            return self
+                .revaultd_address
+                .as_ref()
+                .map(|(index, address)| (*index, address));
+        }
+        let index = self.current_index()?;
+        self.derived
+            .get(index as usize)
+            .map(|(index, address)| (*index, address))
+    }
 }
 
 impl State for DepositState {
     fn update(&mut self, message: Message) -> Command<Message> {
-        if let Message::DepositAddress(res) = message {
-            match res {
+        match message {
+            Message::DepositAddress(res) => match res {
                 Ok(address) => {
-                    // Address is loaded directly in the view in order to cache the created qrcode.
-                    self.view.load(&address);
-                    self.address = Some(address);
+                    self.revaultd_address = Some(address);
+                    self.update_addresses();
+                    return self.derive();
                 }
                 Err(e) => self.warning = Some(Error::RevaultDError(e)),
+            },
+            Message::DepositAddresses(addresses) => {
+                // Addresses derived for a previous range are ignored.
+                if addresses.first().map(|(i, _)| *i as usize) == Some(self.derived.len()) {
+                    self.derived.extend(addresses);
+                    self.update_addresses();
+                }
             }
-        }
+            Message::NewDepositAddress => {
+                if self.current_address().is_some() && self.gap() < GAP_LIMIT {
+                    self.generated += 1;
+                    return self.derive();
+                }
+            }
+            Message::Vaults(res) => match res {
+                Ok(vaults) => {
+                    self.funded = vaults.iter().map(|v| v.derivation_index).collect();
+                    self.update_addresses();
+                }
+                Err(e) => self.warning = Some(Error::RevaultDError(e)),
+            },
+            Message::DepositAmountEdited(amount) => {
+                self.amount.valid = amount.is_empty() || self.denomination.parse(&amount).is_ok();
+                self.amount.value = amount;
+                self.load_qr_code();
+            }
+            Message::DepositLabelEdited(label) => {
+                self.label = label;
+                self.load_qr_code();
+            }
+            Message::ExportDepositSlip => {
+                let checksum = self
+                    .revaultd
+                    .config
+                    .scripts_config
+                    .deposit_descriptor_checksum();
+                if let (Some((index, address)), Some(uri), Some(checksum)) =
+                    (self.current_address(), self.uri(), checksum)
+                {
+                    let amount = self
+                        .denomination
+                        .parse(&self.amount.value)
+                        .ok()
+                        .map(|amount| {
+                            format!("{} {}", self.denomination.format(amount), self.denomination)
+                        });
+                    let slip = DepositSlip {
+                        address: address.to_string(),
+                        derivation_index: index,
+                        uri,
+                        network: self.revaultd.network(),
+                        descriptor_checksum: checksum,
+                        amount,
+                        label: Some(self.label.clone()).filter(|l| !l.is_empty()),
+                    };
+                    let mut cmds = Vec::new();
+                    if let Some(label) = slip.label.clone() {
+                        let address = slip.address.clone();
+                        cmds.push(Command::perform(
+                            async move { (address, label) },
+                            |(a, l)| Message::AddressLabel(a, l),
+                        ));
+                    }
+                    cmds.push(Command::perform(
+                        export_deposit_slip(self.revaultd.clone(), slip),
+                        Message::DepositSlipExported,
+                    ));
+                    return Command::batch(cmds);
+                }
+            }
+            Message::DepositSlipExported(res) => match res {
+                Ok(path) => self.exported = Some(path),
+                Err(e) => self.warning = Some(e),
+            },
+            _ => {}
+        };
         Command::none()
     }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
-        self.view
-            .view(ctx, self.warning.as_ref(), self.address.as_ref())
+        let gap = self.gap();
+        let address = self
+            .current_address()
+            .map(|(index, address)| (index, address.clone()));
+        let uri = self.uri();
+        self.view.view(
+            ctx,
+            self.warning.as_ref(),
+            address.as_ref().map(|(index, address)| (*index, address)),
+            uri.as_deref(),
+            &self.amount,
+            &self.label,
+            self.exported.as_ref(),
+            &self.addresses,
+            &self.derivation_paths,
+            gap,
+        )
     }
 
     fn load(&self) -> Command<Message> {
-        Command::perform(
-            get_deposit_address(self.revaultd.clone()),
-            Message::DepositAddress,
-        )
+        Command::batch(vec![
+            Command::perform(
+                get_deposit_address(self.revaultd.clone()),
+                Message::DepositAddress,
+            ),
+            Command::perform(
+                list_vaults(self.revaultd.clone(), None, None),
+                Message::Vaults,
+            ),
+        ])
     }
 }
 
@@ -1,26 +1,57 @@
+use std::collections::HashMap;
 use std::convert::From;
 use std::sync::Arc;
+use std::time::Duration;
 
-use iced::{Command, Element};
+use iced::{Command, Element, Subscription};
 
-use super::{cmd::list_vaults, State};
+use super::{
+    cmd::{get_emergency_fees, list_vaults, track_emergency},
+    State,
+};
 
-use crate::revaultd::{model::VaultStatus, RevaultD};
+use crate::revault::TransactionKind;
+use crate::revaultd::{
+    model::{self, VaultStatus, VaultTransactions},
+    RevaultD,
+};
 
 use crate::app::{
     error::Error,
     message::Message,
     state::cmd,
-    view::{Context, EmergencyView},
+    view::{Context, EmergencyTrackingView, EmergencyView},
 };
 
+/// Phrase the user must type to confirm the broadcast of the emergency transactions.
+pub const EMERGENCY_CONFIRMATION: &str = "SEND ALL FUNDS TO THE EMERGENCY ADDRESS";
+
+/// Interval between two reloads of the vaults after the broadcast.
+const BROADCAST_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Statuses of the vaults swept by the emergency command.
+const EMERGENCY_STATUSES: [VaultStatus; 5] = [
+    VaultStatus::Secured,
+    VaultStatus::Active,
+    VaultStatus::Activating,
+    VaultStatus::Unvaulting,
+    VaultStatus::Unvaulted,
+];
+
 #[derive(Debug)]
 pub struct EmergencyState {
     revaultd: Arc<RevaultD>,
     view: EmergencyView,
 
-    vaults_number: usize,
-    funds_amount: u64,
+    vaults: Vec<EmergencyVault>,
+    /// emergency_address is the destination of the emergency transactions.
+    emergency_address: Option<String>,
+    /// fees of the emergency transaction of each vault.
+    fees: HashMap<String, u64>,
+    /// errors of the vaults whose fees could not be computed.
+    fee_errors: HashMap<String, String>,
+    /// confirmation is the phrase typed by the user.
+    confirmation: String,
 
     warning: Option<Error>,
 
@@ -32,31 +63,110 @@ pub struct EmergencyState {
 
 impl EmergencyState {
     pub fn new(revaultd: Arc<RevaultD>) -> Self {
+        let emergency_address = revaultd
+            .config
+            .stakeholder_config
+            .as_ref()
+            .map(|cfg| cfg.emergency_address.clone());
         EmergencyState {
             revaultd,
             view: EmergencyView::new(),
-            vaults_number: 0,
-            funds_amount: 0,
+            vaults: Vec::new(),
+            emergency_address,
+            fees: HashMap::new(),
+            fee_errors: HashMap::new(),
+            confirmation: String::new(),
             warning: None,
             loading: true,
             processing: false,
             success: false,
         }
     }
+
+    fn update_vaults(&mut self, vaults: Vec<model::Vault>) -> Command<Message> {
+        self.loading = false;
+        if self.success {
+            // The vaults are reloaded after the broadcast to follow their statuses.
+            for vault in vaults {
+                if let Some(v) = self
+                    .vaults
+                    .iter_mut()
+                    .find(|v| v.vault.outpoint() == vault.outpoint())
+                {
+                    v.vault = vault;
+                }
+            }
+            return Command::none();
+        }
+
+        self.vaults = vaults.into_iter().map(EmergencyVault::new).collect();
+        if self.vaults.is_empty() {
+            return Command::none();
+        }
+        Command::perform(
+            get_emergency_fees(
+                self.revaultd.clone(),
+                self.vaults
+                    .iter()
+                    .map(|v| (v.vault.outpoint(), v.kind))
+                    .collect(),
+            ),
+            Message::EmergencyFees,
+        )
+    }
+
+    /// total_fees returns None until the fees of all the vaults are computed or failed,
+    /// the total is then partial if the fees of some vaults failed.
+    fn total_fees(&self) -> Option<u64> {
+        if self.fees.len() + self.fee_errors.len() < self.vaults.len() {
+            return None;
+        }
+        Some(self.fees.values().sum())
+    }
+
+    /// broadcasted returns true if the emergency transactions of all the vaults
+    /// are broadcasted.
+    fn broadcasted(&self) -> bool {
+        self.vaults.iter().all(|v| v.broadcasted())
+    }
+
+    fn reload_vaults(&self) -> Command<Message> {
+        Command::perform(
+            list_vaults(
+                self.revaultd.clone(),
+                None,
+                Some(self.vaults.iter().map(|v| v.vault.outpoint()).collect()),
+            ),
+            Message::Vaults,
+        )
+    }
 }
 
 impl State for EmergencyState {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::Vaults(res) => match res {
-                Ok(vaults) => {
-                    self.loading = false;
-                    self.vaults_number = vaults.len();
-                    self.funds_amount = vaults.into_iter().fold(0, |acc, vault| acc + vault.amount);
-                }
+                Ok(vaults) => return self.update_vaults(vaults),
                 Err(e) => self.warning = Error::from(e).into(),
             },
+            Message::EmergencyFees(fees) => {
+                for (outpoint, res) in fees {
+                    match res {
+                        Ok(fee) => {
+                            self.fees.insert(outpoint, fee);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to compute the fees of {}: {}", outpoint, e);
+                            self.fee_errors.insert(outpoint, e.to_string());
+                        }
+                    }
+                }
+            }
+            Message::EmergencyConfirmation(phrase) => self.confirmation = phrase,
             Message::Emergency => {
+                if self.confirmation != EMERGENCY_CONFIRMATION {
+                    return Command::none();
+                }
                 self.processing = true;
                 self.warning = None;
                 return Command::perform(
@@ -70,18 +180,25 @@ impl State for EmergencyState {
                     self.warning = Some(Error::RevaultDError(e));
                 } else {
                     self.success = true;
+                    return self.reload_vaults();
                 }
             }
+            Message::RefreshEmergency => return self.reload_vaults(),
             _ => {}
         };
         Command::none()
     }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let total_fees = self.total_fees();
         self.view.view(
             ctx,
-            self.vaults_number,
-            self.funds_amount,
+            &self.vaults,
+            self.emergency_address.as_ref(),
+            &self.fees,
+            &self.fee_errors,
+            total_fees,
+            &self.confirmation,
             self.warning.as_ref(),
             self.loading,
             self.processing,
@@ -89,19 +206,17 @@ impl State for EmergencyState {
         )
     }
 
+    /// The vaults are reloaded until revaultd broadcasted all the emergency transactions.
+    fn subscription(&self) -> Subscription<Message> {
+        if !self.success || self.broadcasted() {
+            return Subscription::none();
+        }
+        iced::time::every(BROADCAST_REFRESH_INTERVAL).map(|_| Message::RefreshEmergency)
+    }
+
     fn load(&self) -> Command<Message> {
         Command::batch(vec![Command::perform(
-            list_vaults(
-                self.revaultd.clone(),
-                Some(&[
-                    VaultStatus::Secured,
-                    VaultStatus::Active,
-                    VaultStatus::Activating,
-                    VaultStatus::Unvaulting,
-                    VaultStatus::Unvaulted,
-                ]),
-                None,
-            ),
+            list_vaults(self.revaultd.clone(), Some(&EMERGENCY_STATUSES), None),
             Message::Vaults,
         )])
     }
@@ -112,3 +227,134 @@ impl From<EmergencyState> for Box<dyn State> {
         Box::new(s)
     }
 }
+
+/// EmergencyVault is a vault swept by the emergency command.
+#[derive(Debug, Clone)]
+pub struct EmergencyVault {
+    pub vault: model::Vault,
+    /// kind is the emergency transaction broadcasted for the vault.
+    pub kind: TransactionKind,
+}
+
+impl EmergencyVault {
+    fn new(vault: model::Vault) -> Self {
+        // Funds of an unvaulting vault are in the unvault output,
+        // the unvault emergency transaction sweeps them.
+        let kind = match vault.status {
+            VaultStatus::Unvaulting | VaultStatus::Unvaulted => TransactionKind::EmergencyUnvault,
+            _ => TransactionKind::Emergency,
+        };
+        Self { vault, kind }
+    }
+
+    /// broadcasted returns true if revaultd broadcasted the emergency transaction.
+    pub fn broadcasted(&self) -> bool {
+        VaultStatus::EMERGENCY.contains(&self.vault.status)
+    }
+}
+
+/// Interval between two updates of the emergency tracking.
+const TRACKING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// EmergencyTrackingState follows the vaults in an emergency status until
+/// their emergency transactions are confirmed.
+#[derive(Debug)]
+pub struct EmergencyTrackingState {
+    revaultd: Arc<RevaultD>,
+    view: EmergencyTrackingView,
+
+    vaults: Vec<model::Vault>,
+    /// onchain transactions of the vaults by vault outpoint.
+    txs: HashMap<String, VaultTransactions>,
+
+    warning: Option<Error>,
+    /// loading is true until Message::EmergencyTracked is handled
+    loading: bool,
+    /// tracking is true while an update of the tracking is running.
+    tracking: bool,
+}
+
+impl EmergencyTrackingState {
+    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+        EmergencyTrackingState {
+            revaultd,
+            view: EmergencyTrackingView::new(),
+            vaults: Vec::new(),
+            txs: HashMap::new(),
+            warning: None,
+            loading: true,
+            tracking: true,
+        }
+    }
+
+    /// in_progress returns true if an emergency transaction is not confirmed yet.
+    fn in_progress(&self) -> bool {
+        self.vaults.iter().any(|v| {
+            v.status == VaultStatus::EmergencyVaulting
+                || v.status == VaultStatus::UnvaultEmergencyVaulting
+        })
+    }
+}
+
+impl State for EmergencyTrackingState {
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::TrackEmergency if !self.tracking => {
+                self.tracking = true;
+                return self.load();
+            }
+            Message::EmergencyTracked(res) => {
+                self.loading = false;
+                self.tracking = false;
+                match res {
+                    Ok((vaults, txs)) => {
+                        self.warning = None;
+                        self.vaults = vaults;
+                        self.txs = txs
+                            .into_iter()
+                            .map(|tx| (tx.vault_outpoint.clone(), tx))
+                            .collect();
+                    }
+                    Err(e) => self.warning = Error::from(e).into(),
+                }
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    /// Keep following the vaults until all the emergency transactions are confirmed,
+    /// or retry if revaultd could not be reached. The timer stops when the user
+    /// leaves the tracking.
+    fn subscription(&self) -> Subscription<Message> {
+        if self.loading || !(self.in_progress() || self.warning.is_some()) {
+            return Subscription::none();
+        }
+        iced::time::every(TRACKING_INTERVAL).map(|_| Message::TrackEmergency)
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let in_progress = self.in_progress();
+        self.view.view(
+            ctx,
+            &self.vaults,
+            &self.txs,
+            in_progress,
+            self.warning.as_ref(),
+            self.loading,
+        )
+    }
+
+    fn load(&self) -> Command<Message> {
+        Command::perform(
+            track_emergency(self.revaultd.clone()),
+            Message::EmergencyTracked,
+        )
+    }
+}
+
+impl From<EmergencyTrackingState> for Box<dyn State> {
+    fn from(s: EmergencyTrackingState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
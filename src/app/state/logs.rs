@@ -0,0 +1,195 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use iced::{Command, Element, Subscription};
+
+use super::{
+    cmd::{diagnostics, read_logs},
+    State,
+};
+
+use crate::{
+    app::{
+        error::Error,
+        message::{LogLevel, LogsMessage, Message},
+        view::{Context, LogsView},
+    },
+    revaultd::{supervisor::Supervisor, RevaultD},
+};
+
+/// Number of log lines put in the diagnostics bundle.
+const DIAGNOSTICS_LINES: usize = 200;
+
+/// Interval between two reads of the logs while the menu is displayed.
+const LOGS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// LogsState displays the revaultd log file and the output of the revaultd
+/// child process, filtered by level and searched by text.
+#[derive(Debug)]
+pub struct LogsState {
+    revaultd: Arc<RevaultD>,
+    supervisor: Supervisor,
+    /// lines of the log file followed by the output of the child process,
+    /// read by the last load.
+    lines: Vec<String>,
+    /// lines matching the level and the search, computed again only when
+    /// the lines or the filter change.
+    filtered: Vec<String>,
+    /// loading is true while the log file is read.
+    loading: bool,
+    level: LogLevel,
+    search: String,
+    warning: Option<Error>,
+    /// copied is true once the diagnostics bundle is in the clipboard.
+    copied: bool,
+    view: LogsView,
+}
+
+impl LogsState {
+    pub fn new(revaultd: Arc<RevaultD>, supervisor: Supervisor) -> Self {
+        LogsState {
+            revaultd,
+            supervisor,
+            lines: Vec::new(),
+            filtered: Vec::new(),
+            loading: false,
+            level: LogLevel::Trace,
+            search: String::new(),
+            warning: None,
+            copied: false,
+            view: LogsView::new(),
+        }
+    }
+
+    fn refresh_filtered(&mut self) {
+        self.filtered = filter(&self.lines, self.level, &self.search);
+    }
+
+    fn on_logs_message(&mut self, message: LogsMessage) -> Command<Message> {
+        match message {
+            LogsMessage::Loaded(res) => {
+                self.loading = false;
+                match res {
+                    Ok(mut lines) => {
+                        self.warning = None;
+                        lines.extend(self.supervisor.logs());
+                        self.lines = lines;
+                        self.refresh_filtered();
+                    }
+                    Err(e) => self.warning = Some(e),
+                }
+            }
+            LogsMessage::Refresh => {
+                if !self.loading {
+                    self.loading = true;
+                    return self.load();
+                }
+            }
+            LogsMessage::Level(level) => {
+                self.level = level;
+                self.refresh_filtered();
+            }
+            LogsMessage::Search(search) => {
+                self.search = search;
+                self.refresh_filtered();
+            }
+            LogsMessage::CopyDiagnostics => {
+                let lines = &self.lines;
+                let logs = lines[lines.len().saturating_sub(DIAGNOSTICS_LINES)..].to_vec();
+                return Command::perform(diagnostics(self.revaultd.clone(), logs), |res| {
+                    Message::Logs(LogsMessage::Diagnostics(res))
+                });
+            }
+            LogsMessage::Diagnostics(res) => match res {
+                Ok(bundle) => {
+                    self.copied = true;
+                    return Command::perform(async move { bundle }, Message::Clipboard);
+                }
+                Err(e) => self.warning = Some(e),
+            },
+        }
+        Command::none()
+    }
+}
+
+impl State for LogsState {
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Logs(msg) => self.on_logs_message(msg),
+            _ => Command::none(),
+        }
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        self.view.view(
+            ctx,
+            self.warning.as_ref(),
+            &self.filtered,
+            self.level,
+            &self.search,
+            self.copied,
+        )
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(LOGS_REFRESH_INTERVAL).map(|_| Message::Logs(LogsMessage::Refresh))
+    }
+
+    fn load(&self) -> Command<Message> {
+        Command::perform(read_logs(self.revaultd.clone()), |res| {
+            Message::Logs(LogsMessage::Loaded(res))
+        })
+    }
+}
+
+impl From<LogsState> for Box<dyn State> {
+    fn from(s: LogsState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
+
+/// filter keeps the lines at the level or more severe which contain the search,
+/// ignoring the case. Lines without level, like the continuation of a message,
+/// are kept.
+fn filter(lines: &[String], level: LogLevel, search: &str) -> Vec<String> {
+    let search = search.to_lowercase();
+    lines
+        .iter()
+        .filter(|line| LogLevel::of(line).map(|l| l <= level).unwrap_or(true))
+        .filter(|line| search.is_empty() || line.to_lowercase().contains(&search))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_logs() {
+        let lines: Vec<String> = vec![
+            "[2021-06-01][10:00:00][revaultd][INFO] Starting revaultd",
+            "[2021-06-01][10:00:01][revaultd][DEBUG] Polling bitcoind",
+            "[2021-06-01][10:00:02][revaultd][ERROR] Bitcoind connection lost",
+            "thread 'main' panicked",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        assert_eq!(filter(&lines, LogLevel::Trace, "").len(), 4);
+        assert_eq!(
+            filter(&lines, LogLevel::Info, ""),
+            vec![lines[0].clone(), lines[2].clone(), lines[3].clone()]
+        );
+        assert_eq!(
+            filter(&lines, LogLevel::Error, "bitcoind"),
+            vec![lines[2].clone()]
+        );
+        assert!(filter(&lines, LogLevel::Trace, "coordinator").is_empty());
+        assert_eq!(
+            LogLevel::of(" 2021-06-01T10:00:00Z  WARN revault_gui: slow"),
+            Some(LogLevel::Warn)
+        );
+    }
+}
@@ -4,10 +4,13 @@ use std::convert::From;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use iced::{Command, Element};
+use iced::{Column, Command, Element};
 
 use super::{
-    cmd::{get_blockheight, get_spend_tx, list_spend_txs, list_vaults, update_spend_tx},
+    cmd::{
+        get_blockheight, get_spend_tx, list_onchain_txs, list_spend_txs, list_vaults,
+        update_spend_tx,
+    },
     vault::{Vault, VaultListItem},
     State,
 };
@@ -17,7 +20,8 @@ use crate::revaultd::{
     RevaultD,
 };
 
-use crate::revault::TransactionKind;
+use crate::conversion::Denomination;
+use crate::revault::{TransactionKind, UnvaultTimelock};
 use crate::ui::component::form;
 
 use crate::app::{
@@ -29,7 +33,10 @@ use crate::app::{
         ManagerSelectInputsView, ManagerSelectOutputsView, ManagerSendOutputView,
         ManagerSendWelcomeView, ManagerSignView, ManagerSpendTransactionCreatedView,
     },
-    view::{vault::VaultListItemView, Context, ManagerHomeView, ManagerNetworkView},
+    view::{
+        vault::{unvault_timelock, VaultListItemView},
+        Context, ManagerHomeView, ManagerNetworkView,
+    },
 };
 
 #[derive(Debug)]
@@ -43,8 +50,13 @@ pub struct ManagerHomeState {
     warning: Option<Error>,
 
     moving_vaults: Vec<VaultListItem<VaultListItemView>>,
+    /// height of the unvault transaction of the unvaulting and unvaulted vaults,
+    /// None if the unvault transaction is not confirmed.
+    unvault_heights: HashMap<String, Option<u64>>,
     spendable_outpoints: HashMap<String, u64>,
     selected_vault: Option<Vault>,
+    /// read_only is true while revaultd syncs, the vault actions are disabled.
+    read_only: bool,
 
     spend_txs: Vec<model::SpendTx>,
     spend_txs_item: Vec<SpendTransactionListItem>,
@@ -54,15 +66,17 @@ pub struct ManagerHomeState {
 }
 
 impl ManagerHomeState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, read_only: bool) -> Self {
         ManagerHomeState {
             revaultd,
+            read_only,
             active_funds: 0,
             inactive_funds: 0,
             view: ManagerHomeView::new(),
             blockheight: 0,
             spendable_outpoints: HashMap::new(),
             moving_vaults: Vec::new(),
+            unvault_heights: HashMap::new(),
             warning: None,
             selected_vault: None,
             spend_txs: Vec::new(),
@@ -129,7 +143,8 @@ impl ManagerHomeState {
             .iter()
             .any(|item| item.psbt.global.unsigned_tx.txid() == psbt.global.unsigned_tx.txid())
         {
-            let selected_spend_tx = SpendTransactionState::new(self.revaultd.clone(), psbt);
+            let selected_spend_tx =
+                SpendTransactionState::new(self.revaultd.clone(), psbt, self.read_only);
             let cmd = selected_spend_tx.load();
             self.selected_spend_tx = Some(selected_spend_tx);
             return cmd;
@@ -137,7 +152,7 @@ impl ManagerHomeState {
         Command::none()
     }
 
-    pub fn update_vaults(&mut self, vaults: Vec<model::Vault>) {
+    pub fn update_vaults(&mut self, vaults: Vec<model::Vault>) -> Command<Message> {
         let (active_funds, inactive_funds) =
             vaults.iter().fold((0, 0), |acc, vault| match vault.status {
                 VaultStatus::Active => (acc.0 + vault.amount, acc.1),
@@ -179,6 +194,33 @@ impl ManagerHomeState {
 
         // The spendable outpoints changed, let's update the spend txs
         self.update_spend_txs(self.spend_txs.clone());
+
+        // The unvault transactions heights are required to display the timelocks.
+        let unvaulting: Vec<String> = self
+            .moving_vaults
+            .iter()
+            .filter(|vlt| is_unvaulting(&vlt.vault))
+            .map(|vlt| vlt.vault.outpoint())
+            .collect();
+        if unvaulting.is_empty() {
+            return Command::none();
+        }
+        Command::perform(
+            list_onchain_txs(self.revaultd.clone(), unvaulting),
+            Message::VaultsOnChainTransactions,
+        )
+    }
+
+    pub fn update_unvault_heights(&mut self, txs: Vec<model::VaultTransactions>) {
+        self.unvault_heights = txs
+            .into_iter()
+            .map(|tx| {
+                (
+                    tx.vault_outpoint,
+                    tx.unvault.and_then(|unvault| unvault.blockheight),
+                )
+            })
+            .collect();
     }
 
     pub fn on_vault_select(&mut self, outpoint: String) -> Command<Message> {
@@ -194,7 +236,7 @@ impl ManagerHomeState {
             .iter()
             .find(|vlt| vlt.vault.outpoint() == outpoint)
         {
-            let selected_vault = Vault::new(selected.vault.clone());
+            let selected_vault = Vault::new(selected.vault.clone(), self.read_only);
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd.map(move |msg| Message::Vault(outpoint.clone(), msg));
@@ -219,7 +261,11 @@ impl State for ManagerHomeState {
                 Err(e) => self.warning = Error::from(e).into(),
             },
             Message::Vaults(res) => match res {
-                Ok(vaults) => self.update_vaults(vaults),
+                Ok(vaults) => return self.update_vaults(vaults),
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::VaultsOnChainTransactions(res) => match res {
+                Ok(txs) => self.update_unvault_heights(txs),
                 Err(e) => self.warning = Error::from(e).into(),
             },
             Message::Vault(outpoint, VaultMessage::Select) => {
@@ -256,6 +302,28 @@ impl State for ManagerHomeState {
             return tx.view(ctx);
         }
 
+        let unvault_heights = &self.unvault_heights;
+        let moving_vaults = self
+            .moving_vaults
+            .iter_mut()
+            .map(|v| {
+                let timelock = match (ctx.unvault_csv, unvault_heights.get(&v.vault.outpoint())) {
+                    (Some(csv), Some(height)) if is_unvaulting(&v.vault) => {
+                        Some(UnvaultTimelock::new(csv, *height))
+                    }
+                    _ => None,
+                };
+                let item = v.view(ctx);
+                match timelock {
+                    Some(timelock) => Column::new()
+                        .push(item)
+                        .push(unvault_timelock(ctx, &timelock).padding(10))
+                        .into(),
+                    None => item,
+                }
+            })
+            .collect();
+
         self.view.view(
             ctx,
             self.warning.as_ref(),
@@ -263,7 +331,7 @@ impl State for ManagerHomeState {
                 .iter_mut()
                 .map(|tx| tx.view(ctx).map(Message::SpendTx))
                 .collect(),
-            self.moving_vaults.iter_mut().map(|v| v.view(ctx)).collect(),
+            moving_vaults,
             self.active_funds,
             self.inactive_funds,
         )
@@ -287,6 +355,12 @@ impl State for ManagerHomeState {
     }
 }
 
+/// is_unvaulting returns true if the vault can still be canceled before the
+/// unvault timelock expires.
+fn is_unvaulting(vault: &model::Vault) -> bool {
+    vault.status == VaultStatus::Unvaulting || vault.status == VaultStatus::Unvaulted
+}
+
 impl From<ManagerHomeState> for Box<dyn State> {
     fn from(s: ManagerHomeState) -> Box<dyn State> {
         Box::new(s)
@@ -295,54 +369,61 @@ impl From<ManagerHomeState> for Box<dyn State> {
 
 pub enum ManagerSendState {
     SendTransactionDetail(SpendTransactionState),
-    ImportSendTransaction(ManagerImportSendTransactionState),
-    CreateSendTransaction(ManagerCreateSendTransactionState),
+    ImportSendTransaction(ManagerImportSendTransactionState, bool),
+    CreateSendTransaction(ManagerCreateSendTransactionState, bool),
 }
 
 impl ManagerSendState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
-        Self::CreateSendTransaction(ManagerCreateSendTransactionState::new(revaultd))
+    /// read_only is true while revaultd syncs, it is passed to the detail of
+    /// the imported spend transaction.
+    pub fn new(revaultd: Arc<RevaultD>, denomination: Denomination, read_only: bool) -> Self {
+        Self::CreateSendTransaction(
+            ManagerCreateSendTransactionState::new(revaultd, denomination),
+            read_only,
+        )
     }
 }
 
 impl State for ManagerSendState {
     fn update(&mut self, message: Message) -> Command<Message> {
         match self {
-            Self::CreateSendTransaction(state) => match message {
+            Self::CreateSendTransaction(state, read_only) => match message {
                 Message::SpendTx(SpendTxMessage::Import) => {
                     *self = ManagerSendState::ImportSendTransaction(
                         ManagerImportSendTransactionState::new(state.revaultd.clone()),
+                        *read_only,
                     );
                     self.load()
                 }
                 _ => state.update(message),
             },
-            Self::ImportSendTransaction(state) => match message {
-                Message::SpendTx(SpendTxMessage::Select(psbt)) => {
-                    *self = ManagerSendState::SendTransactionDetail(SpendTransactionState::new(
-                        state.revaultd.clone(),
-                        psbt,
-                    ));
-                    self.load()
+            Self::ImportSendTransaction(state, read_only) => {
+                match message {
+                    Message::SpendTx(SpendTxMessage::Select(psbt)) => {
+                        *self = ManagerSendState::SendTransactionDetail(
+                            SpendTransactionState::new(state.revaultd.clone(), psbt, *read_only),
+                        );
+                        self.load()
+                    }
+                    _ => state.update(message),
                 }
-                _ => state.update(message),
-            },
+            }
             Self::SendTransactionDetail(state) => state.update(message),
         }
     }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
         match self {
-            Self::CreateSendTransaction(state) => state.view(ctx),
-            Self::ImportSendTransaction(state) => state.view(ctx),
+            Self::CreateSendTransaction(state, _) => state.view(ctx),
+            Self::ImportSendTransaction(state, _) => state.view(ctx),
             Self::SendTransactionDetail(state) => state.view(ctx),
         }
     }
 
     fn load(&self) -> Command<Message> {
         match self {
-            Self::CreateSendTransaction(state) => state.load(),
-            Self::ImportSendTransaction(state) => state.load(),
+            Self::CreateSendTransaction(state, _) => state.load(),
+            Self::ImportSendTransaction(state, _) => state.load(),
             Self::SendTransactionDetail(state) => state.load(),
         }
     }
@@ -444,22 +525,25 @@ pub struct ManagerCreateSendTransactionState {
     psbt: Option<(Psbt, u32)>,
     processing: bool,
     valid_feerate: bool,
+    /// denomination of the output amounts written by the user.
+    denomination: Denomination,
 
     step: ManagerSendStep,
 }
 
 impl ManagerCreateSendTransactionState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, denomination: Denomination) -> Self {
         Self {
             revaultd,
             step: ManagerSendStep::WelcomeUser(ManagerSendWelcomeView::new()),
             warning: None,
             vaults: Vec::new(),
-            outputs: vec![ManagerSendOutput::new()],
+            outputs: vec![ManagerSendOutput::new(denomination)],
             feerate: None,
             psbt: None,
             processing: false,
             valid_feerate: false,
+            denomination,
         }
     }
 
@@ -618,7 +702,7 @@ impl State for ManagerCreateSendTransactionState {
                     _ => ManagerSendStep::SelectOutputs(ManagerSelectOutputsView::new()),
                 }
             }
-            Message::AddRecipient => self.outputs.push(ManagerSendOutput::new()),
+            Message::AddRecipient => self.outputs.push(ManagerSendOutput::new(self.denomination)),
             Message::Recipient(i, RecipientMessage::Delete) => {
                 self.outputs.remove(i);
             }
@@ -651,7 +735,7 @@ impl State for ManagerCreateSendTransactionState {
                     self.outputs
                         .iter_mut()
                         .enumerate()
-                        .map(|(i, v)| v.view().map(move |msg| Message::Recipient(i, msg)))
+                        .map(|(i, v)| v.view(ctx).map(move |msg| Message::Recipient(i, msg)))
                         .collect(),
                     valid,
                 )
@@ -708,15 +792,17 @@ impl From<ManagerSendState> for Box<dyn State> {
 struct ManagerSendOutput {
     address: form::Value<String>,
     amount: form::Value<String>,
+    denomination: Denomination,
 
     view: ManagerSendOutputView,
 }
 
 impl ManagerSendOutput {
-    fn new() -> Self {
+    fn new(denomination: Denomination) -> Self {
         Self {
             address: form::Value::default(),
             amount: form::Value::default(),
+            denomination,
             view: ManagerSendOutputView::new(),
         }
     }
@@ -728,17 +814,18 @@ impl ManagerSendOutput {
             ));
         }
 
-        let amount =
-            bitcoin::Amount::from_str_in(&self.amount.value, bitcoin::Denomination::Bitcoin)
-                .map_err(|_| Error::UnexpectedError("cannot parse output amount".to_string()))?;
+        let amount = self
+            .denomination
+            .parse(&self.amount.value)
+            .map_err(|e| Error::UnexpectedError(format!("cannot parse output amount: {}", e)))?;
 
-        if amount.as_sat() == 0 {
+        if amount == 0 {
             return Err(Error::UnexpectedError(
                 "Amount should be non-zero".to_string(),
             ));
         }
 
-        Ok(amount.as_sat())
+        Ok(amount)
     }
 
     fn valid(&self) -> bool {
@@ -769,8 +856,8 @@ impl ManagerSendOutput {
         };
     }
 
-    fn view(&mut self) -> Element<RecipientMessage> {
-        self.view.view(&self.address, &self.amount)
+    fn view(&mut self, ctx: &Context) -> Element<RecipientMessage> {
+        self.view.view(ctx, &self.address, &self.amount)
     }
 }
 
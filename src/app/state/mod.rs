@@ -2,7 +2,9 @@ pub mod charging;
 mod cmd;
 mod deposit;
 mod emergency;
+mod logs;
 pub mod manager;
+mod review;
 mod settings;
 mod sign;
 mod spend_transaction;
@@ -13,16 +15,24 @@ mod vaults;
 use iced::{Command, Element, Subscription};
 
 pub use charging::ChargingState;
-pub use deposit::DepositState;
-pub use emergency::EmergencyState;
+pub use cmd::{
+    check_daemon_stopped, emergency_in_progress, export_vaults, get_info, reconnect,
+    reconnect_delay, stop_daemon, RECONNECT_ATTEMPTS,
+};
+pub use deposit::{DepositAddress, DepositState, GAP_LIMIT, GAP_LIMIT_WARNING};
+pub use emergency::{
+    EmergencyState, EmergencyTrackingState, EmergencyVault, EMERGENCY_CONFIRMATION,
+};
+pub use logs::LogsState;
 pub use manager::{ManagerHomeState, ManagerNetworkState, ManagerSendState};
-pub use settings::SettingsState;
+pub use review::ReviewState;
+pub use settings::{SettingsForm, SettingsState};
 pub use spend_transaction::{SpendTransactionListItem, SpendTransactionState};
 pub use stakeholder::{
     StakeholderCreateVaultsState, StakeholderDelegateFundsState, StakeholderHomeState,
     StakeholderNetworkState,
 };
-pub use vaults::VaultsState;
+pub use vaults::{VaultFilter, VaultsState};
 
 use super::{message::Message, view::Context};
 
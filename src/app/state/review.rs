@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::convert::From;
+use std::sync::Arc;
+
+use iced::{Command, Element};
+
+use super::{
+    cmd::{
+        acknowledge_unvault, list_acknowledged_unvaults, list_onchain_txs, list_spend_txs,
+        list_vaults, revault,
+    },
+    State,
+};
+
+use crate::{
+    app::{
+        error::Error,
+        message::{Message, VaultMessage},
+        view::{review::ReviewItemView, Context, ReviewView},
+    },
+    revault::UnvaultTimelock,
+    revaultd::{
+        model::{self, VaultStatus},
+        RevaultD,
+    },
+};
+
+/// ReviewState lists the unvault attempts for the stakeholder to review.
+/// Each unvaulting vault is displayed with the spend transaction it is paying for,
+/// the stakeholder can revault it or acknowledge it.
+#[derive(Debug)]
+pub struct ReviewState {
+    revaultd: Arc<RevaultD>,
+    view: ReviewView,
+
+    items: Vec<ReviewItem>,
+    spend_txs: Vec<model::SpendTx>,
+    /// height of the unvault transaction of the vaults,
+    /// None if the unvault transaction is not confirmed.
+    unvault_heights: HashMap<String, Option<u64>>,
+    /// outpoints of the vaults whose unvault was acknowledged by the stakeholder.
+    acknowledged: Vec<String>,
+
+    warning: Option<Error>,
+    /// loading is true until Message::Vaults is handled
+    loading: bool,
+}
+
+impl ReviewState {
+    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+        ReviewState {
+            revaultd,
+            view: ReviewView::new(),
+            items: Vec::new(),
+            spend_txs: Vec::new(),
+            unvault_heights: HashMap::new(),
+            acknowledged: Vec::new(),
+            warning: None,
+            loading: true,
+        }
+    }
+
+    pub fn update_vaults(&mut self, vaults: Vec<model::Vault>) -> Command<Message> {
+        self.loading = false;
+        self.items = vaults.into_iter().map(ReviewItem::new).collect();
+        if self.items.is_empty() {
+            return Command::none();
+        }
+        Command::perform(
+            list_onchain_txs(
+                self.revaultd.clone(),
+                self.items
+                    .iter()
+                    .map(|item| item.vault.outpoint())
+                    .collect(),
+            ),
+            Message::VaultsOnChainTransactions,
+        )
+    }
+
+    pub fn update_unvault_heights(&mut self, txs: Vec<model::VaultTransactions>) {
+        self.unvault_heights = txs
+            .into_iter()
+            .map(|tx| {
+                (
+                    tx.vault_outpoint,
+                    tx.unvault.and_then(|unvault| unvault.blockheight),
+                )
+            })
+            .collect();
+    }
+}
+
+impl State for ReviewState {
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Vaults(res) => match res {
+                Ok(vaults) => return self.update_vaults(vaults),
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::VaultsOnChainTransactions(res) => match res {
+                Ok(txs) => self.update_unvault_heights(txs),
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::SpendTransactions(res) => match res {
+                Ok(txs) => self.spend_txs = txs,
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::AcknowledgeUnvault(outpoint) => {
+                return Command::perform(
+                    acknowledge_unvault(self.revaultd.clone(), outpoint),
+                    Message::AcknowledgedUnvaults,
+                );
+            }
+            Message::AcknowledgedUnvaults(res) => match res {
+                Ok(outpoints) => self.acknowledged = outpoints,
+                Err(e) => self.warning = Some(e),
+            },
+            Message::Vault(outpoint, msg) => {
+                if let Some(item) = self
+                    .items
+                    .iter_mut()
+                    .find(|item| item.vault.outpoint() == outpoint)
+                {
+                    return item
+                        .update(self.revaultd.clone(), msg)
+                        .map(move |msg| Message::Vault(outpoint.clone(), msg));
+                }
+            }
+            _ => {}
+        };
+        Command::none()
+    }
+
+    fn view(&mut self, ctx: &Context) -> Element<Message> {
+        let spend_txs = &self.spend_txs;
+        let unvault_heights = &self.unvault_heights;
+        let acknowledged = &self.acknowledged;
+        let items = self
+            .items
+            .iter_mut()
+            .map(|item| {
+                let outpoint = item.vault.outpoint();
+                let spend_tx = spend_txs
+                    .iter()
+                    .find(|tx| tx.deposit_outpoints.contains(&outpoint));
+                let timelock = match (ctx.unvault_csv, unvault_heights.get(&outpoint)) {
+                    (Some(csv), Some(height)) => Some(UnvaultTimelock::new(csv, *height)),
+                    _ => None,
+                };
+                item.view(ctx, spend_tx, timelock, acknowledged.contains(&outpoint))
+            })
+            .collect();
+
+        self.view
+            .view(ctx, self.warning.as_ref(), items, self.loading)
+    }
+
+    fn load(&self) -> Command<Message> {
+        Command::batch(vec![
+            Command::perform(
+                list_vaults(
+                    self.revaultd.clone(),
+                    Some(&[VaultStatus::Unvaulting, VaultStatus::Unvaulted]),
+                    None,
+                ),
+                Message::Vaults,
+            ),
+            Command::perform(
+                list_spend_txs(self.revaultd.clone(), None),
+                Message::SpendTransactions,
+            ),
+            Command::perform(
+                list_acknowledged_unvaults(self.revaultd.clone()),
+                Message::AcknowledgedUnvaults,
+            ),
+        ])
+    }
+}
+
+impl From<ReviewState> for Box<dyn State> {
+    fn from(s: ReviewState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
+
+/// ReviewItem is an unvault attempt waiting for the review of the stakeholder.
+#[derive(Debug)]
+struct ReviewItem {
+    vault: model::Vault,
+    view: ReviewItemView,
+    /// processing is true while the revault command is running.
+    processing: bool,
+    /// success is true once the vault is revaulted.
+    success: bool,
+    warning: Option<Error>,
+}
+
+impl ReviewItem {
+    fn new(vault: model::Vault) -> Self {
+        Self {
+            vault,
+            view: ReviewItemView::new(),
+            processing: false,
+            success: false,
+            warning: None,
+        }
+    }
+
+    fn update(&mut self, revaultd: Arc<RevaultD>, message: VaultMessage) -> Command<VaultMessage> {
+        match message {
+            VaultMessage::Revault => {
+                self.processing = true;
+                self.warning = None;
+                return Command::perform(
+                    revault(revaultd, self.vault.outpoint()),
+                    VaultMessage::Revaulted,
+                );
+            }
+            VaultMessage::Revaulted(res) => {
+                self.processing = false;
+                match res {
+                    Ok(()) => {
+                        self.success = true;
+                        self.vault.status = VaultStatus::Canceling;
+                    }
+                    Err(e) => self.warning = Error::from(e).into(),
+                }
+            }
+            _ => {}
+        };
+        Command::none()
+    }
+
+    fn view(
+        &mut self,
+        ctx: &Context,
+        spend_tx: Option<&model::SpendTx>,
+        timelock: Option<UnvaultTimelock>,
+        acknowledged: bool,
+    ) -> Element<Message> {
+        self.view.view(
+            ctx,
+            &self.vault,
+            spend_tx,
+            timelock.as_ref(),
+            acknowledged,
+            self.processing,
+            self.success,
+            self.warning.as_ref(),
+        )
+    }
+}
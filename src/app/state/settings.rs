@@ -1,42 +1,132 @@
 use std::convert::From;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
 
 use iced::{Command, Element};
 
-use super::State;
+use super::{cmd::save_revaultd_config, State};
 
-use crate::revaultd::config::Config;
+use crate::{
+    conversion::FiatSource,
+    revaultd::{
+        config::{self, Config},
+        RevaultD,
+    },
+    ui::component::form,
+};
 
 use crate::app::{
     error::Error,
-    message::Message,
+    message::{Message, SettingsMessage},
     view::{Context, SettingsView},
 };
 
 #[derive(Debug)]
 pub struct SettingsState {
+    revaultd: Arc<RevaultD>,
+    revaultd_config_path: PathBuf,
     view: SettingsView,
     warning: Option<Error>,
     config: Config,
+    /// last configuration written to the file, revaultd uses it once restarted.
+    saved_config: Config,
+    fiat: Option<FiatSource>,
+    /// form is Some while the user edits the revaultd configuration.
+    form: Option<SettingsForm>,
+    processing: bool,
+    /// saved is true once the configuration is written,
+    /// revaultd must be restarted to use it.
+    saved: bool,
 }
 
 impl SettingsState {
-    pub fn new(config: Config) -> Self {
+    pub fn new(
+        revaultd: Arc<RevaultD>,
+        revaultd_config_path: PathBuf,
+        fiat: Option<FiatSource>,
+    ) -> Self {
         SettingsState {
+            config: revaultd.config.clone(),
+            saved_config: revaultd.config.clone(),
+            revaultd,
+            revaultd_config_path,
             view: SettingsView::new(),
-            config,
+            fiat,
             warning: None,
+            form: None,
+            processing: false,
+            saved: false,
         }
     }
+
+    fn on_settings_message(&mut self, message: SettingsMessage) -> Command<Message> {
+        match message {
+            SettingsMessage::Edit => {
+                self.saved = false;
+                self.form = Some(SettingsForm::new(&self.config));
+            }
+            SettingsMessage::CancelEdit => self.form = None,
+            SettingsMessage::Save => {
+                if let Some(form) = &mut self.form {
+                    if let Some(config) = form.apply(&self.config) {
+                        self.processing = true;
+                        self.warning = None;
+                        self.config = config.clone();
+                        return Command::perform(
+                            save_revaultd_config(self.revaultd_config_path.clone(), config),
+                            |res| Message::Settings(SettingsMessage::Saved(res)),
+                        );
+                    }
+                }
+            }
+            SettingsMessage::Saved(res) => {
+                self.processing = false;
+                match res {
+                    Ok(()) => {
+                        self.form = None;
+                        self.saved = true;
+                        self.saved_config = self.config.clone();
+                    }
+                    Err(e) => {
+                        // The file was not written, it keeps the last saved configuration.
+                        self.config = self.saved_config.clone();
+                        self.warning = Some(e);
+                    }
+                }
+            }
+            msg => {
+                if let Some(form) = &mut self.form {
+                    form.update(msg);
+                }
+            }
+        };
+        Command::none()
+    }
 }
 
 impl State for SettingsState {
-    fn update(&mut self, _message: Message) -> Command<Message> {
-        Command::none()
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Settings(msg) => self.on_settings_message(msg),
+            Message::DaemonStopped(Err(e)) => {
+                self.warning = Some(e.into());
+                Command::none()
+            }
+            _ => Command::none(),
+        }
     }
 
     fn view(&mut self, ctx: &Context) -> Element<Message> {
-        self.view
-            .view(ctx, self.warning.as_ref(), self.config.clone())
+        self.view.view(
+            ctx,
+            self.warning.as_ref(),
+            self.config.clone(),
+            self.fiat.as_ref(),
+            self.form.as_ref(),
+            self.processing,
+            self.saved,
+        )
     }
 }
 
@@ -45,3 +135,204 @@ impl From<SettingsState> for Box<dyn State> {
         Box::new(s)
     }
 }
+
+/// SettingsForm is the editable part of the revaultd configuration,
+/// validated with the rules of the installer.
+#[derive(Debug, Clone)]
+pub struct SettingsForm {
+    pub bitcoind_address: form::Value<String>,
+    pub cookie_path: form::Value<String>,
+    pub bitcoind_poll: form::Value<String>,
+    pub coordinator_host: form::Value<String>,
+    pub coordinator_noise_key: form::Value<String>,
+    pub coordinator_poll: form::Value<String>,
+    pub log_level: form::Value<String>,
+    /// peers are the cosigners of a manager or the watchtowers of a stakeholder.
+    pub peers: Vec<PeerForm>,
+    /// cosigners is true if the peers are the cosigners.
+    pub cosigners: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PeerForm {
+    pub host: form::Value<String>,
+    pub noise_key: form::Value<String>,
+}
+
+impl PeerForm {
+    fn new(host: &str, noise_key: &str) -> Self {
+        Self {
+            host: value(host.to_string()),
+            noise_key: value(noise_key.to_string()),
+        }
+    }
+}
+
+fn value(value: String) -> form::Value<String> {
+    form::Value { value, valid: true }
+}
+
+fn optional(value: Option<impl ToString>) -> form::Value<String> {
+    self::value(value.map(|v| v.to_string()).unwrap_or_default())
+}
+
+/// parse_poll parses an optional poll interval in seconds.
+fn parse_poll(value: &str) -> Result<Option<u64>, ()> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match u64::from_str(value) {
+        Ok(secs) if secs > 0 => Ok(Some(secs)),
+        _ => Err(()),
+    }
+}
+
+impl SettingsForm {
+    pub fn new(config: &Config) -> Self {
+        let cosigners = config.manager_config.is_some();
+        let peers = if let Some(manager) = &config.manager_config {
+            manager
+                .cosigners
+                .iter()
+                .map(|c| PeerForm::new(&c.host, &c.noise_key))
+                .collect()
+        } else if let Some(stakeholder) = &config.stakeholder_config {
+            stakeholder
+                .watchtowers
+                .iter()
+                .map(|w| PeerForm::new(&w.host, &w.noise_key))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            bitcoind_address: value(config.bitcoind_config.addr.to_string()),
+            cookie_path: value(
+                config
+                    .bitcoind_config
+                    .cookie_path
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            bitcoind_poll: optional(config.bitcoind_config.poll_interval_secs),
+            coordinator_host: value(config.coordinator_host.clone()),
+            coordinator_noise_key: value(config.coordinator_noise_key.clone()),
+            coordinator_poll: optional(config.coordinator_poll_seconds),
+            log_level: optional(config.log_level.as_ref()),
+            peers,
+            cosigners,
+        }
+    }
+
+    fn update(&mut self, message: SettingsMessage) {
+        let field = match message {
+            SettingsMessage::BitcoindAddressEdited(v) => Some((&mut self.bitcoind_address, v)),
+            SettingsMessage::CookiePathEdited(v) => Some((&mut self.cookie_path, v)),
+            SettingsMessage::BitcoindPollEdited(v) => Some((&mut self.bitcoind_poll, v)),
+            SettingsMessage::CoordinatorHostEdited(v) => Some((&mut self.coordinator_host, v)),
+            SettingsMessage::CoordinatorNoiseKeyEdited(v) => {
+                Some((&mut self.coordinator_noise_key, v))
+            }
+            SettingsMessage::CoordinatorPollEdited(v) => Some((&mut self.coordinator_poll, v)),
+            SettingsMessage::LogLevelEdited(v) => Some((&mut self.log_level, v)),
+            SettingsMessage::PeerHostEdited(i, v) => {
+                self.peers.get_mut(i).map(|peer| (&mut peer.host, v))
+            }
+            SettingsMessage::PeerNoiseKeyEdited(i, v) => {
+                self.peers.get_mut(i).map(|peer| (&mut peer.noise_key, v))
+            }
+            SettingsMessage::AddPeer => {
+                self.peers.push(PeerForm::new("", ""));
+                None
+            }
+            SettingsMessage::RemovePeer(i) => {
+                if i < self.peers.len() {
+                    self.peers.remove(i);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some((field, v)) = field {
+            field.value = v;
+            field.valid = true;
+        }
+    }
+
+    /// apply validates the fields and returns the configuration with the edited values,
+    /// or None if a field is invalid.
+    fn apply(&mut self, config: &Config) -> Option<Config> {
+        let addr = std::net::SocketAddr::from_str(&self.bitcoind_address.value);
+        self.bitcoind_address.valid = addr.is_ok();
+        let cookie_path = PathBuf::from_str(&self.cookie_path.value);
+        self.cookie_path.valid = cookie_path.is_ok() && !self.cookie_path.value.is_empty();
+        let bitcoind_poll = parse_poll(&self.bitcoind_poll.value);
+        self.bitcoind_poll.valid = bitcoind_poll.is_ok();
+
+        self.coordinator_host.valid = config::is_valid_host(&self.coordinator_host.value);
+        self.coordinator_noise_key.valid =
+            config::is_valid_noise_key(&self.coordinator_noise_key.value);
+        let coordinator_poll = parse_poll(&self.coordinator_poll.value);
+        self.coordinator_poll.valid = coordinator_poll.is_ok();
+
+        self.log_level.valid = self.log_level.value.is_empty()
+            || config::LOG_LEVELS.contains(&self.log_level.value.as_str());
+
+        for peer in &mut self.peers {
+            peer.noise_key.valid = config::is_valid_noise_key(&peer.noise_key.value);
+            // The installer does not check the host of the watchtowers.
+            if self.cosigners {
+                peer.host.valid = config::is_valid_host(&peer.host.value);
+            }
+        }
+
+        let (addr, cookie_path, bitcoind_poll, coordinator_poll) =
+            match (addr, cookie_path, bitcoind_poll, coordinator_poll) {
+                (Ok(addr), Ok(path), Ok(bitcoind_poll), Ok(coordinator_poll))
+                    if self.cookie_path.valid =>
+                {
+                    (addr, path, bitcoind_poll, coordinator_poll)
+                }
+                _ => return None,
+            };
+        if !self.coordinator_host.valid
+            || !self.coordinator_noise_key.valid
+            || !self.log_level.valid
+            || self
+                .peers
+                .iter()
+                .any(|peer| !peer.host.valid || !peer.noise_key.valid)
+        {
+            return None;
+        }
+
+        let mut config = config.clone();
+        config.bitcoind_config.addr = addr;
+        config.bitcoind_config.cookie_path = cookie_path;
+        config.bitcoind_config.poll_interval_secs = bitcoind_poll;
+        config.coordinator_host = self.coordinator_host.value.clone();
+        config.coordinator_noise_key = self.coordinator_noise_key.value.clone();
+        config.coordinator_poll_seconds = coordinator_poll;
+        config.log_level = Some(self.log_level.value.clone()).filter(|l| !l.is_empty());
+        if let Some(manager) = &mut config.manager_config {
+            manager.cosigners = self
+                .peers
+                .iter()
+                .map(|peer| config::CosignerConfig {
+                    host: peer.host.value.clone(),
+                    noise_key: peer.noise_key.value.clone(),
+                })
+                .collect();
+        } else if let Some(stakeholder) = &mut config.stakeholder_config {
+            stakeholder.watchtowers = self
+                .peers
+                .iter()
+                .map(|peer| config::WatchtowerConfig {
+                    host: peer.host.value.clone(),
+                    noise_key: peer.noise_key.value.clone(),
+                })
+                .collect();
+        }
+        Some(config)
+    }
+}
@@ -36,15 +36,19 @@ pub struct SpendTransactionState {
     warning: Option<Error>,
 
     action: SpendTransactionAction,
+    /// read_only is true while revaultd syncs, the transaction cannot be
+    /// signed, broadcasted or deleted.
+    read_only: bool,
 
     view: SpendTransactionView,
 }
 
 impl SpendTransactionState {
-    pub fn new(revaultd: Arc<RevaultD>, psbt: Psbt) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, psbt: Psbt, read_only: bool) -> Self {
         Self {
             revaultd,
             psbt,
+            read_only,
             deposit_outpoints: Vec::new(),
             deposits: Vec::new(),
             action: SpendTransactionAction::new(),
@@ -57,6 +61,12 @@ impl SpendTransactionState {
 impl State for SpendTransactionState {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
+            // revaultd must be synced to sign, broadcast or delete the transaction.
+            Message::SpendTx(SpendTxMessage::Sign(_))
+            | Message::SpendTx(SpendTxMessage::Broadcast)
+            | Message::SpendTx(SpendTxMessage::Delete)
+            | Message::SpendTx(SpendTxMessage::Update)
+                if self.read_only => {}
             Message::SpendTx(SpendTxMessage::Inputs(res)) => match res {
                 Ok(vaults) => {
                     self.deposits = vaults
@@ -32,12 +32,14 @@ pub struct StakeholderHomeState {
 
     moving_vaults: Vec<VaultListItem<VaultListItemView>>,
     selected_vault: Option<Vault>,
+    /// read_only is true while revaultd syncs, the vault actions are disabled.
+    read_only: bool,
 
     view: StakeholderHomeView,
 }
 
 impl StakeholderHomeState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, read_only: bool) -> Self {
         StakeholderHomeState {
             revaultd,
             warning: None,
@@ -45,6 +47,7 @@ impl StakeholderHomeState {
             balance: HashMap::new(),
             moving_vaults: Vec::new(),
             selected_vault: None,
+            read_only,
         }
     }
 
@@ -79,7 +82,7 @@ impl StakeholderHomeState {
             .iter()
             .find(|vlt| vlt.vault.outpoint() == outpoint)
         {
-            let selected_vault = Vault::new(selected.vault.clone());
+            let selected_vault = Vault::new(selected.vault.clone(), self.read_only);
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd.map(move |msg| Message::Vault(outpoint.clone(), msg));
@@ -232,14 +235,17 @@ pub struct StakeholderCreateVaultsState {
     address: Option<bitcoin::Address>,
     deposits: Vec<VaultListItem<SecureVaultListItemView>>,
     selected_vault: Option<Vault>,
+    /// read_only is true while revaultd syncs, the vault actions are disabled.
+    read_only: bool,
 
     view: StakeholderCreateVaultsView,
 }
 
 impl StakeholderCreateVaultsState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, read_only: bool) -> Self {
         StakeholderCreateVaultsState {
             revaultd,
+            read_only,
             address: None,
             warning: None,
             deposits: Vec::new(),
@@ -262,7 +268,7 @@ impl StakeholderCreateVaultsState {
             .iter()
             .find(|vlt| vlt.vault.outpoint() == outpoint)
         {
-            self.selected_vault = Some(Vault::new(selected.vault.clone()));
+            self.selected_vault = Some(Vault::new(selected.vault.clone(), self.read_only));
             return Command::perform(
                 get_revocation_txs(self.revaultd.clone(), selected.vault.outpoint()),
                 move |res| {
@@ -294,7 +300,7 @@ impl State for StakeholderCreateVaultsState {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::DepositAddress(res) => match res {
-                Ok(address) => {
+                Ok((_, address)) => {
                     // Address is loaded directly in the view in order to cache the created qrcode.
                     self.view.load(&address);
                     self.address = Some(address);
@@ -370,14 +376,17 @@ pub struct StakeholderDelegateFundsState {
     vaults: Vec<VaultListItem<DelegateVaultListItemView>>,
     selected_vault: Option<Vault>,
     warning: Option<Error>,
+    /// read_only is true while revaultd syncs, the vault actions are disabled.
+    read_only: bool,
 
     view: StakeholderDelegateFundsView,
 }
 
 impl StakeholderDelegateFundsState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(revaultd: Arc<RevaultD>, read_only: bool) -> Self {
         StakeholderDelegateFundsState {
             revaultd,
+            read_only,
             active_balance: 0,
             activating_balance: 0,
             vaults: Vec::new(),
@@ -405,7 +414,7 @@ impl StakeholderDelegateFundsState {
             .iter()
             .find(|vlt| vlt.vault.outpoint() == outpoint)
         {
-            let selected_vault = Vault::new(selected.vault.clone());
+            let selected_vault = Vault::new(selected.vault.clone(), self.read_only);
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd.map(move |msg| Message::Vault(outpoint.clone(), msg));
@@ -414,6 +423,14 @@ impl StakeholderDelegateFundsState {
     }
 
     pub fn on_vault_delegate(&mut self, outpoint: String) -> Command<Message> {
+        // The vault cannot be delegated while revaultd syncs, it is only displayed.
+        if self.read_only {
+            if self.selected_vault.is_none() {
+                return self.on_vault_select(outpoint);
+            }
+            return Command::none();
+        }
+
         if let Some(selected) = &mut self.selected_vault {
             if selected.vault.outpoint() == outpoint {
                 return selected
@@ -427,7 +444,7 @@ impl StakeholderDelegateFundsState {
             .iter()
             .find(|vlt| vlt.vault.outpoint() == outpoint)
         {
-            let mut selected_vault = Vault::new(selected.vault.clone());
+            let mut selected_vault = Vault::new(selected.vault.clone(), self.read_only);
             let cmd = selected_vault.update(self.revaultd.clone(), VaultMessage::Delegate);
             self.selected_vault = Some(selected_vault);
             return cmd.map(move |msg| Message::Vault(outpoint.clone(), msg));
@@ -21,11 +21,12 @@ use crate::{
             Context,
         },
     },
-    revault::TransactionKind,
+    revault::{parse_address, AddressError, TransactionKind},
     revaultd::{
         model::{self, RevocationTransactions, VaultStatus, VaultTransactions},
-        RevaultD,
+        RevaultD, RevaultDError,
     },
+    revocation::check_revocation_txs,
 };
 
 #[derive(Debug)]
@@ -55,12 +56,15 @@ pub struct Vault {
     warning: Option<Error>,
     section: VaultSection,
     view: VaultModal,
+    /// read_only is true while revaultd syncs, the actions are disabled.
+    read_only: bool,
 }
 
 impl Vault {
-    pub fn new(vault: model::Vault) -> Self {
+    pub fn new(vault: model::Vault, read_only: bool) -> Self {
         Self {
             vault,
+            read_only,
             section: VaultSection::Unloaded,
             view: VaultModal::new(),
             warning: None,
@@ -73,6 +77,15 @@ impl Vault {
         message: VaultMessage,
     ) -> Command<VaultMessage> {
         match message {
+            // revaultd must be synced to create, delegate or revault the vault.
+            VaultMessage::Secure
+            | VaultMessage::Delegate
+            | VaultMessage::SelectRevault
+            | VaultMessage::Revault
+                if self.read_only =>
+            {
+                return Command::none();
+            }
             VaultMessage::ListOnchainTransaction => {
                 return Command::perform(
                     get_onchain_txs(revaultd, self.vault.outpoint()),
@@ -88,7 +101,19 @@ impl Vault {
                 Err(e) => self.warning = Error::from(e).into(),
             },
             VaultMessage::RevocationTransactions(res) => match res {
-                Ok(tx) => self.section = VaultSection::new_ack_section(tx),
+                Ok((txs, unvault_tx)) => {
+                    let deposit_address = revaultd
+                        .config
+                        .scripts_config
+                        .deposit_address(self.vault.derivation_index, revaultd.network());
+                    self.section = VaultSection::new_ack_section(
+                        &self.vault,
+                        txs,
+                        unvault_tx,
+                        emergency_address(&revaultd),
+                        deposit_address,
+                    )
+                }
                 Err(e) => self.warning = Error::from(e).into(),
             },
             VaultMessage::SelectRevault => {
@@ -100,7 +125,9 @@ impl Vault {
                     VaultMessage::UnvaultTransaction,
                 );
             }
-            VaultMessage::Secure => {
+            // Retry fetches again the revocation transactions and the unvault
+            // transaction, which is required to check them.
+            VaultMessage::Secure | VaultMessage::Retry => {
                 return Command::perform(
                     get_revocation_txs(revaultd, self.vault.outpoint()),
                     VaultMessage::RevocationTransactions,
@@ -119,7 +146,7 @@ impl Vault {
             &self.vault,
             self.warning.as_ref(),
             self.section.title(&self.vault),
-            self.section.view(ctx, &self.vault),
+            self.section.view(ctx, &self.vault, self.read_only),
         )
     }
 
@@ -147,6 +174,9 @@ pub enum VaultSection {
         emergency_tx: (Psbt, bool),
         emergency_unvault_tx: (Psbt, bool),
         cancel_tx: (Psbt, bool),
+        /// emergency_address is the destination of the emergency transactions
+        /// or the failed checks explaining why the transactions must not be signed.
+        emergency_address: Result<bitcoin::Address, Vec<String>>,
         warning: Option<Error>,
         view: SecureVaultView,
         signer: SignState,
@@ -200,13 +230,47 @@ impl VaultSection {
         }
     }
 
-    pub fn new_ack_section(txs: RevocationTransactions) -> Self {
+    pub fn new_ack_section(
+        vault: &model::Vault,
+        txs: RevocationTransactions,
+        unvault_tx: Result<Psbt, RevaultDError>,
+        emergency_address: Result<bitcoin::Address, AddressError>,
+        deposit_address: Option<bitcoin::Address>,
+    ) -> Self {
+        let emergency_address = match emergency_address {
+            Ok(address) => {
+                let errors: Vec<String> = check_revocation_txs(
+                    vault,
+                    &txs,
+                    unvault_tx.as_ref().map_err(|e| e.to_string()),
+                    &address,
+                    deposit_address.as_ref(),
+                )
+                .iter()
+                .map(|e| e.to_string())
+                .collect();
+                if errors.is_empty() {
+                    Ok(address)
+                } else {
+                    Err(errors)
+                }
+            }
+            Err(e) => Err(vec![format!(
+                "Emergency address of the configuration: {}",
+                e
+            )]),
+        };
+        let mut view = SecureVaultView::new();
+        if let Ok(address) = &emergency_address {
+            view.load(address);
+        }
         Self::Secure {
+            emergency_address,
             emergency_tx: (txs.emergency_tx.clone(), false),
             emergency_unvault_tx: (txs.emergency_unvault_tx.clone(), false),
             cancel_tx: (txs.cancel_tx.clone(), false),
             signer: SignState::new(txs.emergency_tx, TransactionKind::Emergency),
-            view: SecureVaultView::new(),
+            view,
             warning: None,
         }
     }
@@ -295,9 +359,14 @@ impl VaultSection {
                     emergency_tx,
                     emergency_unvault_tx,
                     cancel_tx,
+                    emergency_address,
                     warning,
                     ..
                 } => {
+                    // Transactions failing the sanity checks must not be signed.
+                    if emergency_address.is_err() {
+                        return Command::none();
+                    }
                     *warning = None;
                     signer.update(msg);
                     if let Some(psbt) = &signer.signed_psbt {
@@ -338,11 +407,16 @@ impl VaultSection {
         Command::none()
     }
 
-    pub fn view(&mut self, ctx: &Context, vault: &model::Vault) -> Element<Message> {
+    pub fn view(
+        &mut self,
+        ctx: &Context,
+        vault: &model::Vault,
+        read_only: bool,
+    ) -> Element<Message> {
         let outpoint = vault.outpoint();
         match self {
             Self::Unloaded => iced::Container::new(iced::Column::new()).into(),
-            Self::OnchainTransactions { txs, view } => view.view(ctx, &vault, &txs),
+            Self::OnchainTransactions { txs, view } => view.view(ctx, &vault, &txs, read_only),
             Self::Delegate {
                 signer,
                 view,
@@ -353,6 +427,7 @@ impl VaultSection {
                 emergency_tx,
                 emergency_unvault_tx,
                 cancel_tx,
+                emergency_address,
                 warning,
                 view,
                 signer,
@@ -364,6 +439,7 @@ impl VaultSection {
                     &emergency_tx,
                     &emergency_unvault_tx,
                     &cancel_tx,
+                    emergency_address.as_ref(),
                     signer.view(ctx).map(VaultMessage::Sign),
                 )
                 .map(move |msg| Message::Vault(outpoint.clone(), msg)),
@@ -372,7 +448,25 @@ impl VaultSection {
                 success,
                 warning,
                 view,
-            } => view.view(ctx, vault, &processing, &success, warning.as_ref()),
+            } => view.view(
+                ctx,
+                vault,
+                &processing,
+                &success,
+                warning.as_ref(),
+                read_only,
+            ),
         }
     }
 }
+
+/// emergency_address returns the emergency address of the stakeholder configuration
+/// if it is valid for the network.
+fn emergency_address(revaultd: &RevaultD) -> Result<bitcoin::Address, AddressError> {
+    match &revaultd.config.stakeholder_config {
+        Some(cfg) => parse_address(&cfg.emergency_address, revaultd.network()),
+        None => Err(AddressError::Invalid(
+            "no stakeholder configuration".to_string(),
+        )),
+    }
+}
@@ -1,22 +1,40 @@
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use iced::{Command, Element};
+use chrono::NaiveDate;
+use iced::{Command, Element, Subscription};
+use iced_native::{event, mouse, window, Event};
 
 use super::{
-    cmd::{get_blockheight, list_vaults},
+    cmd::{export_vaults, get_blockheight, list_onchain_txs, list_vaults_with_statuses},
     vault::{Vault, VaultListItem},
     State,
 };
 
-use crate::revaultd::{model, model::VaultStatus, RevaultD};
+use crate::{
+    conversion::{Denomination, FiatSource},
+    revaultd::{
+        model::{self, VaultStatus, VaultTransactions},
+        RevaultD,
+    },
+    ui::component::form,
+};
 
 use crate::app::{
     error::Error,
-    message::{Message, VaultFilterMessage, VaultMessage},
+    message::{Message, VaultDate, VaultFilterMessage, VaultMessage, VaultSort},
     view::{vault::VaultListItemView, Context, VaultsView},
 };
 
+/// Number of vaults rendered at once, enough to fill the highest windows.
+const VAULTS_WINDOW: usize = 50;
+
+/// Number of vaults rendered above the first visible one, the rows are
+/// displayed while the window is rebuilt after a scroll up.
+const VAULTS_OVERSCAN: usize = 10;
+
 #[derive(Debug)]
 pub struct VaultsState {
     revaultd: Arc<RevaultD>,
@@ -24,33 +42,155 @@ pub struct VaultsState {
 
     blockheight: u64,
 
-    vault_status_filter: &'static [VaultStatus],
-    vaults: Vec<VaultListItem<VaultListItemView>>,
+    /// filter is kept while a vault is selected, so the user finds
+    /// the same list when going back.
+    filter: VaultFilter,
+    /// all the vaults listed by revaultd, sorted by the filter.
+    vaults: Vec<model::Vault>,
+    /// txids of the onchain transactions of each vault by outpoint,
+    /// searched with the outpoint and the address.
+    txids: HashMap<String, Vec<String>>,
+    /// labels of the deposit slips by deposit address.
+    labels: BTreeMap<String, String>,
+    /// indexes of the vaults matching the filter, computed again only
+    /// when the vaults or the filter change.
+    matching: Vec<usize>,
+    /// list items are only built for the window of matching vaults
+    /// around the scroll offset.
+    items: Vec<VaultListItem<VaultListItemView>>,
+    /// position in the matching vaults of the first list item.
+    first: usize,
     selected_vault: Option<Vault>,
+    /// read_only is true while revaultd syncs, the vault actions are disabled.
+    read_only: bool,
 
     warning: Option<Error>,
 
     /// loading is true until Message::Vaults is handled
     loading: bool,
+    /// exporting is true until Message::VaultsExported is handled
+    exporting: bool,
+    /// paths of the files created by the last export.
+    exported: Option<Vec<PathBuf>>,
+    /// fiat source used to value the exported vaults.
+    fiat: Option<FiatSource>,
 }
 
 impl VaultsState {
-    pub fn new(revaultd: Arc<RevaultD>) -> Self {
+    pub fn new(
+        revaultd: Arc<RevaultD>,
+        fiat: Option<FiatSource>,
+        denomination: Denomination,
+        labels: BTreeMap<String, String>,
+        read_only: bool,
+    ) -> Self {
         VaultsState {
             revaultd,
+            labels,
+            read_only,
             view: VaultsView::new(),
             blockheight: 0,
-            vault_status_filter: &VaultStatus::CURRENT,
+            filter: VaultFilter::new(denomination),
             vaults: Vec::new(),
+            txids: HashMap::new(),
+            matching: Vec::new(),
+            items: Vec::new(),
+            first: 0,
             selected_vault: None,
             warning: None,
             loading: true,
+            exporting: false,
+            exported: None,
+            fiat,
         }
     }
 
-    pub fn update_vaults(&mut self, vaults: Vec<model::Vault>) {
-        self.vaults = vaults.into_iter().map(VaultListItem::new).collect();
+    pub fn update_vaults(&mut self, vaults: Vec<model::Vault>) -> Command<Message> {
+        self.vaults = vaults;
+        self.filter.sort(&mut self.vaults);
+        self.refresh_items();
         self.loading = false;
+        if self.vaults.is_empty() {
+            return Command::none();
+        }
+        Command::perform(
+            list_onchain_txs(
+                self.revaultd.clone(),
+                self.vaults.iter().map(|v| v.outpoint()).collect(),
+            ),
+            Message::VaultsOnChainTransactions,
+        )
+    }
+
+    fn update_txids(&mut self, txs: Vec<VaultTransactions>) {
+        self.txids = txs
+            .into_iter()
+            .map(|txs| {
+                let txids = vec![
+                    Some(&txs.deposit),
+                    txs.unvault.as_ref(),
+                    txs.spend.as_ref(),
+                    txs.cancel.as_ref(),
+                    txs.emergency.as_ref(),
+                    txs.unvault_emergency.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                .map(|tx| tx.tx.txid().to_string())
+                .collect();
+                (txs.vault_outpoint, txids)
+            })
+            .collect();
+        if !self.filter.search.trim().is_empty() {
+            self.refresh_items();
+        }
+    }
+
+    fn matches(&self, vault: &model::Vault) -> bool {
+        self.filter.matches(
+            vault,
+            self.txids.get(&vault.outpoint()),
+            self.labels.get(&vault.address),
+        )
+    }
+
+    /// refresh_items computes the vaults matching the filter and rebuilds
+    /// the list items of the scrolled window.
+    fn refresh_items(&mut self) {
+        self.matching = self
+            .vaults
+            .iter()
+            .enumerate()
+            .filter(|(_, vault)| self.matches(vault))
+            .map(|(i, _)| i)
+            .collect();
+        self.build_items();
+    }
+
+    /// on_scroll rebuilds the list items if the scrolled window moved.
+    fn on_scroll(&mut self) {
+        if self.window_start() != self.first {
+            self.build_items();
+        }
+    }
+
+    fn window_start(&self) -> usize {
+        self.view
+            .first_visible_row()
+            .saturating_sub(VAULTS_OVERSCAN)
+            .min(self.matching.len().saturating_sub(VAULTS_WINDOW))
+    }
+
+    fn build_items(&mut self) {
+        self.first = self.window_start();
+        let vaults = &self.vaults;
+        self.items = self
+            .matching
+            .iter()
+            .skip(self.first)
+            .take(VAULTS_WINDOW)
+            .map(|i| VaultListItem::new(vaults[*i].clone()))
+            .collect();
     }
 
     pub fn on_vault_select(&mut self, outpoint: String) -> Command<Message> {
@@ -61,25 +201,68 @@ impl VaultsState {
             }
         }
 
-        if let Some(selected) = self
-            .vaults
-            .iter()
-            .find(|vlt| vlt.vault.outpoint() == outpoint)
-        {
-            let selected_vault = Vault::new(selected.vault.clone());
+        if let Some(selected) = self.vaults.iter().find(|vlt| vlt.outpoint() == outpoint) {
+            let selected_vault = Vault::new(selected.clone(), self.read_only);
             let cmd = selected_vault.load(self.revaultd.clone());
             self.selected_vault = Some(selected_vault);
             return cmd.map(move |msg| Message::Vault(outpoint.clone(), msg));
         };
         Command::none()
     }
+
+    fn load_vaults(&mut self) -> Command<Message> {
+        // An empty status list would return all the vaults.
+        if self.filter.statuses.is_empty() {
+            return self.update_vaults(Vec::new());
+        }
+        self.loading = true;
+        Command::perform(
+            list_vaults_with_statuses(self.revaultd.clone(), self.filter.statuses.clone()),
+            Message::Vaults,
+        )
+    }
+
+    pub fn on_filter(&mut self, msg: VaultFilterMessage) -> Command<Message> {
+        match msg {
+            VaultFilterMessage::Status(statuses) => {
+                self.filter.statuses = statuses.to_vec();
+                return self.load_vaults();
+            }
+            VaultFilterMessage::ToggleStatus(status, selected) => {
+                self.filter.statuses.retain(|s| *s != status);
+                if selected {
+                    self.filter.statuses.push(status);
+                }
+                return self.load_vaults();
+            }
+            VaultFilterMessage::Sort(sort) => {
+                self.filter.sort = sort;
+                self.filter.sort(&mut self.vaults);
+                self.refresh_items();
+            }
+            VaultFilterMessage::Reset => {
+                self.filter = VaultFilter::new(self.filter.denomination);
+                return self.load_vaults();
+            }
+            VaultFilterMessage::ToggleCriteria => self.filter.update(msg),
+            msg => {
+                self.filter.update(msg);
+                self.refresh_items();
+            }
+        };
+        Command::none()
+    }
 }
 
 impl State for VaultsState {
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::Vaults(res) => match res {
-                Ok(vaults) => self.update_vaults(vaults),
+                Ok(vaults) => return self.update_vaults(vaults),
+                Err(e) => self.warning = Error::from(e).into(),
+            },
+            Message::VaultsOnChainTransactions(res) => match res {
+                Ok(txs) => self.update_txids(txs),
                 Err(e) => self.warning = Error::from(e).into(),
             },
             Message::Vault(outpoint, VaultMessage::Select) => {
@@ -94,14 +277,31 @@ impl State for VaultsState {
                     }
                 }
             }
-            Message::FilterVaults(VaultFilterMessage::Status(statuses)) => {
-                self.loading = true;
-                self.vault_status_filter = statuses;
+            Message::FilterVaults(msg) => return self.on_filter(msg),
+            Message::VaultsScrolled => self.on_scroll(),
+            // The export contains the vaults displayed to the user,
+            // matching all the criteria of the filter.
+            Message::ExportVaults(format) => {
+                self.exporting = true;
+                self.exported = None;
+                self.warning = None;
+                let vaults = self
+                    .matching
+                    .iter()
+                    .map(|i| self.vaults[*i].clone())
+                    .collect();
                 return Command::perform(
-                    list_vaults(self.revaultd.clone(), Some(self.vault_status_filter), None),
-                    Message::Vaults,
+                    export_vaults(self.revaultd.clone(), vaults, format, self.fiat.clone()),
+                    Message::VaultsExported,
                 );
             }
+            Message::VaultsExported(res) => {
+                self.exporting = false;
+                match res {
+                    Ok(paths) => self.exported = Some(paths),
+                    Err(e) => self.warning = Some(e),
+                }
+            }
             Message::BlockHeight(b) => match b {
                 Ok(height) => self.blockheight = height,
                 Err(e) => self.warning = Error::from(e).into(),
@@ -118,17 +318,28 @@ impl State for VaultsState {
         self.view.view(
             ctx,
             self.warning.as_ref(),
-            self.vaults.iter_mut().map(|v| v.view(ctx)).collect(),
-            self.vault_status_filter,
+            self.items.iter_mut().map(|v| v.view(ctx)).collect(),
+            self.first,
+            self.matching.len(),
+            &self.filter,
             self.loading,
+            self.exporting,
+            self.exported.as_ref(),
         )
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        if self.selected_vault.is_some() {
+            return Subscription::none();
+        }
+        iced_native::subscription::events_with(scroll_event)
+    }
+
     fn load(&self) -> Command<Message> {
         Command::batch(vec![
             Command::perform(get_blockheight(self.revaultd.clone()), Message::BlockHeight),
             Command::perform(
-                list_vaults(self.revaultd.clone(), Some(self.vault_status_filter), None),
+                list_vaults_with_statuses(self.revaultd.clone(), self.filter.statuses.clone()),
                 Message::Vaults,
             ),
         ])
@@ -140,3 +351,186 @@ impl From<VaultsState> for Box<dyn State> {
         Box::new(s)
     }
 }
+
+/// scroll_event maps the events moving the vault list: the mouse wheel,
+/// the release of the dragged scrollbar and the resize of the window.
+fn scroll_event(event: Event, _status: event::Status) -> Option<Message> {
+    match event {
+        Event::Mouse(mouse::Event::WheelScrolled { .. })
+        | Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+        | Event::Window(window::Event::Resized { .. }) => Some(Message::VaultsScrolled),
+        _ => None,
+    }
+}
+
+/// VaultFilter holds the criteria selected by the user to filter and sort the vaults.
+/// The statuses are sent to revaultd, the other criteria are applied to the listed vaults.
+#[derive(Debug, Clone)]
+pub struct VaultFilter {
+    pub statuses: Vec<VaultStatus>,
+    pub search: String,
+    pub sort: VaultSort,
+    pub min_amount: form::Value<String>,
+    pub max_amount: form::Value<String>,
+    pub date: VaultDate,
+    /// dates are written by the user with the YYYY-MM-DD format.
+    pub after: form::Value<String>,
+    pub before: form::Value<String>,
+    /// show_criteria is true if the amount and date criteria are displayed.
+    pub show_criteria: bool,
+    /// denomination of the amount criteria.
+    denomination: Denomination,
+}
+
+impl VaultFilter {
+    pub fn new(denomination: Denomination) -> Self {
+        Self {
+            statuses: VaultStatus::CURRENT.to_vec(),
+            search: String::new(),
+            sort: VaultSort::Newest,
+            min_amount: form::Value::default(),
+            max_amount: form::Value::default(),
+            date: VaultDate::Received,
+            after: form::Value::default(),
+            before: form::Value::default(),
+            show_criteria: false,
+            denomination,
+        }
+    }
+
+    fn update(&mut self, msg: VaultFilterMessage) {
+        match msg {
+            VaultFilterMessage::Search(search) => self.search = search,
+            VaultFilterMessage::MinAmount(amount) => {
+                self.min_amount.valid = amount.is_empty() || self.amount(&amount).is_some();
+                self.min_amount.value = amount;
+            }
+            VaultFilterMessage::MaxAmount(amount) => {
+                self.max_amount.valid = amount.is_empty() || self.amount(&amount).is_some();
+                self.max_amount.value = amount;
+            }
+            VaultFilterMessage::Date(date) => self.date = date,
+            VaultFilterMessage::After(date) => {
+                self.after.valid = date.is_empty() || parse_date(&date).is_some();
+                self.after.value = date;
+            }
+            VaultFilterMessage::Before(date) => {
+                self.before.valid = date.is_empty() || parse_date(&date).is_some();
+                self.before.value = date;
+            }
+            VaultFilterMessage::ToggleCriteria => self.show_criteria = !self.show_criteria,
+            _ => {}
+        }
+    }
+
+    fn amount(&self, amount: &str) -> Option<u64> {
+        self.denomination.parse(amount).ok()
+    }
+
+    /// matches returns true if the vault meets all the criteria, the search
+    /// is also looked up in the txids of the vault onchain transactions and
+    /// in the label of the deposit slip of the vault address.
+    /// Invalid criteria are ignored.
+    pub fn matches(
+        &self,
+        vault: &model::Vault,
+        txids: Option<&Vec<String>>,
+        label: Option<&String>,
+    ) -> bool {
+        let search = self.search.trim().to_lowercase();
+        if !search.is_empty()
+            && !vault.outpoint().to_lowercase().contains(&search)
+            && !vault.address.to_lowercase().contains(&search)
+            && !txids
+                .map(|txids| txids.iter().any(|txid| txid.contains(&search)))
+                .unwrap_or(false)
+            && !label
+                .map(|label| label.to_lowercase().contains(&search))
+                .unwrap_or(false)
+        {
+            return false;
+        }
+
+        if let Some(min) = self.amount(&self.min_amount.value) {
+            if vault.amount < min {
+                return false;
+            }
+        }
+        if let Some(max) = self.amount(&self.max_amount.value) {
+            if vault.amount > max {
+                return false;
+            }
+        }
+
+        let timestamp = match self.date {
+            VaultDate::Received => vault.received_at,
+            VaultDate::Updated => vault.updated_at,
+        };
+        if let Some(after) = parse_date(&self.after.value) {
+            if timestamp < after.and_hms(0, 0, 0).timestamp() {
+                return false;
+            }
+        }
+        if let Some(before) = parse_date(&self.before.value) {
+            if timestamp > before.and_hms(23, 59, 59).timestamp() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn sort(&self, vaults: &mut [model::Vault]) {
+        match self.sort {
+            VaultSort::Newest => vaults.sort_by(|a, b| b.received_at.cmp(&a.received_at)),
+            VaultSort::Oldest => vaults.sort_by(|a, b| a.received_at.cmp(&b.received_at)),
+            VaultSort::LargestAmount => vaults.sort_by(|a, b| b.amount.cmp(&a.amount)),
+            VaultSort::SmallestAmount => vaults.sort_by(|a, b| a.amount.cmp(&b.amount)),
+            VaultSort::Status => vaults.sort_by_key(|v| status_rank(&v.status)),
+            VaultSort::DerivationIndex => vaults.sort_by_key(|v| v.derivation_index),
+        }
+    }
+}
+
+/// status_rank orders the statuses following the vault lifecycle.
+fn status_rank(status: &VaultStatus) -> usize {
+    VaultStatus::ALL
+        .iter()
+        .position(|s| s == status)
+        .unwrap_or(VaultStatus::ALL.len())
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_vault_label() {
+        let vault = model::Vault {
+            address: "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+            amount: 100_000,
+            derivation_index: 3,
+            received_at: 0,
+            status: VaultStatus::Funded,
+            txid: "e5a7d6d9c1b1e1e0d42e6c8eab4d1c7a2f6b1a3c2d1e0f9a8b7c6d5e4f3a2b1c".to_string(),
+            updated_at: 0,
+            vout: 1,
+        };
+        let label = "Payroll March".to_string();
+        let mut filter = VaultFilter::new(Denomination::default());
+
+        filter.search = "payroll".to_string();
+        assert!(filter.matches(&vault, None, Some(&label)));
+        assert!(!filter.matches(&vault, None, None));
+
+        filter.search = "e5a7d6".to_string();
+        assert!(filter.matches(&vault, None, None));
+
+        filter.search = "invoice".to_string();
+        assert!(!filter.matches(&vault, None, Some(&label)));
+    }
+}
@@ -1,25 +1,145 @@
-use iced::Element;
+use std::time::Duration;
+
+use iced::{scrollable, Column, Container, Element, Length};
 
 use crate::{
     app::{message::Message, view::layout},
-    ui::component,
+    revaultd::{
+        version::{MAX_SUPPORTED, MIN_SUPPORTED},
+        GetInfoResponse,
+    },
+    ui::component::{self, button, card, scroll, text},
 };
 
 pub fn charging_connect_view() -> Element<'static, Message> {
     layout::cover(component::text::paragraph("Connecting to daemon..."))
 }
 
-pub fn charging_starting_daemon_view() -> Element<'static, Message> {
-    layout::cover(component::text::paragraph("Starting daemon..."))
+pub fn charging_starting_daemon_view<'a>(
+    scroll: &'a mut scrollable::State,
+    logs: &[String],
+) -> Element<'a, Message> {
+    layout::cover(
+        Container::new(
+            Column::new()
+                .push(component::text::paragraph("Starting daemon..."))
+                .push(daemon_logs(scroll, logs))
+                .spacing(20),
+        )
+        .width(Length::Fill),
+    )
+}
+
+/// charging_syncing_view displays the sync progress of revaultd with the
+/// estimated remaining time, the app can be opened in read-only mode.
+pub fn charging_syncing_view<'a>(
+    info: Option<&GetInfoResponse>,
+    synced_blocks: u64,
+    eta: Option<Duration>,
+    read_only_button: &'a mut iced::button::State,
+) -> Element<'a, Message> {
+    let progress = info.map(|info| info.sync * 100.0).unwrap_or(0.0);
+    let details = if let Some(info) = info {
+        Column::new()
+            .push(text::simple(&format!(
+                "Block height: {} ({} blocks synced)",
+                info.blockheight, synced_blocks
+            )))
+            .push(text::simple(&format!(
+                "Time remaining: {}",
+                eta.map(format_duration)
+                    .unwrap_or_else(|| "estimating...".to_string())
+            )))
+            .push(text::small(&format!(
+                "Network: {}, revaultd {}",
+                info.network, info.version
+            )))
+            .spacing(10)
+    } else {
+        Column::new()
+    };
+    layout::cover(
+        Container::new(
+            Column::new()
+                .push(component::text::paragraph(&format!(
+                    "Syncing... {:.2}%",
+                    progress
+                )))
+                .push(details)
+                .push(
+                    button::transparent(
+                        read_only_button,
+                        button::button_content(None, "Open in read-only mode"),
+                    )
+                    .on_press(Message::OpenReadOnly),
+                )
+                .spacing(20),
+        )
+        .width(Length::Fill),
+    )
+}
+
+/// format_duration displays a duration rounded to the minute.
+fn format_duration(duration: Duration) -> String {
+    let minutes = (duration.as_secs() + 59) / 60;
+    if minutes < 60 {
+        format!("about {} min", minutes)
+    } else {
+        format!("about {}h {:02}min", minutes / 60, minutes % 60)
+    }
+}
+
+/// charging_incompatible_view explains that the running revaultd cannot be
+/// used with this version of the GUI.
+pub fn charging_incompatible_view(version: &str, stopped: bool) -> Element<'static, Message> {
+    layout::cover(
+        Container::new(
+            Column::new()
+                .push(component::text::paragraph(&format!(
+                    "revaultd {} is not compatible with this version of the GUI{}.",
+                    version,
+                    if stopped { ", the GUI stopped it" } else { "" }
+                )))
+                .push(text::simple(&format!(
+                    "The GUI supports revaultd from version {} up to version {} excluded. \
+                    Install a supported version of revaultd or a version of the GUI \
+                    supporting revaultd {}.",
+                    MIN_SUPPORTED, MAX_SUPPORTED, version
+                )))
+                .spacing(20),
+        )
+        .width(Length::Fill),
+    )
 }
 
-pub fn charging_syncing_view(progress: &f64) -> Element<'static, Message> {
-    layout::cover(component::text::paragraph(&format!(
-        "Syncing... {}%",
-        progress
-    )))
+pub fn charging_error_view<'a>(
+    error: &str,
+    scroll: &'a mut scrollable::State,
+    logs: &[String],
+) -> Element<'a, Message> {
+    layout::cover(
+        Container::new(
+            Column::new()
+                .push(component::text::paragraph(&format!("Error: {}", error)))
+                .push(daemon_logs(scroll, logs))
+                .spacing(20),
+        )
+        .width(Length::Fill),
+    )
 }
 
-pub fn charging_error_view(error: &str) -> Element<'static, Message> {
-    layout::cover(component::text::paragraph(&format!("Error: {}", error)))
+/// daemon_logs displays the output of the revaultd child process, if any.
+fn daemon_logs<'a>(
+    scroll_state: &'a mut scrollable::State,
+    logs: &[String],
+) -> Container<'a, Message> {
+    if logs.is_empty() {
+        return Container::new(Column::new());
+    }
+    let col = logs.iter().fold(Column::new().spacing(2), |col, line| {
+        col.push(text::small(line))
+    });
+    card::white(Container::new(scroll(scroll_state, Container::new(col))))
+        .width(Length::Fill)
+        .height(Length::Units(300))
 }
@@ -1,12 +1,20 @@
-use iced::{scrollable, Align, Column, Container, Element, Length, QRCode, Row};
+use std::path::PathBuf;
+
+use iced::{
+    scrollable, text_input, Align, Column, Container, Element, Length, QRCode, Row, TextInput,
+};
 
 use crate::{
     app::{
         error::Error,
         message::Message,
+        state::{DepositAddress, GAP_LIMIT, GAP_LIMIT_WARNING},
         view::{layout, sidebar::Sidebar, Context},
     },
-    ui::component::{button, card, navbar, scroll, text},
+    ui::{
+        color,
+        component::{button, card, form, navbar, scroll, text},
+    },
 };
 
 /// DepositView is the view rendering the deposit panel.
@@ -17,6 +25,11 @@ pub struct DepositView {
     qr_code: Option<iced::qr_code::State>,
     scroll: scrollable::State,
     copy_button: iced::button::State,
+    new_address_button: iced::button::State,
+    amount_input: text_input::State,
+    label_input: text_input::State,
+    copy_uri_button: iced::button::State,
+    export_button: iced::button::State,
 }
 
 impl DepositView {
@@ -26,19 +39,32 @@ impl DepositView {
             sidebar: Sidebar::new(),
             scroll: scrollable::State::new(),
             copy_button: iced::button::State::default(),
+            new_address_button: iced::button::State::default(),
+            amount_input: text_input::State::new(),
+            label_input: text_input::State::new(),
+            copy_uri_button: iced::button::State::default(),
+            export_button: iced::button::State::default(),
         }
     }
 
-    // Address is loaded directly in the view in order to cache the created qrcode.
-    pub fn load(&mut self, address: &bitcoin::Address) {
-        self.qr_code = iced::qr_code::State::new(address.to_string()).ok();
+    // Payment request is loaded directly in the view in order to cache the created qrcode.
+    pub fn load(&mut self, uri: &str) {
+        self.qr_code = iced::qr_code::State::new(uri).ok();
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
         warning: Option<&Error>,
-        address: Option<&bitcoin::Address>,
+        address: Option<(u32, &bitcoin::Address)>,
+        uri: Option<&str>,
+        amount: &form::Value<String>,
+        label: &str,
+        exported: Option<&PathBuf>,
+        addresses: &[DepositAddress],
+        derivation_paths: &[String],
+        gap: u32,
     ) -> Element<'a, Message> {
         let mut col = Column::new().align_items(Align::Center).spacing(20);
         if address.is_some() {
@@ -49,28 +75,193 @@ impl DepositView {
         if let Some(qr_code) = self.qr_code.as_mut() {
             col = col.push(Container::new(QRCode::new(qr_code).cell_size(5)));
         }
-        if let Some(addr) = address {
-            col = col.push(Container::new(
-                Row::new()
-                    .push(Container::new(text::bold(text::small(&addr.to_string()))))
-                    .push(
-                        button::clipboard(
-                            &mut self.copy_button,
-                            Message::Clipboard(addr.to_string()),
+        if let Some((index, addr)) = address {
+            col = col
+                .push(Container::new(
+                    Row::new()
+                        .push(Container::new(text::bold(text::small(&addr.to_string()))))
+                        .push(
+                            button::clipboard(
+                                &mut self.copy_button,
+                                Message::Clipboard(addr.to_string()),
+                            )
+                            .width(Length::Shrink),
                         )
-                        .width(Length::Shrink),
-                    )
-                    .align_items(Align::Center),
+                        .align_items(Align::Center),
+                ))
+                .push(
+                    text::small(&format!(
+                        "Derived from the deposit descriptor at index #{}",
+                        index
+                    ))
+                    .color(color::DARK_GREY),
+                );
+            if !derivation_paths.is_empty() {
+                col = col.push(
+                    derivation_paths
+                        .iter()
+                        .fold(
+                            Column::new().push(text::small(
+                                "Verify it on the signing devices with the derivation paths:",
+                            )),
+                            |col, path| col.push(text::small(path)),
+                        )
+                        .align_items(Align::Center)
+                        .spacing(5),
+                );
+            }
+        }
+
+        if let Some(uri) = uri {
+            col = col.push(payment_request(
+                ctx,
+                uri,
+                amount,
+                label,
+                &mut self.amount_input,
+                &mut self.label_input,
+                &mut self.copy_uri_button,
             ));
         }
+
+        let mut export_button = button::transparent(
+            &mut self.export_button,
+            button::button_content(None, "Export deposit slip"),
+        );
+        let mut new_address_button = button::primary(
+            &mut self.new_address_button,
+            button::button_content(None, "Generate a new address"),
+        );
+        if address.is_some() {
+            export_button = export_button.on_press(Message::ExportDepositSlip);
+            if gap < GAP_LIMIT {
+                new_address_button = new_address_button.on_press(Message::NewDepositAddress);
+            }
+        }
+        col = col.push(
+            Row::new()
+                .push(export_button)
+                .push(new_address_button)
+                .spacing(10),
+        );
+        if let Some(path) = exported {
+            col = col.push(text::success(text::small(&format!(
+                "Deposit slip exported to {}",
+                path.to_string_lossy()
+            ))));
+        }
+
+        let mut content = Column::new()
+            .spacing(20)
+            .push(card::white(Container::new(col)));
+        if gap >= GAP_LIMIT {
+            content = content.push(card::alert_warning(Container::new(text::simple(&format!(
+                "{} addresses were generated since the last deposit, \
+                     reuse one of them: funds sent to a new address may not be detected",
+                gap
+            )))));
+        } else if gap + GAP_LIMIT_WARNING >= GAP_LIMIT {
+            content = content.push(card::alert_warning(Container::new(text::simple(&format!(
+                "{} addresses were generated since the last deposit, \
+                     only {} more can be generated before receiving funds",
+                gap,
+                GAP_LIMIT - gap
+            )))));
+        }
+
+        if !addresses.is_empty() {
+            content = content.push(deposit_addresses(addresses));
+        }
+
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
             self.sidebar.view(ctx),
             layout::main_section(Container::new(scroll(
                 &mut self.scroll,
-                card::white(Container::new(col)),
+                Container::new(content),
             ))),
         )
         .into()
     }
 }
+
+/// payment_request displays the inputs of the BIP21 payment request and the request itself.
+fn payment_request<'a>(
+    ctx: &Context,
+    uri: &str,
+    amount: &form::Value<String>,
+    label: &str,
+    amount_input: &'a mut text_input::State,
+    label_input: &'a mut text_input::State,
+    copy_button: &'a mut iced::button::State,
+) -> Container<'a, Message> {
+    Container::new(
+        Column::new()
+            .push(
+                Row::new()
+                    .push(
+                        Container::new(
+                            form::Form::new(amount_input, "Amount (optional)", amount, |a| {
+                                Message::DepositAmountEdited(a)
+                            })
+                            .warning("Please enter a valid amount")
+                            .size(15)
+                            .padding(10)
+                            .render(),
+                        )
+                        .width(Length::FillPortion(1)),
+                    )
+                    .push(text::small(&ctx.converter.unit.to_string()))
+                    .push(
+                        TextInput::new(label_input, "Label (optional)", label, |l| {
+                            Message::DepositLabelEdited(l)
+                        })
+                        .size(15)
+                        .padding(10)
+                        .width(Length::FillPortion(2)),
+                    )
+                    .spacing(10)
+                    .align_items(Align::Center),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        Container::new(text::small(uri).color(color::DARK_GREY))
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        button::clipboard(copy_button, Message::Clipboard(uri.to_string()))
+                            .width(Length::Shrink),
+                    )
+                    .align_items(Align::Center),
+            )
+            .spacing(10),
+    )
+    .width(Length::Units(500))
+}
+
+/// deposit_addresses lists the issued addresses, the last issued first.
+fn deposit_addresses<'a, T: 'a>(addresses: &[DepositAddress]) -> Container<'a, T> {
+    let mut col = Column::new()
+        .push(text::bold(text::simple("Previous addresses")))
+        .spacing(10);
+    for addr in addresses.iter().rev() {
+        col = col.push(
+            Row::new()
+                .push(
+                    text::small(&format!("#{}", addr.derivation_index))
+                        .color(color::DARK_GREY)
+                        .width(Length::Units(50)),
+                )
+                .push(Container::new(text::small(&addr.address.to_string())).width(Length::Fill))
+                .push(if addr.funded {
+                    text::small("Received funds").color(color::SUCCESS)
+                } else {
+                    text::small("Unused").color(color::DARK_GREY)
+                })
+                .spacing(10)
+                .align_items(Align::Center),
+        );
+    }
+    card::simple(Container::new(col))
+}
@@ -1,11 +1,24 @@
-use iced::{scrollable, Align, Column, Container, Element, Length, Row};
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use iced::{scrollable, text_input, Align, Column, Container, Element, Length, Row};
 
 use crate::{
-    app::{error::Error, menu::Menu, message::Message, view::Context},
+    app::{
+        error::Error,
+        menu::Menu,
+        message::Message,
+        state::{EmergencyVault, EMERGENCY_CONFIRMATION},
+        view::{layout, sidebar::Sidebar, Context},
+    },
+    revault::TransactionKind,
+    revaultd::model::{BroadcastedTransaction, Vault, VaultStatus, VaultTransactions},
     ui::{
         color,
-        component::{button, card, scroll, text, ContainerBackgroundStyle},
-        icon::warning_icon,
+        component::{
+            button, card, form, navbar, scroll, separation, text, ContainerBackgroundStyle,
+        },
+        icon::{dot_icon, warning_icon},
     },
 };
 
@@ -14,6 +27,8 @@ pub struct EmergencyView {
     scroll: scrollable::State,
     close_button: iced::button::State,
     emergency_button: iced::button::State,
+    track_button: iced::button::State,
+    confirmation_input: text_input::State,
 }
 
 impl EmergencyView {
@@ -22,14 +37,21 @@ impl EmergencyView {
             scroll: scrollable::State::new(),
             close_button: iced::button::State::new(),
             emergency_button: iced::button::State::new(),
+            track_button: iced::button::State::new(),
+            confirmation_input: text_input::State::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
-        vaults_number: usize,
-        funds_amount: u64,
+        vaults: &[EmergencyVault],
+        emergency_address: Option<&String>,
+        fees: &HashMap<String, u64>,
+        fee_errors: &HashMap<String, String>,
+        total_fees: Option<u64>,
+        confirmation: &str,
         warning: Option<&Error>,
         loading: bool,
         processing: bool,
@@ -58,39 +80,76 @@ impl EmergencyView {
         }
 
         if !loading {
-            let mut emergency_button = button::primary(
-                &mut self.emergency_button,
-                button::button_content(None, "Emergency"),
-            );
+            let funds_amount = vaults.iter().fold(0, |acc, v| acc + v.vault.amount);
+            let summary = Row::new()
+                .push(text::simple(if success {
+                    "Sending"
+                } else {
+                    "This action will send"
+                }))
+                .push(text::bold(text::simple(&format!(
+                    " {} ",
+                    ctx.converter.converts(funds_amount)
+                ))))
+                .push(text::simple(&ctx.converter.unit.to_string()))
+                .push(text::simple(" from"))
+                .push(text::bold(text::simple(&format!(" {} ", vaults.len()))))
+                .push(text::simple("vaults"));
 
-            if !processing {
-                emergency_button = emergency_button.on_press(Message::Emergency);
-            }
+            let destination = Column::new()
+                .push(text::simple("to the Emergency Deep Vault"))
+                .push(text::bold(text::small(
+                    emergency_address
+                        .map(|addr| addr.as_str())
+                        .unwrap_or("Unknown emergency address"),
+                )))
+                .spacing(5)
+                .align_items(Align::Center);
 
             if !success {
+                let mut emergency_button = button::primary(
+                    &mut self.emergency_button,
+                    button::button_content(None, "Emergency"),
+                );
+
+                if !processing && confirmation == EMERGENCY_CONFIRMATION {
+                    emergency_button = emergency_button.on_press(Message::Emergency);
+                }
+
                 col = col.push(
                     card::border_primary(Container::new(
                         Column::new()
                             .push(warning_icon().color(color::PRIMARY))
                             .push(
                                 Column::new()
+                                    .push(summary)
+                                    .push(destination)
+                                    .spacing(5)
+                                    .align_items(Align::Center),
+                            )
+                            .push(preview(ctx, vaults, fees, fee_errors, total_fees))
+                            .push(
+                                Column::new()
+                                    .push(text::small(&format!(
+                                        "Type \"{}\" to confirm",
+                                        EMERGENCY_CONFIRMATION
+                                    )))
                                     .push(
-                                        Row::new()
-                                            .push(text::simple("This action will send"))
-                                            .push(text::bold(text::simple(&format!(
-                                                " {} ",
-                                                ctx.converter.converts(funds_amount)
-                                            ))))
-                                            .push(text::simple(&ctx.converter.unit.to_string()))
-                                            .push(text::simple(" from"))
-                                            .push(text::bold(text::simple(&format!(
-                                                " {} ",
-                                                vaults_number
-                                            ))))
-                                            .push(text::simple("vaults")),
+                                        form::Form::new(
+                                            &mut self.confirmation_input,
+                                            EMERGENCY_CONFIRMATION,
+                                            &form::Value {
+                                                value: confirmation.to_string(),
+                                                valid: true,
+                                            },
+                                            Message::EmergencyConfirmation,
+                                        )
+                                        .padding(10)
+                                        .size(15)
+                                        .render(),
                                     )
-                                    .push(text::simple("to the Emergency Deep Vault"))
-                                    .align_items(Align::Center),
+                                    .spacing(10)
+                                    .max_width(500),
                             )
                             .push(emergency_button)
                             .spacing(30)
@@ -107,24 +166,19 @@ impl EmergencyView {
                             .push(warning_icon().color(color::SUCCESS))
                             .push(
                                 Column::new()
-                                    .push(
-                                        Row::new()
-                                            .push(text::simple("Sending"))
-                                            .push(text::bold(text::simple(&format!(
-                                                " {} ",
-                                                ctx.converter.converts(funds_amount)
-                                            ))))
-                                            .push(text::simple(&ctx.converter.unit.to_string()))
-                                            .push(text::simple(" from"))
-                                            .push(text::bold(text::simple(&format!(
-                                                " {} ",
-                                                vaults_number
-                                            ))))
-                                            .push(text::simple("vaults")),
-                                    )
-                                    .push(text::simple("to the Emergency Deep Vault"))
+                                    .push(summary)
+                                    .push(destination)
+                                    .spacing(5)
                                     .align_items(Align::Center),
                             )
+                            .push(broadcast_status(vaults))
+                            .push(
+                                button::primary(
+                                    &mut self.track_button,
+                                    button::button_content(None, "Track the emergency"),
+                                )
+                                .on_press(Message::Menu(Menu::EmergencyTracking)),
+                            )
                             .spacing(30)
                             .align_items(Align::Center),
                     ))
@@ -143,3 +197,251 @@ impl EmergencyView {
             .into()
     }
 }
+
+fn emergency_transaction_name(kind: TransactionKind) -> &'static str {
+    if kind == TransactionKind::EmergencyUnvault {
+        "Unvault emergency transaction"
+    } else {
+        "Emergency transaction"
+    }
+}
+
+/// preview lists the vaults swept by the emergency transactions with their fees.
+/// The total fees are partial if the fees of some vaults could not be computed.
+fn preview<'a, T: 'a>(
+    ctx: &Context,
+    vaults: &[EmergencyVault],
+    fees: &HashMap<String, u64>,
+    fee_errors: &HashMap<String, String>,
+    total_fees: Option<u64>,
+) -> Container<'a, T> {
+    let mut col = Column::new().spacing(10);
+    for v in vaults {
+        let outpoint = v.vault.outpoint();
+        col = col.push(
+            Row::new()
+                .push(
+                    Column::new()
+                        .push(text::small(&outpoint))
+                        .push(
+                            text::small(&format!(
+                                "{}, {}",
+                                v.vault.status,
+                                emergency_transaction_name(v.kind)
+                            ))
+                            .color(color::DARK_GREY),
+                        )
+                        .width(Length::Fill),
+                )
+                .push(
+                    Column::new()
+                        .push(text::bold(text::small(&format!(
+                            "{} {}",
+                            ctx.converter.converts(v.vault.amount),
+                            ctx.converter.unit
+                        ))))
+                        .push(
+                            text::small(&match fees.get(&outpoint) {
+                                Some(fee) => format!(
+                                    "fees: {} {}",
+                                    ctx.converter.converts(*fee),
+                                    ctx.converter.unit
+                                ),
+                                None if fee_errors.contains_key(&outpoint) => {
+                                    "fees: unavailable".to_string()
+                                }
+                                None => "fees: unknown".to_string(),
+                            })
+                            .color(color::DARK_GREY),
+                        )
+                        .align_items(Align::End),
+                )
+                .spacing(20)
+                .align_items(Align::Center),
+        );
+    }
+    col = col.push(separation().width(Length::Fill)).push(
+        Row::new()
+            .push(text::bold(text::simple("Total fees")).width(Length::Fill))
+            .push(text::bold(text::simple(&match total_fees {
+                Some(fees) => format!("{} {}", ctx.converter.converts(fees), ctx.converter.unit),
+                None => "Computing...".to_string(),
+            }))),
+    );
+    if total_fees.is_some() && !fee_errors.is_empty() {
+        col = col.push(
+            text::small(&format!(
+                "The fees of {} vaults could not be computed, the total is partial",
+                fee_errors.len()
+            ))
+            .color(color::WARNING),
+        );
+    }
+    Container::new(col).max_width(800)
+}
+
+/// broadcast_status lists the vaults with the broadcast status
+/// of their emergency transaction.
+fn broadcast_status<'a, T: 'a>(vaults: &[EmergencyVault]) -> Container<'a, T> {
+    let mut col = Column::new().spacing(10);
+    for v in vaults {
+        let (status, status_color) = if v.broadcasted() {
+            ("Broadcasted", color::SUCCESS)
+        } else {
+            ("Pending", color::DARK_GREY)
+        };
+        col = col.push(
+            Row::new()
+                .push(
+                    Column::new()
+                        .push(text::small(&v.vault.outpoint()))
+                        .push(
+                            text::small(emergency_transaction_name(v.kind)).color(color::DARK_GREY),
+                        )
+                        .width(Length::Fill),
+                )
+                .push(
+                    Row::new()
+                        .push(dot_icon().size(5).color(status_color))
+                        .push(text::small(status).color(status_color))
+                        .spacing(5)
+                        .align_items(Align::Center),
+                )
+                .spacing(20)
+                .align_items(Align::Center),
+        );
+    }
+    Container::new(col).max_width(800)
+}
+
+/// EmergencyTrackingView follows the confirmation of the emergency transactions.
+#[derive(Debug)]
+pub struct EmergencyTrackingView {
+    scroll: scrollable::State,
+    sidebar: Sidebar,
+}
+
+impl EmergencyTrackingView {
+    pub fn new() -> Self {
+        EmergencyTrackingView {
+            scroll: scrollable::State::new(),
+            sidebar: Sidebar::new(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        vaults: &[Vault],
+        txs: &HashMap<String, VaultTransactions>,
+        in_progress: bool,
+        warning: Option<&Error>,
+        loading: bool,
+    ) -> Element<'a, Message> {
+        let mut col = Column::new()
+            .push(
+                Row::new()
+                    .push(warning_icon().color(color::PRIMARY))
+                    .push(text::bold(text::simple("Emergency")))
+                    .spacing(10)
+                    .align_items(Align::Center),
+            )
+            .spacing(20);
+
+        if !loading {
+            col = col.push(text::simple(if vaults.is_empty() {
+                "No vault is in an emergency status"
+            } else if in_progress {
+                "Emergency transactions are waiting to be confirmed"
+            } else {
+                "All the emergency transactions are confirmed"
+            }));
+            for vault in vaults {
+                col = col.push(tracked_vault(ctx, vault, txs.get(&vault.outpoint())));
+            }
+        }
+
+        layout::dashboard(
+            navbar(layout::navbar_warning(warning)),
+            self.sidebar.view(ctx),
+            layout::main_section(Container::new(scroll(
+                &mut self.scroll,
+                Container::new(col),
+            ))),
+        )
+        .into()
+    }
+}
+
+/// tracked_vault displays the confirmations of the emergency transaction of the vault.
+fn tracked_vault<'a, T: 'a>(
+    ctx: &Context,
+    vault: &Vault,
+    txs: Option<&VaultTransactions>,
+) -> Container<'a, T> {
+    let (name, tx): (&str, Option<&BroadcastedTransaction>) = match vault.status {
+        VaultStatus::UnvaultEmergencyVaulting | VaultStatus::UnvaultEmergencyVaulted => (
+            emergency_transaction_name(TransactionKind::EmergencyUnvault),
+            txs.and_then(|txs| txs.unvault_emergency.as_ref()),
+        ),
+        _ => (
+            emergency_transaction_name(TransactionKind::Emergency),
+            txs.and_then(|txs| txs.emergency.as_ref()),
+        ),
+    };
+
+    let done = vault.status == VaultStatus::EmergencyVaulted
+        || vault.status == VaultStatus::UnvaultEmergencyVaulted;
+    let confirmations = match (tx.and_then(|tx| tx.blockheight), ctx.health.blockheight) {
+        (Some(height), Some(tip)) if tip >= height => {
+            format!("{} confirmations", tip - height + 1)
+        }
+        (Some(height), _) => format!("Mined in block {}", height),
+        (None, _) => "Not confirmed yet".to_string(),
+    };
+
+    let mut details = Column::new()
+        .push(text::bold(text::small(&vault.outpoint())))
+        .push(text::small(&format!("{}: {}", name, confirmations)));
+    if let Some(tx) = tx {
+        details = details
+            .push(text::small(&tx.tx.txid().to_string()).color(color::DARK_GREY))
+            .push(
+                text::small(&format!(
+                    "Broadcasted at {}",
+                    NaiveDateTime::from_timestamp(tx.received_at, 0)
+                ))
+                .color(color::DARK_GREY),
+            );
+    }
+
+    let (status_color, status) = if done {
+        (color::SUCCESS, "Emergency vaulted")
+    } else {
+        (color::PRIMARY, "Emergency vaulting")
+    };
+
+    card::white(Container::new(
+        Row::new()
+            .push(details.spacing(5).width(Length::Fill))
+            .push(
+                Column::new()
+                    .push(text::bold(text::simple(&format!(
+                        "{} {}",
+                        ctx.converter.converts(vault.amount),
+                        ctx.converter.unit
+                    ))))
+                    .push(
+                        Row::new()
+                            .push(dot_icon().size(5).color(status_color))
+                            .push(text::small(status).color(status_color))
+                            .spacing(5)
+                            .align_items(Align::Center),
+                    )
+                    .align_items(Align::End),
+            )
+            .spacing(20)
+            .align_items(Align::Center),
+    ))
+    .width(Length::Fill)
+}
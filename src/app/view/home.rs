@@ -124,6 +124,7 @@ fn manager_overview<'a, T: 'a>(
                             .push(text::simple(&format!(" {}", ctx.converter.unit)))
                             .align_items(Align::Center),
                     )
+                    .push(layout::fiat_amount(&ctx.converter, active_funds))
                     .push(
                         Container::new(
                             text::simple("are available to managers")
@@ -146,6 +147,7 @@ fn manager_overview<'a, T: 'a>(
                             .push(text::simple(&format!(" {}", ctx.converter.unit)))
                             .align_items(Align::Center),
                     )
+                    .push(layout::fiat_amount(&ctx.converter, inactive_funds))
                     .push(
                         Container::new(
                             text::simple("are held by stakeholders")
@@ -316,6 +318,7 @@ impl StakeholderOverview {
                                 .push(text::simple(&format!(" {}", ctx.converter.unit)))
                                 .align_items(Align::Center),
                         )
+                        .push(layout::fiat_amount(&ctx.converter, total_amount))
                         .push(
                             Row::new()
                                 .push(Column::new().width(Length::Fill))
@@ -1,12 +1,14 @@
 use crate::{
-    app::error::Error,
+    app::{config::Theme, error::Error},
+    conversion::Converter,
+    revaultd::supervisor::DaemonStatus,
     ui::{
         color,
-        component::{card, image::revault_colored_logo, text},
+        component::{button, card, image::revault_colored_logo, text},
     },
 };
 
-use iced::{container, Column, Container, Element, Length, Row};
+use iced::{container, Align, Column, Container, Element, Length, Row};
 
 pub fn navbar_warning<'a, T: 'a>(warning: Option<&Error>) -> Option<Container<'a, T>> {
     if let Some(e) = warning {
@@ -18,6 +20,66 @@ pub fn navbar_warning<'a, T: 'a>(warning: Option<&Error>) -> Option<Container<'a
     None
 }
 
+/// read_only_banner is displayed above the current panel while the app is
+/// opened before the end of the sync.
+pub fn read_only_banner<'a, T: 'a>(progress: f64) -> Element<'a, T> {
+    card::alert_warning(Container::new(text::simple(&format!(
+        "Read-only mode: revaultd is syncing ({:.2}%), actions are available once it is synced.",
+        progress * 100.0
+    ))))
+    .width(Length::Fill)
+    .into()
+}
+
+/// reconnecting_banner is displayed above the current panel while the GUI
+/// tries to reconnect to revaultd. Once the GUI gave up, the retry message
+/// is given and the banner displays a button to try again.
+pub fn reconnecting_banner<'a, T: 'a + Clone>(
+    retry_button: &'a mut iced::button::State,
+    attempt: u32,
+    status: DaemonStatus,
+    retry: Option<T>,
+) -> Element<'a, T> {
+    match retry {
+        None => card::alert_warning(Container::new(text::simple(&format!(
+            "Connection to revaultd lost, reconnecting (attempt {}, revaultd {})...",
+            attempt + 1,
+            status
+        )))),
+        Some(retry) => card::alert_warning(Container::new(
+            Row::new()
+                .push(
+                    Container::new(text::simple(&format!(
+                        "Connection to revaultd lost, failed to reconnect after {} attempts \
+                        (revaultd {}).",
+                        attempt, status
+                    )))
+                    .width(Length::Fill),
+                )
+                .push(
+                    button::primary(retry_button, button::button_content(None, "Retry"))
+                        .on_press(retry),
+                )
+                .spacing(10)
+                .align_items(Align::Center),
+        )),
+    }
+    .width(Length::Fill)
+    .into()
+}
+
+/// fiat_amount displays the fiat value of the amount aligned to the right,
+/// or nothing if no fiat rate is known.
+pub fn fiat_amount<'a, T: 'a>(converter: &Converter, amount: u64) -> Container<'a, T> {
+    if let Some(value) = converter.fiat(amount) {
+        Container::new(text::small(&value).color(color::SECONDARY))
+            .width(Length::Fill)
+            .align_x(Align::End)
+    } else {
+        Container::new(Column::new())
+    }
+}
+
 pub fn cover<'a, T: 'a>(content: Container<'a, T>) -> Element<'a, T> {
     Column::new()
         .push(large_logo())
@@ -71,6 +133,7 @@ impl container::StyleSheet for SidebarStyle {
     fn style(&self) -> container::Style {
         container::Style {
             background: color::FOREGROUND.into(),
+            text_color: iced::Color::BLACK.into(),
             border_width: 1.0,
             border_color: color::SECONDARY,
             ..container::Style::default()
@@ -83,6 +146,7 @@ impl container::StyleSheet for SidebarMenuStyle {
     fn style(&self) -> container::Style {
         container::Style {
             background: color::FOREGROUND.into(),
+            text_color: iced::Color::BLACK.into(),
             ..container::Style::default()
         }
     }
@@ -105,12 +169,37 @@ pub fn main_section<'a, T: 'a>(menu: Container<'a, T>) -> Container<'a, T> {
         .height(Length::Fill)
 }
 
+/// The main section is transparent, its background is the one of the theme.
 pub struct MainSectionStyle;
 impl container::StyleSheet for MainSectionStyle {
     fn style(&self) -> container::Style {
-        container::Style {
-            background: color::BACKGROUND.into(),
-            ..container::Style::default()
+        container::Style::default()
+    }
+}
+
+/// themed applies the background and the text color of the theme to the content
+/// of the window. The cards keep their own colors.
+pub fn themed<'a, T: 'a>(theme: Theme, content: Element<'a, T>) -> Element<'a, T> {
+    Container::new(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(ThemeStyle(theme))
+        .into()
+}
+
+pub struct ThemeStyle(Theme);
+impl container::StyleSheet for ThemeStyle {
+    fn style(&self) -> container::Style {
+        match self.0 {
+            Theme::Light => container::Style {
+                background: color::BACKGROUND.into(),
+                ..container::Style::default()
+            },
+            Theme::Dark => container::Style {
+                background: color::DARK_BACKGROUND.into(),
+                text_color: color::FOREGROUND.into(),
+                ..container::Style::default()
+            },
         }
     }
 }
@@ -0,0 +1,127 @@
+use iced::{pick_list, scrollable, text_input, Column, Container, Element, Length, Row};
+
+use crate::{
+    app::{
+        error::Error,
+        message::{LogLevel, LogsMessage, Message},
+        view::{layout, sidebar::Sidebar, Context},
+    },
+    ui::{
+        component::{button, card, form, navbar, scroll, text, TransparentPickListStyle},
+        icon::clipboard_icon,
+    },
+};
+
+#[derive(Debug)]
+pub struct LogsView {
+    scroll: scrollable::State,
+    sidebar: Sidebar,
+    pick_level: pick_list::State<LogLevel>,
+    search_input: text_input::State,
+    refresh_button: iced::button::State,
+    diagnostics_button: iced::button::State,
+}
+
+impl LogsView {
+    pub fn new() -> Self {
+        LogsView {
+            scroll: scrollable::State::new(),
+            sidebar: Sidebar::new(),
+            pick_level: pick_list::State::default(),
+            search_input: text_input::State::new(),
+            refresh_button: iced::button::State::new(),
+            diagnostics_button: iced::button::State::new(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        warning: Option<&Error>,
+        lines: &[String],
+        level: LogLevel,
+        search: &str,
+        copied: bool,
+    ) -> Element<'a, Message> {
+        let controls = Row::new()
+            .push(
+                pick_list::PickList::new(
+                    &mut self.pick_level,
+                    &LogLevel::ALL[..],
+                    Some(level),
+                    |level| Message::Logs(LogsMessage::Level(level)),
+                )
+                .padding(10)
+                .style(TransparentPickListStyle),
+            )
+            .push(
+                form::Form::new(
+                    &mut self.search_input,
+                    "Search in the logs",
+                    &form::Value {
+                        value: search.to_string(),
+                        valid: true,
+                    },
+                    |search| Message::Logs(LogsMessage::Search(search)),
+                )
+                .padding(10)
+                .size(15)
+                .render()
+                .width(Length::Fill),
+            )
+            .push(
+                button::transparent(
+                    &mut self.refresh_button,
+                    button::button_content(None, "Refresh"),
+                )
+                .on_press(Message::Logs(LogsMessage::Refresh)),
+            )
+            .push(
+                button::primary(
+                    &mut self.diagnostics_button,
+                    button::button_content(
+                        Some(clipboard_icon()),
+                        if copied {
+                            "Diagnostics copied"
+                        } else {
+                            "Copy diagnostics"
+                        },
+                    ),
+                )
+                .on_press(Message::Logs(LogsMessage::CopyDiagnostics)),
+            )
+            .spacing(10)
+            .align_items(iced::Align::Center);
+
+        let content = if lines.is_empty() {
+            Column::new().push(text::simple("No log line"))
+        } else {
+            lines.iter().fold(Column::new().spacing(2), |col, line| {
+                col.push(text::small(line))
+            })
+        };
+
+        layout::dashboard(
+            navbar(layout::navbar_warning(warning)),
+            self.sidebar.view(ctx),
+            layout::main_section(Container::new(
+                Column::new()
+                    .push(controls)
+                    .push(text::small(
+                        "Logs of the revaultd log file followed by the output of \
+                         the revaultd started by the GUI.",
+                    ))
+                    .push(
+                        card::white(Container::new(scroll(
+                            &mut self.scroll,
+                            Container::new(content),
+                        )))
+                        .width(Length::Fill)
+                        .height(Length::Fill),
+                    )
+                    .spacing(20),
+            )),
+        )
+        .into()
+    }
+}
@@ -333,6 +333,7 @@ impl ManagerSendOutputView {
     }
     pub fn view(
         &mut self,
+        ctx: &Context,
         address: &form::Value<String>,
         amount: &form::Value<String>,
     ) -> Element<RecipientMessage> {
@@ -352,7 +353,7 @@ impl ManagerSendOutputView {
             .push(
                 form::Form::new(
                     &mut self.amount_input,
-                    "Amount in BTC, ex: 0.123",
+                    &format!("Amount in {}", ctx.converter.unit),
                     &amount,
                     RecipientMessage::AmountEdited,
                 )
@@ -485,9 +486,13 @@ impl ManagerSelectInputsView {
                 .push(header)
                 .push(
                     Container::new(text::bold(text::simple(&format!(
-                        "Select coins worth at least {} {}",
+                        "Select coins worth at least {} {}{}",
                         &ctx.converter.converts(output_amount),
-                        ctx.converter.unit
+                        ctx.converter.unit,
+                        ctx.converter
+                            .fiat(output_amount)
+                            .map(|value| format!(" ({})", value))
+                            .unwrap_or_default(),
                     ))))
                     .width(Length::Fill)
                     .align_x(Align::Center),
@@ -767,7 +772,13 @@ pub fn spend_tx_with_feerate_view<'a, T: 'a>(
                             "{}",
                             ctx.converter.converts(total_fees)
                         ))))
-                        .push(text::simple(&format!(" {}", ctx.converter.unit))),
+                        .push(text::simple(&format!(" {}", ctx.converter.unit)))
+                        .push(text::simple(
+                            &ctx.converter
+                                .fiat(total_fees)
+                                .map(|value| format!(" ({})", value))
+                                .unwrap_or_default(),
+                        )),
                 ),
             )
             .push(
@@ -3,8 +3,10 @@ mod deposit;
 mod emergency;
 mod home;
 mod layout;
+mod logs;
 pub mod manager;
 mod network;
+pub mod review;
 mod settings;
 mod sidebar;
 pub mod sign;
@@ -14,9 +16,12 @@ pub mod vault;
 mod vaults;
 
 pub use deposit::DepositView;
-pub use emergency::EmergencyView;
+pub use emergency::{EmergencyTrackingView, EmergencyView};
 pub use home::{ManagerHomeView, StakeholderHomeView};
+pub use layout::{read_only_banner, reconnecting_banner, themed};
+pub use logs::LogsView;
 pub use network::{ManagerNetworkView, StakeholderNetworkView};
+pub use review::ReviewView;
 pub use settings::SettingsView;
 pub use spend_transaction::{SpendTransactionListItemView, SpendTransactionView};
 pub use stakeholder::{StakeholderCreateVaultsView, StakeholderDelegateFundsView};
@@ -25,8 +30,15 @@ pub use vaults::VaultsView;
 
 use bitcoin::Network;
 
-use super::menu::Menu;
-use crate::{conversion::Converter, revault::Role};
+use super::{
+    config::{Preferences, Profile},
+    health::NetworkHealth,
+    menu::Menu,
+};
+use crate::{
+    conversion::{Converter, Denomination},
+    revault::Role,
+};
 
 /// Context stores display informations and features
 /// used directly by views. It does not store anything
@@ -35,9 +47,20 @@ pub struct Context {
     pub converter: Converter,
     pub network: Network,
     pub network_up: bool,
+    /// sync progress of revaultd while the app is opened in read-only mode
+    /// before the end of the sync.
+    pub syncing: Option<f64>,
     pub menu: Menu,
     pub role: Role,
     pub role_edit: bool,
+    /// health of the components revaultd connects to, updated in the background.
+    pub health: NetworkHealth,
+    /// relative timelock of the unvault descriptor, in blocks.
+    pub unvault_csv: Option<u32>,
+    pub preferences: Preferences,
+    /// profiles found in the datadir, the current one is first selected.
+    pub profiles: Vec<Profile>,
+    pub profile: Option<Profile>,
 }
 
 impl Context {
@@ -55,6 +78,12 @@ impl Context {
             menu,
             network,
             network_up: false,
+            syncing: None,
+            health: NetworkHealth::default(),
+            unvault_csv: None,
+            preferences: Preferences::default(),
+            profiles: Vec::new(),
+            profile: None,
         }
     }
 }
@@ -62,12 +91,18 @@ impl Context {
 impl std::default::Default for Context {
     fn default() -> Self {
         Context {
-            converter: Converter::new(Network::Bitcoin),
+            converter: Converter::new(Network::Bitcoin, Denomination::default()),
             network: Network::Bitcoin,
             network_up: false,
+            syncing: None,
             role: Role::Manager,
             menu: Menu::Home,
             role_edit: false,
+            health: NetworkHealth::default(),
+            unvault_csv: None,
+            preferences: Preferences::default(),
+            profiles: Vec::new(),
+            profile: None,
         }
     }
 }
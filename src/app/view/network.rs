@@ -1,8 +1,10 @@
+use chrono::NaiveDateTime;
 use iced::{scrollable, Column, Container, Element, Length, Row};
 
 use crate::{
     app::{
         error::Error,
+        health::{Component, ComponentHealth, NetworkHealth},
         message::Message,
         view::{layout, sidebar::Sidebar, Context},
     },
@@ -40,7 +42,7 @@ impl ManagerNetworkView {
                 &mut self.scroll,
                 Container::new(
                     Column::new()
-                        .push(bitcoin_core_card(blockheight))
+                        .push(network_cards(ctx, blockheight))
                         .spacing(20),
                 ),
             ))),
@@ -76,7 +78,7 @@ impl StakeholderNetworkView {
                 &mut self.scroll,
                 Container::new(
                     Column::new()
-                        .push(bitcoin_core_card(blockheight))
+                        .push(network_cards(ctx, blockheight))
                         .spacing(20),
                 ),
             ))),
@@ -85,24 +87,102 @@ impl StakeholderNetworkView {
     }
 }
 
-fn bitcoin_core_card<'a, T: 'a>(blockheight: Option<&u64>) -> Container<'a, T> {
+/// network_cards displays the health of each component revaultd connects to.
+fn network_cards<'a, T: 'a>(ctx: &Context, blockheight: Option<&u64>) -> Column<'a, T> {
+    let health = &ctx.health;
+    let mut col = Column::new().spacing(20);
+    if let Some(error) = &health.info_error {
+        col = col.push(card::alert_warning(Container::new(text::small(&format!(
+            "revaultd did not answer: {}",
+            error
+        )))));
+    }
+    for component in &health.components {
+        let mut card_col = component_header(component);
+        if component.component == Component::Bitcoind {
+            card_col = card_col.push(bitcoind_status(health, blockheight));
+        }
+        col = col.push(card::simple(Container::new(card_col)));
+    }
+    if let Some(timestamp) = health.last_check {
+        col = col.push(
+            text::small(&format!("Last check: {}", format_timestamp(timestamp)))
+                .color(color::DARK_GREY),
+        );
+    }
+    col
+}
+
+fn component_header<'a, T: 'a>(component: &ComponentHealth) -> Column<'a, T> {
+    let (status, status_color) = match component.reachable {
+        None => ("Checking", color::DARK_GREY),
+        Some(true) => ("Reachable", color::SUCCESS),
+        Some(false) => ("Unreachable", color::WARNING),
+    };
     let mut col = Column::new()
         .push(
             Row::new()
-                .push(Container::new(text::bold(text::simple("Bitcoin Core"))).width(Length::Fill))
+                .push(
+                    Container::new(
+                        Column::new()
+                            .push(text::bold(text::simple(&component.component.to_string())))
+                            .push(text::small(&component.host)),
+                    )
+                    .width(Length::Fill),
+                )
                 .push(
                     Container::new(
                         Row::new()
-                            .push(dot_icon().size(5).color(color::SUCCESS))
-                            .push(text::small("Running").color(color::SUCCESS))
+                            .push(dot_icon().size(5).color(status_color))
+                            .push(text::small(status).color(status_color))
                             .align_items(iced::Align::Center),
                     )
                     .width(Length::Shrink),
                 ),
         )
         .spacing(10);
-    if let Some(b) = blockheight {
-        col = col.push(
+
+    let mut details = Row::new().spacing(20);
+    if let Some(latency) = component.latency {
+        details = details.push(text::small(&format!("Latency: {} ms", latency.as_millis())));
+    }
+    details = details.push(text::small(&format!(
+        "Last contact: {}",
+        component
+            .last_contact
+            .map(format_timestamp)
+            .unwrap_or_else(|| "never".to_string())
+    )));
+    col = col.push(details);
+
+    if let Some(error) = &component.error {
+        col = col.push(text::small(error).color(color::WARNING));
+    }
+
+    if !component.outages.is_empty() {
+        let mut outages = Column::new()
+            .push(text::bold(text::small("Outages")))
+            .spacing(5);
+        for outage in &component.outages {
+            outages = outages.push(text::small(&format!(
+                "{} - {}: {}",
+                format_timestamp(outage.start),
+                outage
+                    .end
+                    .map(format_timestamp)
+                    .unwrap_or_else(|| "ongoing".to_string()),
+                outage.error
+            )));
+        }
+        col = col.push(outages);
+    }
+    col
+}
+
+fn bitcoind_status<'a, T: 'a>(health: &NetworkHealth, blockheight: Option<&u64>) -> Row<'a, T> {
+    let mut row = Row::new().spacing(20);
+    if let Some(b) = blockheight.or_else(|| health.blockheight.as_ref()) {
+        row = row.push(
             Row::new()
                 .push(badge::block())
                 .push(
@@ -113,5 +193,18 @@ fn bitcoin_core_card<'a, T: 'a>(blockheight: Option<&u64>) -> Container<'a, T> {
                 .spacing(10),
         );
     }
-    card::simple(Container::new(col))
+    if let Some(sync) = health.sync {
+        row = row.push(
+            Column::new()
+                .push(text::bold(text::simple("Sync progress")))
+                .push(text::simple(&format!("{:.2}%", sync * 100.0))),
+        );
+    }
+    row
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
 }
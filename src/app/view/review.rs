@@ -0,0 +1,226 @@
+use iced::{scrollable, Align, Column, Container, Element, Length, Row};
+
+use crate::{
+    app::{
+        error::Error,
+        message::{Message, VaultMessage},
+        view::{layout, sidebar::Sidebar, vault::unvault_timelock, Context},
+    },
+    revault::UnvaultTimelock,
+    revaultd::model::{SpendTx, Vault},
+    ui::{
+        color,
+        component::{badge, button, card, navbar, scroll, separation, text},
+    },
+};
+
+/// ReviewView lists the unvault attempts the stakeholder has to review.
+#[derive(Debug)]
+pub struct ReviewView {
+    scroll: scrollable::State,
+    sidebar: Sidebar,
+}
+
+impl ReviewView {
+    pub fn new() -> Self {
+        ReviewView {
+            scroll: scrollable::State::new(),
+            sidebar: Sidebar::new(),
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        ctx: &Context,
+        warning: Option<&Error>,
+        items: Vec<Element<'a, Message>>,
+        loading: bool,
+    ) -> Element<'a, Message> {
+        let mut col = Column::new()
+            .push(text::bold(text::simple("Unvault attempts")))
+            .push(text::small(
+                "Funds leaving a vault can be revaulted until the unvault timelock expires",
+            ))
+            .spacing(20);
+
+        if !loading {
+            if items.is_empty() {
+                col = col.push(card::simple(Container::new(text::simple(
+                    "No funds are moving, there is nothing to review",
+                ))));
+            } else {
+                col = col.push(Column::with_children(items).spacing(10));
+            }
+        }
+
+        layout::dashboard(
+            navbar(layout::navbar_warning(warning)),
+            self.sidebar.view(ctx),
+            layout::main_section(Container::new(scroll(
+                &mut self.scroll,
+                Container::new(col),
+            ))),
+        )
+        .into()
+    }
+}
+
+/// ReviewItemView displays an unvaulting vault with the spend transaction
+/// it is paying for and the actions available to the stakeholder.
+#[derive(Debug)]
+pub struct ReviewItemView {
+    revault_button: iced::button::State,
+    acknowledge_button: iced::button::State,
+}
+
+impl ReviewItemView {
+    pub fn new() -> Self {
+        ReviewItemView {
+            revault_button: iced::button::State::new(),
+            acknowledge_button: iced::button::State::new(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn view(
+        &mut self,
+        ctx: &Context,
+        vault: &Vault,
+        spend_tx: Option<&SpendTx>,
+        timelock: Option<&UnvaultTimelock>,
+        acknowledged: bool,
+        processing: bool,
+        success: bool,
+        warning: Option<&Error>,
+    ) -> Element<Message> {
+        let mut col = Column::new()
+            .push(
+                Row::new()
+                    .push(if success {
+                        badge::vault_canceling()
+                    } else {
+                        badge::vault_unvaulting()
+                    })
+                    .push(
+                        Column::new()
+                            .push(text::bold(text::small(&vault.outpoint())))
+                            .push(text::small(&vault.status.to_string())),
+                    )
+                    .push(Column::new().width(Length::Fill))
+                    .push(
+                        Row::new()
+                            .push(text::bold(text::simple(
+                                &ctx.converter.converts(vault.amount),
+                            )))
+                            .push(text::small(&format!(" {}", ctx.converter.unit)))
+                            .align_items(Align::Center),
+                    )
+                    .spacing(20)
+                    .align_items(Align::Center),
+            )
+            .spacing(20);
+
+        if let Some(timelock) = timelock {
+            col = col.push(unvault_timelock(ctx, timelock));
+        }
+
+        col = col
+            .push(separation().width(Length::Fill))
+            .push(destinations(ctx, spend_tx));
+
+        if let Some(error) = warning {
+            col = col.push(card::alert_warning(Container::new(text::small(
+                &error.to_string(),
+            ))));
+        }
+
+        let outpoint = vault.outpoint();
+        let mut actions = Row::new().spacing(10).align_items(Align::Center);
+        if success {
+            actions = actions.push(
+                Container::new(text::success(text::simple(
+                    "Revault transaction broadcasted",
+                )))
+                .width(Length::Fill),
+            );
+        } else {
+            let status = if acknowledged {
+                text::simple("Acknowledged").color(color::SUCCESS)
+            } else {
+                text::simple("Waiting for review").color(color::DARK_GREY)
+            };
+            actions = actions.push(Container::new(status).width(Length::Fill));
+
+            let mut acknowledge_button = button::transparent(
+                &mut self.acknowledge_button,
+                button::button_content(None, "Acknowledge"),
+            );
+            if !acknowledged && !processing {
+                acknowledge_button =
+                    acknowledge_button.on_press(Message::AcknowledgeUnvault(outpoint.clone()));
+            }
+
+            let mut revault_button = button::primary(
+                &mut self.revault_button,
+                button::button_content(None, if processing { "Revaulting" } else { "Revault" }),
+            );
+            if !processing {
+                revault_button =
+                    revault_button.on_press(Message::Vault(outpoint, VaultMessage::Revault));
+            }
+            actions = actions.push(acknowledge_button).push(revault_button);
+        }
+
+        card::white(Container::new(col.push(actions)))
+            .width(Length::Fill)
+            .into()
+    }
+}
+
+/// destinations lists the outputs of the spend transaction paid by the vault,
+/// the change and the CPFP outputs are not displayed.
+fn destinations<'a, T: 'a>(ctx: &Context, spend_tx: Option<&SpendTx>) -> Container<'a, T> {
+    let spend_tx = match spend_tx {
+        Some(tx) => tx,
+        None => {
+            return Container::new(
+                text::small("The spend transaction paid by this vault is unknown")
+                    .color(color::WARNING),
+            )
+        }
+    };
+
+    let mut col = Column::new()
+        .push(text::bold(text::small(&format!(
+            "Spend transaction {}",
+            spend_tx.psbt.global.unsigned_tx.txid()
+        ))))
+        .spacing(10);
+    for (i, output) in spend_tx.psbt.global.unsigned_tx.output.iter().enumerate() {
+        if Some(i) == spend_tx.change_index || i == spend_tx.cpfp_index {
+            continue;
+        }
+        let destination = bitcoin::Address::from_script(&output.script_pubkey, ctx.network)
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|| output.script_pubkey.to_string());
+        col = col.push(
+            Row::new()
+                .push(Container::new(text::small(&destination)).width(Length::Fill))
+                .push(text::bold(text::small(&format!(
+                    "{} {}",
+                    ctx.converter.converts(output.value),
+                    ctx.converter.unit
+                )))),
+        );
+    }
+    if spend_tx.deposit_outpoints.len() > 1 {
+        col = col.push(
+            text::small(&format!(
+                "This transaction spends {} vaults",
+                spend_tx.deposit_outpoints.len()
+            ))
+            .color(color::DARK_GREY),
+        );
+    }
+    Container::new(col)
+}
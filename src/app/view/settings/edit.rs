@@ -0,0 +1,243 @@
+use iced::{text_input, Align, Column, Container, Length, Row};
+
+use crate::{
+    app::{
+        message::{Message, SettingsMessage},
+        state::SettingsForm,
+    },
+    ui::component::{button, card, form, separation, text},
+};
+
+/// SettingsEditView holds the widget states of the revaultd configuration form.
+#[derive(Debug, Default)]
+pub struct SettingsEditView {
+    bitcoind_address_input: text_input::State,
+    cookie_path_input: text_input::State,
+    bitcoind_poll_input: text_input::State,
+    coordinator_host_input: text_input::State,
+    coordinator_noise_key_input: text_input::State,
+    coordinator_poll_input: text_input::State,
+    log_level_input: text_input::State,
+    peers: Vec<PeerView>,
+    add_peer_button: iced::button::State,
+    cancel_button: iced::button::State,
+    save_button: iced::button::State,
+}
+
+#[derive(Debug, Default)]
+struct PeerView {
+    host_input: text_input::State,
+    noise_key_input: text_input::State,
+    remove_button: iced::button::State,
+}
+
+impl SettingsEditView {
+    pub fn view<'a>(
+        &'a mut self,
+        settings: &SettingsForm,
+        processing: bool,
+    ) -> Container<'a, Message> {
+        self.peers
+            .resize_with(settings.peers.len(), PeerView::default);
+
+        let mut peers = Column::new().spacing(10);
+        for (i, (peer, view)) in settings.peers.iter().zip(self.peers.iter_mut()).enumerate() {
+            peers = peers.push(
+                Row::new()
+                    .push(
+                        Container::new(
+                            form::Form::new(&mut view.host_input, "Host", &peer.host, move |msg| {
+                                Message::Settings(SettingsMessage::PeerHostEdited(i, msg))
+                            })
+                            .warning("Please enter a valid address")
+                            .size(15)
+                            .padding(10)
+                            .render(),
+                        )
+                        .width(Length::FillPortion(1)),
+                    )
+                    .push(
+                        Container::new(
+                            form::Form::new(
+                                &mut view.noise_key_input,
+                                "Noise key",
+                                &peer.noise_key,
+                                move |msg| {
+                                    Message::Settings(SettingsMessage::PeerNoiseKeyEdited(i, msg))
+                                },
+                            )
+                            .warning("Noise key must be 32 bytes long")
+                            .size(15)
+                            .padding(10)
+                            .render(),
+                        )
+                        .width(Length::FillPortion(2)),
+                    )
+                    .push(
+                        button::transparent(
+                            &mut view.remove_button,
+                            button::button_content(None, "Remove"),
+                        )
+                        .on_press(Message::Settings(SettingsMessage::RemovePeer(i))),
+                    )
+                    .spacing(10)
+                    .align_items(Align::Center),
+            );
+        }
+
+        let peers_title = if settings.cosigners {
+            "Cosigners"
+        } else {
+            "Watchtowers"
+        };
+
+        let mut save_button = button::primary(
+            &mut self.save_button,
+            button::button_content(None, if processing { "Saving..." } else { "Save" }),
+        );
+        if !processing {
+            save_button = save_button.on_press(Message::Settings(SettingsMessage::Save));
+        }
+
+        card::simple(Container::new(
+            Column::new()
+                .push(
+                    Column::new()
+                        .push(text::bold(text::simple("Edit the revaultd configuration")))
+                        .push(text::small(
+                            "revaultd must be restarted to apply the new configuration",
+                        )),
+                )
+                .push(separation().width(Length::Fill))
+                .push(field(
+                    "Bitcoind address",
+                    form::Form::new(
+                        &mut self.bitcoind_address_input,
+                        "127.0.0.1:8332",
+                        &settings.bitcoind_address,
+                        |msg| Message::Settings(SettingsMessage::BitcoindAddressEdited(msg)),
+                    )
+                    .warning("Please enter a valid address")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(field(
+                    "Bitcoind cookie path",
+                    form::Form::new(
+                        &mut self.cookie_path_input,
+                        "Cookie path",
+                        &settings.cookie_path,
+                        |msg| Message::Settings(SettingsMessage::CookiePathEdited(msg)),
+                    )
+                    .warning("Please enter a valid path")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(field(
+                    "Bitcoind poll interval (seconds)",
+                    form::Form::new(
+                        &mut self.bitcoind_poll_input,
+                        "Default",
+                        &settings.bitcoind_poll,
+                        |msg| Message::Settings(SettingsMessage::BitcoindPollEdited(msg)),
+                    )
+                    .warning("Please enter a positive number of seconds")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(separation().width(Length::Fill))
+                .push(field(
+                    "Coordinator host",
+                    form::Form::new(
+                        &mut self.coordinator_host_input,
+                        "Host",
+                        &settings.coordinator_host,
+                        |msg| Message::Settings(SettingsMessage::CoordinatorHostEdited(msg)),
+                    )
+                    .warning("Please enter a valid address")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(field(
+                    "Coordinator noise key",
+                    form::Form::new(
+                        &mut self.coordinator_noise_key_input,
+                        "Noise key",
+                        &settings.coordinator_noise_key,
+                        |msg| Message::Settings(SettingsMessage::CoordinatorNoiseKeyEdited(msg)),
+                    )
+                    .warning("Noise key must be 32 bytes long")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(field(
+                    "Coordinator poll interval (seconds)",
+                    form::Form::new(
+                        &mut self.coordinator_poll_input,
+                        "Default",
+                        &settings.coordinator_poll,
+                        |msg| Message::Settings(SettingsMessage::CoordinatorPollEdited(msg)),
+                    )
+                    .warning("Please enter a positive number of seconds")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(separation().width(Length::Fill))
+                .push(
+                    Column::new()
+                        .push(text::bold(text::simple(peers_title)))
+                        .push(peers)
+                        .push(
+                            button::transparent(
+                                &mut self.add_peer_button,
+                                button::button_content(None, "Add"),
+                            )
+                            .on_press(Message::Settings(SettingsMessage::AddPeer)),
+                        )
+                        .spacing(10),
+                )
+                .push(separation().width(Length::Fill))
+                .push(field(
+                    "Log level",
+                    form::Form::new(
+                        &mut self.log_level_input,
+                        "Default",
+                        &settings.log_level,
+                        |msg| Message::Settings(SettingsMessage::LogLevelEdited(msg)),
+                    )
+                    .warning("Log level must be one of off, error, warn, info, debug, trace")
+                    .size(15)
+                    .padding(10)
+                    .render(),
+                ))
+                .push(
+                    Row::new()
+                        .push(Container::new(text::simple("")).width(Length::Fill))
+                        .push(
+                            button::cancel(
+                                &mut self.cancel_button,
+                                button::button_content(None, "Cancel"),
+                            )
+                            .on_press(Message::Settings(SettingsMessage::CancelEdit)),
+                        )
+                        .push(save_button)
+                        .spacing(10),
+                )
+                .spacing(20),
+        ))
+        .width(Length::Fill)
+    }
+}
+
+fn field<'a>(label: &str, input: Container<'a, Message>) -> Column<'a, Message> {
+    Column::new()
+        .push(text::bold(text::small(label)))
+        .push(input)
+        .spacing(5)
+}
@@ -1,24 +1,39 @@
-use iced::{scrollable, Column, Container, Element};
+use iced::{pick_list, scrollable, Align, Checkbox, Column, Container, Element, Length, Row};
 
 use crate::revault::Role;
 use crate::{
     app::{
+        config::{RefreshInterval, Theme, WindowSize},
         error::Error,
-        message::Message,
+        health::CHECK_INTERVAL,
+        message::{Message, PreferencesMessage, SettingsMessage},
+        state::SettingsForm,
         view::{layout, sidebar::Sidebar, Context},
     },
-    ui::component::{navbar, scroll},
+    conversion::{Denomination, FiatSource, Unit},
+    ui::component::{button, card, navbar, scroll, separation, text, TransparentPickListStyle},
 };
 
 use crate::revaultd::config::Config;
 
 mod boxes;
+mod edit;
 use boxes::*;
+use edit::SettingsEditView;
 
 #[derive(Debug)]
 pub struct SettingsView {
     scroll: scrollable::State,
     sidebar: Sidebar,
+    pick_unit: pick_list::State<Denomination>,
+    pick_currency: pick_list::State<String>,
+    pick_theme: pick_list::State<Theme>,
+    pick_role: pick_list::State<Role>,
+    pick_window_size: pick_list::State<WindowSize>,
+    pick_refresh_interval: pick_list::State<RefreshInterval>,
+    edit: SettingsEditView,
+    edit_button: iced::button::State,
+    restart_button: iced::button::State,
 }
 
 impl SettingsView {
@@ -26,24 +41,67 @@ impl SettingsView {
         SettingsView {
             sidebar: Sidebar::new(),
             scroll: scrollable::State::new(),
+            pick_unit: pick_list::State::default(),
+            pick_currency: pick_list::State::default(),
+            pick_theme: pick_list::State::default(),
+            pick_role: pick_list::State::default(),
+            pick_window_size: pick_list::State::default(),
+            pick_refresh_interval: pick_list::State::default(),
+            edit: SettingsEditView::default(),
+            edit_button: iced::button::State::default(),
+            restart_button: iced::button::State::default(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
         warning: Option<&Error>,
         config: Config,
+        fiat: Option<&FiatSource>,
+        form: Option<&SettingsForm>,
+        processing: bool,
+        saved: bool,
     ) -> Element<'a, Message> {
+        let mut boxes = Column::new().spacing(20);
+        if saved {
+            boxes = boxes.push(restart_box(&mut self.restart_button));
+        }
+        if let Some(form) = form {
+            boxes = boxes.push(self.edit.view(form, processing));
+        } else {
+            boxes = boxes
+                .push(
+                    Row::new()
+                        .push(Container::new(text::simple("")).width(Length::Fill))
+                        .push(
+                            button::primary(
+                                &mut self.edit_button,
+                                button::button_content(None, "Edit the configuration"),
+                            )
+                            .on_press(Message::Settings(SettingsMessage::Edit)),
+                        ),
+                )
+                .push(SettingsView::display_boxes(&ctx, &config));
+        }
+        boxes = boxes
+            .push(display_unit_box(ctx, &mut self.pick_unit))
+            .push(preferences_box(
+                ctx,
+                &mut self.pick_theme,
+                &mut self.pick_role,
+                &mut self.pick_window_size,
+                &mut self.pick_refresh_interval,
+            ));
+        if let Some(fiat) = fiat {
+            boxes = boxes.push(fiat_box(ctx, fiat, &mut self.pick_currency));
+        }
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
             self.sidebar.view(ctx),
             layout::main_section(Container::new(
-                scroll(
-                    &mut self.scroll,
-                    Container::new(SettingsView::display_boxes(&ctx, &config)),
-                )
-                .spacing(8),
+                scroll(&mut self.scroll, Container::new(boxes)).spacing(8),
             )),
         )
         .into()
@@ -67,3 +125,217 @@ impl SettingsView {
         column.push(boxes.scripts.display(config)).spacing(20)
     }
 }
+
+/// restart_box asks the user to restart revaultd once the new configuration is saved.
+fn restart_box<'a>(restart_button: &'a mut iced::button::State) -> Container<'a, Message> {
+    card::simple(Container::new(
+        Row::new()
+            .push(
+                Column::new()
+                    .push(text::bold(text::simple("Configuration saved")))
+                    .push(text::small(
+                        "revaultd must be restarted to apply the new configuration",
+                    ))
+                    .width(Length::Fill),
+            )
+            .push(
+                button::primary(
+                    restart_button,
+                    button::button_content(None, "Restart revaultd"),
+                )
+                .on_press(Message::RestartDaemon),
+            )
+            .spacing(20)
+            .align_items(Align::Center),
+    ))
+    .width(Length::Fill)
+}
+
+/// preferences_box lets the user edit the preferences of the GUI.
+fn preferences_box<'a>(
+    ctx: &Context,
+    pick_theme: &'a mut pick_list::State<Theme>,
+    pick_role: &'a mut pick_list::State<Role>,
+    pick_window_size: &'a mut pick_list::State<WindowSize>,
+    pick_refresh_interval: &'a mut pick_list::State<RefreshInterval>,
+) -> Container<'a, Message> {
+    let preferences = &ctx.preferences;
+    let notifications = preferences.notifications();
+    let mut column = Column::new()
+        .push(
+            Column::new()
+                .push(text::bold(text::simple("Preferences")))
+                .push(text::small(
+                    "Display settings saved in the GUI configuration",
+                )),
+        )
+        .push(separation().width(Length::Fill))
+        .push(preference(
+            "Theme",
+            pick_list::PickList::new(
+                pick_theme,
+                &Theme::ALL[..],
+                Some(preferences.theme()),
+                |theme| Message::Preferences(PreferencesMessage::Theme(theme)),
+            ),
+        ));
+
+    if ctx.role_edit {
+        column = column.push(preference(
+            "Role at startup",
+            pick_list::PickList::new(
+                pick_role,
+                &Role::ALL[..],
+                Some(preferences.default_role.unwrap_or(Role::Stakeholder)),
+                |role| Message::Preferences(PreferencesMessage::DefaultRole(role)),
+            ),
+        ));
+    }
+
+    column = column
+        .push(preference(
+            "Window size at startup",
+            pick_list::PickList::new(
+                pick_window_size,
+                &WindowSize::ALL[..],
+                preferences.window_size,
+                |size| Message::Preferences(PreferencesMessage::WindowSize(size)),
+            ),
+        ))
+        .push(preference(
+            "Network refresh interval",
+            pick_list::PickList::new(
+                pick_refresh_interval,
+                &RefreshInterval::ALL[..],
+                Some(RefreshInterval(
+                    preferences
+                        .refresh_interval_secs
+                        .unwrap_or_else(|| CHECK_INTERVAL.as_secs()),
+                )),
+                |interval| Message::Preferences(PreferencesMessage::RefreshInterval(interval.0)),
+            ),
+        ))
+        .push(separation().width(Length::Fill))
+        .push(
+            Checkbox::new(
+                notifications.network,
+                "Display the network status",
+                |enabled| Message::Preferences(PreferencesMessage::NetworkNotifications(enabled)),
+            )
+            .text_size(15),
+        )
+        .push(
+            Checkbox::new(
+                notifications.emergency,
+                "Display the emergency tracking at startup",
+                |enabled| Message::Preferences(PreferencesMessage::EmergencyNotifications(enabled)),
+            )
+            .text_size(15),
+        )
+        .spacing(20);
+
+    card::simple(Container::new(column)).width(Length::Fill)
+}
+
+fn preference<'a, T>(label: &str, pick: pick_list::PickList<'a, T, Message>) -> Row<'a, Message>
+where
+    T: 'a + ToString + Eq + Clone,
+    [T]: ToOwned<Owned = Vec<T>>,
+{
+    Row::new()
+        .push(Container::new(text::simple(label)).width(Length::Fill))
+        .push(
+            pick.text_size(15)
+                .padding(10)
+                .width(Length::Units(200))
+                .style(TransparentPickListStyle),
+        )
+        .spacing(20)
+        .align_items(Align::Center)
+}
+
+/// display_unit_box lets the user select the denomination of the amounts.
+fn display_unit_box<'a>(
+    ctx: &Context,
+    pick_unit: &'a mut pick_list::State<Denomination>,
+) -> Container<'a, Message> {
+    card::simple(Container::new(
+        Row::new()
+            .push(
+                Column::new()
+                    .push(text::bold(text::simple("Display unit")))
+                    .push(text::small("Unit used to display and enter the amounts"))
+                    .width(Length::Fill),
+            )
+            .push(
+                pick_list::PickList::new(
+                    pick_unit,
+                    &Denomination::ALL[..],
+                    Some(ctx.converter.unit.denomination),
+                    Message::DisplayUnit,
+                )
+                .text_size(15)
+                .padding(10)
+                .width(Length::Units(200))
+                .style(TransparentPickListStyle),
+            )
+            .spacing(20)
+            .align_items(Align::Center),
+    ))
+    .width(Length::Fill)
+}
+
+/// fiat_box displays the current fiat rate and lets the user select the currency
+/// among the ones supported by the price source.
+fn fiat_box<'a>(
+    ctx: &Context,
+    fiat: &FiatSource,
+    pick_currency: &'a mut pick_list::State<String>,
+) -> Container<'a, Message> {
+    let rate = match &ctx.converter.fiat {
+        Some(rate) => format!(
+            "1 {} = {:.2} {}",
+            Unit {
+                network: ctx.network,
+                denomination: Denomination::Bitcoin,
+            },
+            rate.price,
+            rate.currency
+        ),
+        None => "Price not available".to_string(),
+    };
+    card::simple(Container::new(
+        Column::new()
+            .push(
+                Row::new()
+                    .push(
+                        Column::new()
+                            .push(text::bold(text::simple("Fiat")))
+                            .push(text::small("Currency used to value the amounts"))
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        pick_list::PickList::new(
+                            pick_currency,
+                            fiat.source.currencies(),
+                            Some(fiat.currency.clone()),
+                            Message::FiatCurrency,
+                        )
+                        .text_size(15)
+                        .padding(10)
+                        .width(Length::Units(200))
+                        .style(TransparentPickListStyle),
+                    )
+                    .spacing(20)
+                    .align_items(Align::Center),
+            )
+            .push(separation().width(Length::Fill))
+            .push(
+                Row::new()
+                    .push(Container::new(text::bold(text::small("Rate"))).width(Length::Fill))
+                    .push(text::small(&rate)),
+            )
+            .spacing(20),
+    ))
+    .width(Length::Fill)
+}
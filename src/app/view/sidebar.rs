@@ -3,6 +3,7 @@ use iced::{pick_list, Column, Container, Length, Row};
 use crate::revault::Role;
 use crate::{
     app::{
+        config::Profile,
         menu::Menu,
         message::Message,
         view::{layout, Context},
@@ -11,8 +12,8 @@ use crate::{
         color,
         component::{button, separation, text, TransparentPickListStyle},
         icon::{
-            deposit_icon, dot_icon, home_icon, network_icon, person_check_icon, plus_icon,
-            send_icon, settings_icon, vaults_icon, warning_icon,
+            deposit_icon, dot_icon, history_icon, home_icon, network_icon, person_check_icon,
+            plus_icon, send_icon, settings_icon, shield_notif_icon, vaults_icon, warning_icon,
         },
     },
 };
@@ -20,14 +21,17 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct Sidebar {
     pick_role: pick_list::State<Role>,
+    pick_profile: pick_list::State<Profile>,
     deposit_menu_button: iced::button::State,
     delegate_menu_button: iced::button::State,
     emergency_menu_button: iced::button::State,
     home_menu_button: iced::button::State,
     vaults_menu_button: iced::button::State,
     network_menu_button: iced::button::State,
+    review_menu_button: iced::button::State,
     spend_menu_button: iced::button::State,
     settings_menu_button: iced::button::State,
+    logs_menu_button: iced::button::State,
 }
 
 impl Sidebar {
@@ -39,9 +43,12 @@ impl Sidebar {
             emergency_menu_button: iced::button::State::new(),
             vaults_menu_button: iced::button::State::new(),
             network_menu_button: iced::button::State::new(),
+            review_menu_button: iced::button::State::new(),
             spend_menu_button: iced::button::State::new(),
             settings_menu_button: iced::button::State::new(),
+            logs_menu_button: iced::button::State::new(),
             pick_role: pick_list::State::default(),
+            pick_profile: pick_list::State::default(),
         }
     }
 
@@ -61,6 +68,21 @@ impl Sidebar {
         } else {
             Container::new(text::simple(&context.role.to_string())).padding(10)
         };
+        let profile = if context.profiles.len() > 1 {
+            Container::new(
+                pick_list::PickList::new(
+                    &mut self.pick_profile,
+                    context.profiles.clone(),
+                    context.profile.clone(),
+                    Message::SwitchProfile,
+                )
+                .padding(10)
+                .width(Length::Units(200))
+                .style(TransparentPickListStyle),
+            )
+        } else {
+            Container::new(Column::new())
+        };
         let home_button = if context.menu == Menu::Home {
             button::primary(
                 &mut self.home_menu_button,
@@ -100,10 +122,12 @@ impl Sidebar {
                 .spacing(10)
                 .align_items(iced::Align::Center);
 
-            if context.network_up {
-                row = row.push(text::success(dot_icon().size(7)))
-            } else {
-                row = row.push(text::danger(dot_icon().size(7)))
+            if context.preferences.notifications().network {
+                row = row.push(if context.network_up {
+                    text::success(dot_icon().size(7))
+                } else {
+                    text::danger(dot_icon().size(7))
+                });
             }
 
             button::transparent(
@@ -129,8 +153,26 @@ impl Sidebar {
             .width(iced::Length::Units(200))
         };
 
+        let logs_button = if context.menu == Menu::Logs {
+            button::primary(
+                &mut self.logs_menu_button,
+                button::button_content(Some(history_icon()), "Logs"),
+            )
+            .on_press(Message::Menu(Menu::Logs))
+            .width(iced::Length::Units(200))
+        } else {
+            button::transparent(
+                &mut self.logs_menu_button,
+                button::button_content(Some(history_icon()), "Logs"),
+            )
+            .on_press(Message::Menu(Menu::Logs))
+            .width(iced::Length::Units(200))
+        };
+
         let mut actions = Column::new().spacing(15);
-        if context.role == Role::Manager {
+        if context.syncing.is_some() {
+            actions = actions.push(text::small("Actions are available once revaultd is synced"));
+        } else if context.role == Role::Manager {
             let deposit_button = if context.menu == Menu::Deposit {
                 button::primary(
                     &mut self.deposit_menu_button,
@@ -175,6 +217,21 @@ impl Sidebar {
                     .width(iced::Length::Units(200)),
                 )
             };
+            let action_review = if context.menu == Menu::Review {
+                button::primary(
+                    &mut self.review_menu_button,
+                    button::button_content(Some(shield_notif_icon()), "Review"),
+                )
+                .on_press(Message::Menu(Menu::Review))
+                .width(iced::Length::Units(200))
+            } else {
+                button::transparent(
+                    &mut self.review_menu_button,
+                    button::button_content(Some(shield_notif_icon()), "Review"),
+                )
+                .on_press(Message::Menu(Menu::Review))
+                .width(iced::Length::Units(200))
+            };
             actions = actions
                 .push(
                     button::transparent(
@@ -185,6 +242,7 @@ impl Sidebar {
                     .width(iced::Length::Units(200)),
                 )
                 .push(action_delegate)
+                .push(action_review)
                 .push(Container::new(
                     button::transparent(
                         &mut self.emergency_menu_button,
@@ -203,6 +261,7 @@ impl Sidebar {
         }
         layout::sidebar(
             layout::sidebar_menu(vec![
+                profile.width(Length::Units(200)),
                 role.width(Length::Units(200)),
                 separation().width(iced::Length::Units(200)),
                 Container::new(home_button.width(Length::Units(200))),
@@ -211,7 +270,12 @@ impl Sidebar {
                 separation().width(Length::Units(200)),
                 Container::new(actions.width(Length::Units(200))),
             ]),
-            Container::new(settings_button),
+            Container::new(
+                Column::new()
+                    .push(logs_button)
+                    .push(settings_button)
+                    .spacing(15),
+            ),
         )
     }
 }
@@ -1,6 +1,6 @@
 use bitcoin::util::psbt::PartiallySignedTransaction as Psbt;
 use chrono::NaiveDateTime;
-use iced::{scrollable, Align, Column, Container, Element, Length, Row};
+use iced::{scrollable, Align, Column, Container, Element, Length, QRCode, Row};
 
 use crate::{
     app::{
@@ -9,13 +9,14 @@ use crate::{
         view::Context,
     },
     ui::{
+        color,
         component::{badge, button, card, scroll, separation, text, ContainerBackgroundStyle},
         icon,
     },
 };
 
 use crate::{
-    revault::Role,
+    revault::{Role, UnvaultTimelock, Urgency},
     revaultd::model::{BroadcastedTransaction, Vault, VaultStatus, VaultTransactions},
 };
 
@@ -180,6 +181,7 @@ impl VaultOnChainTransactionsPanel {
         ctx: &Context,
         vault: &Vault,
         txs: &VaultTransactions,
+        read_only: bool,
     ) -> Element<Message> {
         let mut col = Column::new().spacing(20);
         if ctx.role == Role::Stakeholder {
@@ -194,16 +196,14 @@ impl VaultOnChainTransactionsPanel {
                                 .width(Length::Fill),
                             )
                             .push(
-                                Container::new(
+                                Container::new(action(
+                                    read_only,
                                     button::important(
                                         &mut self.action_button,
                                         button::button_content(None, "Create vault"),
-                                    )
-                                    .on_press(Message::Vault(
-                                        vault.outpoint(),
-                                        VaultMessage::Secure,
-                                    )),
-                                )
+                                    ),
+                                    Message::Vault(vault.outpoint(), VaultMessage::Secure),
+                                ))
                                 .width(Length::Shrink),
                             )
                             .align_items(Align::Center),
@@ -219,16 +219,14 @@ impl VaultOnChainTransactionsPanel {
                                 .width(Length::Fill),
                             )
                             .push(
-                                Container::new(
+                                Container::new(action(
+                                    read_only,
                                     button::important(
                                         &mut self.action_button,
                                         button::button_content(None, "Delegate vault"),
-                                    )
-                                    .on_press(Message::Vault(
-                                        vault.outpoint(),
-                                        VaultMessage::Delegate,
-                                    )),
-                                )
+                                    ),
+                                    Message::Vault(vault.outpoint(), VaultMessage::Delegate),
+                                ))
                                 .width(Length::Shrink),
                             )
                             .align_items(Align::Center),
@@ -238,22 +236,25 @@ impl VaultOnChainTransactionsPanel {
                     col = col.push(card::white(Container::new(
                         Row::new()
                             .push(
-                                Container::new(text::simple(
-                                    "Funds are moving, do you want to revault them?",
-                                ))
+                                Container::new(
+                                    Column::new()
+                                        .push(text::simple(
+                                            "Funds are moving, do you want to revault them?",
+                                        ))
+                                        .push(vault_timelock(ctx, txs))
+                                        .spacing(10),
+                                )
                                 .width(Length::Fill),
                             )
                             .push(
-                                Container::new(
+                                Container::new(action(
+                                    read_only,
                                     button::primary(
                                         &mut self.action_button,
                                         button::button_content(None, "Revault"),
-                                    )
-                                    .on_press(Message::Vault(
-                                        vault.outpoint(),
-                                        VaultMessage::SelectRevault,
-                                    )),
-                                )
+                                    ),
+                                    Message::Vault(vault.outpoint(), VaultMessage::SelectRevault),
+                                ))
                                 .width(Length::Shrink),
                             )
                             .align_items(Align::Center),
@@ -266,22 +267,25 @@ impl VaultOnChainTransactionsPanel {
             col = col.push(card::white(Container::new(
                 Row::new()
                     .push(
-                        Container::new(text::simple(
-                            "Funds are moving, do you want to revault them?",
-                        ))
+                        Container::new(
+                            Column::new()
+                                .push(text::simple(
+                                    "Funds are moving, do you want to revault them?",
+                                ))
+                                .push(vault_timelock(ctx, txs))
+                                .spacing(10),
+                        )
                         .width(Length::Fill),
                     )
                     .push(
-                        Container::new(
+                        Container::new(action(
+                            read_only,
                             button::primary(
                                 &mut self.action_button,
                                 button::button_content(None, "Revault"),
-                            )
-                            .on_press(Message::Vault(
-                                vault.outpoint(),
-                                VaultMessage::SelectRevault,
-                            )),
-                        )
+                            ),
+                            Message::Vault(vault.outpoint(), VaultMessage::SelectRevault),
+                        ))
                         .width(Length::Shrink),
                     )
                     .align_items(Align::Center),
@@ -311,6 +315,102 @@ impl VaultOnChainTransactionsPanel {
     }
 }
 
+/// action sends the message on press, unless the vault is in read-only mode
+/// while revaultd syncs: the button is then disabled with a hint.
+fn action<'a>(
+    read_only: bool,
+    button: iced::Button<'a, Message>,
+    message: Message,
+) -> Element<'a, Message> {
+    if read_only {
+        Column::new()
+            .push(button)
+            .push(text::small("Available once revaultd is synced"))
+            .align_items(Align::Center)
+            .spacing(5)
+            .into()
+    } else {
+        button.on_press(message).into()
+    }
+}
+
+/// vault_timelock displays the unvault timelock of the vault,
+/// the unvault transaction height is taken from its onchain transactions.
+fn vault_timelock<'a, T: 'a>(ctx: &Context, txs: &VaultTransactions) -> Container<'a, T> {
+    match ctx.unvault_csv {
+        Some(csv) => unvault_timelock(
+            ctx,
+            &UnvaultTimelock::new(csv, txs.unvault.as_ref().and_then(|tx| tx.blockheight)),
+        ),
+        None => Container::new(
+            text::small("The unvault timelock cannot be read from the unvault descriptor")
+                .color(color::DARK_GREY),
+        ),
+    }
+}
+
+/// unvault_timelock displays the blocks remaining before the managers can spend
+/// the unvaulted funds, with the estimated deadline to cancel the unvault.
+/// The tip is the last block height given by revaultd.
+pub fn unvault_timelock<'a, T: 'a>(ctx: &Context, timelock: &UnvaultTimelock) -> Container<'a, T> {
+    let tip = match ctx.health.blockheight {
+        Some(tip) => tip,
+        None => {
+            return Container::new(
+                text::small("Waiting for the block height to compute the timelock")
+                    .color(color::DARK_GREY),
+            )
+        }
+    };
+
+    if !timelock.started() {
+        return Container::new(
+            text::small(&format!(
+                "Unvault transaction not confirmed yet, funds will be spendable {} blocks after its confirmation",
+                timelock.csv
+            ))
+            .color(color::DARK_GREY),
+        );
+    }
+
+    let remaining = timelock.remaining_blocks(tip);
+    let urgency = timelock.urgency(tip);
+    let (label, label_color) = match urgency {
+        Urgency::Expired => ("Expired", color::WARNING),
+        Urgency::Critical => ("Critical", color::WARNING),
+        Urgency::Warning => ("Soon", color::PRIMARY),
+        Urgency::Normal => ("On time", color::SUCCESS),
+    };
+
+    let details = if urgency == Urgency::Expired {
+        "Timelock expired, the funds can be spent at any block".to_string()
+    } else {
+        format!(
+            "{} blocks left to cancel, until about {}",
+            remaining,
+            NaiveDateTime::from_timestamp(
+                timelock.deadline(tip, chrono::Utc::now().timestamp()),
+                0
+            )
+            .format("%Y-%m-%d %H:%M")
+        )
+    };
+
+    Container::new(
+        Row::new()
+            .push(
+                Row::new()
+                    .push(icon::dot_icon().size(5).color(label_color))
+                    .push(text::bold(text::small(label)).color(label_color))
+                    .spacing(5)
+                    .align_items(Align::Center),
+            )
+            .push(text::small(&details))
+            .spacing(10)
+            .align_items(Align::Center),
+    )
+}
+
 fn transaction<'a, T: 'a>(
     ctx: &Context,
     title: &str,
@@ -648,15 +748,27 @@ fn vault_delegate<'a>(
 #[derive(Debug)]
 pub struct SecureVaultView {
     retry_button: iced::button::State,
+    fetch_button: iced::button::State,
+    copy_button: iced::button::State,
+    qr_code: Option<iced::qr_code::State>,
 }
 
 impl SecureVaultView {
     pub fn new() -> Self {
         SecureVaultView {
             retry_button: iced::button::State::default(),
+            fetch_button: iced::button::State::default(),
+            copy_button: iced::button::State::default(),
+            qr_code: None,
         }
     }
 
+    // Address is loaded directly in the view in order to cache the created qrcode.
+    pub fn load(&mut self, emergency_address: &bitcoin::Address) {
+        self.qr_code = iced::qr_code::State::new(emergency_address.to_string()).ok();
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
@@ -665,6 +777,7 @@ impl SecureVaultView {
         emergency_tx: &(Psbt, bool),
         emergency_unvault_tx: &(Psbt, bool),
         cancel_tx: &(Psbt, bool),
+        emergency_address: Result<&bitcoin::Address, &Vec<String>>,
         signer: Element<'a, VaultMessage>,
     ) -> Element<'a, VaultMessage> {
         let mut row_transactions = Row::new();
@@ -783,10 +896,66 @@ impl SecureVaultView {
                     .align_items(Align::Center),
             ))
             .push(separation().width(Length::Fill))
-            .push(row_transactions.spacing(10))
-            .push(signer)
-            .spacing(20)
-            .push(Column::new());
+            .push(row_transactions.spacing(10));
+
+        col = match emergency_address {
+            Ok(address) => {
+                let mut row = Row::new().push(
+                    Column::new()
+                        .push(text::bold(text::simple(
+                            "The emergency transactions send the funds to:",
+                        )))
+                        .push(
+                            Row::new()
+                                .push(text::bold(text::small(&address.to_string())))
+                                .push(button::clipboard(
+                                    &mut self.copy_button,
+                                    VaultMessage::Sign(SignMessage::Clipboard(address.to_string())),
+                                ))
+                                .align_items(Align::Center),
+                        )
+                        .push(text::small(
+                            "Please check that it is the address of your Emergency Deep Vault",
+                        ))
+                        .spacing(10)
+                        .width(Length::Fill),
+                );
+                if let Some(qr_code) = self.qr_code.as_mut() {
+                    row = row.push(
+                        Container::new(QRCode::new(qr_code).cell_size(5)).width(Length::Shrink),
+                    );
+                }
+                col.push(card::simple(Container::new(row.spacing(10))))
+                    .push(signer)
+            }
+            Err(errors) => col.push(card::alert_warning(Container::new(
+                Row::new()
+                    .push(icon::warning_icon().color(color::WARNING))
+                    .push(
+                        errors
+                            .iter()
+                            .fold(Column::new(), |col, e| {
+                                col.push(text::bold(text::simple(e)))
+                            })
+                            .push(text::simple(
+                                "Do not sign these transactions, they could send the funds \
+                                 to an address you do not control.",
+                            ))
+                            .push(
+                                button::primary(
+                                    &mut self.fetch_button,
+                                    button::button_content(None, "Check again"),
+                                )
+                                .on_press(VaultMessage::Retry),
+                            )
+                            .spacing(10),
+                    )
+                    .spacing(20)
+                    .align_items(Align::Center),
+            ))),
+        }
+        .spacing(20)
+        .push(Column::new());
 
         if let Some(error) = warning {
             col = col.push(card::alert_warning(Container::new(
@@ -881,6 +1050,7 @@ impl RevaultVaultView {
         processing: &bool,
         success: &bool,
         warning: Option<&Error>,
+        read_only: bool,
     ) -> Element<'a, Message> {
         let mut col = Column::new();
         if let Some(error) = warning {
@@ -889,28 +1059,33 @@ impl RevaultVaultView {
             ))));
         }
 
-        let button_broadcast_action = if *processing {
+        let button_broadcast_action: Element<'a, Message> = if *processing {
             col = col.push(text::simple("waiting for revauld..."));
             button::primary(
                 &mut self.broadcast_button,
                 button::button_content(None, "Broadcasting"),
             )
+            .into()
         } else if *success {
             col = col.push(text::simple("The cancel transaction is broadcasted"));
             button::success(
                 &mut self.broadcast_button,
                 button::button_content(None, "Broadcasted"),
             )
+            .into()
         } else {
             col = col
                 .push(text::bold(text::simple("Revault vault")))
                 .push(text::simple("The cancel transaction will be broadcast"))
                 .push(text::simple("Are you sure to revault ?"));
-            button::primary(
-                &mut self.broadcast_button,
-                button::button_content(None, "Yes Revault"),
+            action(
+                read_only,
+                button::primary(
+                    &mut self.broadcast_button,
+                    button::button_content(None, "Yes Revault"),
+                ),
+                Message::Vault(vault.outpoint(), VaultMessage::Revault),
             )
-            .on_press(Message::Vault(vault.outpoint(), VaultMessage::Revault))
         };
 
         let col = col
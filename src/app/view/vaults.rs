@@ -1,13 +1,23 @@
-use iced::{pick_list, scrollable, Align, Column, Container, Element, Length, Row};
+use std::path::PathBuf;
+
+use iced::{
+    pick_list, scrollable, text_input, Align, Checkbox, Column, Container, Element, Length,
+    Rectangle, Row, Space,
+};
 
 use crate::{
     app::{
         error::Error,
-        message::{Message, VaultFilterMessage},
+        message::{Message, VaultDate, VaultFilterMessage, VaultSort},
+        state::VaultFilter,
         view::{layout, sidebar::Sidebar, Context},
     },
+    conversion::{Denomination, Unit},
+    export::AmountFormat,
     revaultd::model::VaultStatus,
-    ui::component::{navbar, scroll, text, TransparentPickListStyle},
+    ui::component::{
+        button, card, form, navbar, scroll, separation, text, TransparentPickListStyle,
+    },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,14 +34,13 @@ impl VaultsFilter {
         VaultsFilter::Moved,
     ];
 
-    pub fn new(statuses: &[VaultStatus]) -> VaultsFilter {
-        if statuses == VaultStatus::MOVING {
-            VaultsFilter::Moving
-        } else if statuses == VaultStatus::MOVED {
-            VaultsFilter::Moved
-        } else {
-            VaultsFilter::Current
-        }
+    /// returns the predefined filter matching the statuses,
+    /// or None if the user selected a custom set of statuses.
+    pub fn new(statuses: &[VaultStatus]) -> Option<VaultsFilter> {
+        VaultsFilter::ALL.iter().cloned().find(|filter| {
+            let expected = filter.statuses();
+            expected.len() == statuses.len() && expected.iter().all(|s| statuses.contains(s))
+        })
     }
 
     pub fn statuses(&self) -> &'static [VaultStatus] {
@@ -53,13 +62,36 @@ impl std::fmt::Display for VaultsFilter {
     }
 }
 
-/// VaultsView renders a list of vaults filtered by the status filter.
-/// If the loading field is true, only the status pick_list component is displayed.
+/// Height of a row of the vault list, all the rows have the same height so the
+/// rows out of the scrolled window are replaced by a blank space of the same size.
+const VAULT_ROW_HEIGHT: usize = 75;
+
+/// Padding of the scrolled content above the first row, set by the scroll component.
+const VAULT_LIST_PADDING: usize = 10;
+
+/// Highest blank space, a Length::Units is a u16.
+const MAX_SPACE_HEIGHT: usize = 60_000;
+
+/// VaultsView renders a list of vaults filtered by the vault filter.
+/// Only a window of the matching vaults around the scroll offset is rendered,
+/// the filter stays above the scrolled list so the offset maps to the rows.
+/// If the loading field is true, only the filter components are displayed.
 #[derive(Debug)]
 pub struct VaultsView {
     scroll: scrollable::State,
     sidebar: Sidebar,
     pick_filter: pick_list::State<VaultsFilter>,
+    pick_sort: pick_list::State<VaultSort>,
+    pick_date: pick_list::State<VaultDate>,
+    search_input: text_input::State,
+    min_amount_input: text_input::State,
+    max_amount_input: text_input::State,
+    after_input: text_input::State,
+    before_input: text_input::State,
+    criteria_button: iced::button::State,
+    reset_button: iced::button::State,
+    export_btc_button: iced::button::State,
+    export_sats_button: iced::button::State,
 }
 
 impl VaultsView {
@@ -68,59 +100,117 @@ impl VaultsView {
             sidebar: Sidebar::new(),
             scroll: scrollable::State::new(),
             pick_filter: pick_list::State::default(),
+            pick_sort: pick_list::State::default(),
+            pick_date: pick_list::State::default(),
+            search_input: text_input::State::new(),
+            min_amount_input: text_input::State::new(),
+            max_amount_input: text_input::State::new(),
+            after_input: text_input::State::new(),
+            before_input: text_input::State::new(),
+            criteria_button: iced::button::State::new(),
+            reset_button: iced::button::State::new(),
+            export_btc_button: iced::button::State::new(),
+            export_sats_button: iced::button::State::new(),
         }
     }
 
+    /// first_visible_row returns the index of the vault list row displayed
+    /// at the top of the scrolled window.
+    pub fn first_visible_row(&self) -> usize {
+        // The scrollable clamps its offset when it is scrolled, the bounds
+        // are not known outside of the layout so they do not limit it here.
+        let bounds = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: 0.0,
+            height: 0.0,
+        };
+        let content = Rectangle {
+            height: f32::MAX,
+            ..bounds
+        };
+        let offset = self.scroll.offset(bounds, content) as usize;
+        offset.saturating_sub(VAULT_LIST_PADDING) / VAULT_ROW_HEIGHT
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn view<'a>(
         &'a mut self,
         ctx: &Context,
         warning: Option<&Error>,
         vaults: Vec<Element<'a, Message>>,
-        vault_status_filter: &[VaultStatus],
+        first: usize,
+        matching: usize,
+        filter: &VaultFilter,
         loading: bool,
+        exporting: bool,
+        exported: Option<&Vec<PathBuf>>,
     ) -> Element<'a, Message> {
         let mut col = Column::new();
 
+        if let Some(paths) = exported {
+            let mut files = Column::new().push(text::simple("Vaults exported to:"));
+            for path in paths {
+                files = files.push(text::small(&path.to_string_lossy()));
+            }
+            col = col.push(card::success(Container::new(files.spacing(5))));
+        }
+
+        let mut header = Row::new();
         if !loading {
-            col = col
+            let mut export_btc = button::transparent(
+                &mut self.export_btc_button,
+                button::button_content(
+                    None,
+                    &format!(
+                        "Export ({})",
+                        Unit {
+                            network: ctx.network,
+                            denomination: Denomination::Bitcoin,
+                        }
+                    ),
+                ),
+            );
+            let mut export_sats = button::transparent(
+                &mut self.export_sats_button,
+                button::button_content(
+                    None,
+                    &format!(
+                        "Export ({})",
+                        Unit {
+                            network: ctx.network,
+                            denomination: Denomination::Satoshi,
+                        }
+                    ),
+                ),
+            );
+            if !exporting && matching > 0 {
+                export_btc = export_btc.on_press(Message::ExportVaults(AmountFormat::Bitcoin));
+                export_sats = export_sats.on_press(Message::ExportVaults(AmountFormat::Sats));
+            }
+            header = header
                 .push(
-                    Row::new()
-                        .push(
-                            Container::new(
-                                Row::new()
-                                    .push(text::bold(text::simple(&format!(" {}", vaults.len()))))
-                                    .push(text::simple(" vaults")),
-                            )
-                            .width(Length::Fill),
-                        )
-                        .push(
-                            pick_list::PickList::new(
-                                &mut self.pick_filter,
-                                &VaultsFilter::ALL[..],
-                                Some(VaultsFilter::new(vault_status_filter)),
-                                |filter| {
-                                    Message::FilterVaults(VaultFilterMessage::Status(
-                                        filter.statuses(),
-                                    ))
-                                },
-                            )
-                            .text_size(15)
-                            .padding(10)
-                            .width(Length::Units(200))
-                            .style(TransparentPickListStyle),
-                        )
-                        .align_items(Align::Center),
+                    Container::new(
+                        Row::new()
+                            .push(text::bold(text::simple(&format!(" {}", matching))))
+                            .push(text::simple(" vaults")),
+                    )
+                    .width(Length::Fill),
                 )
-                .push(Column::with_children(vaults).spacing(5));
+                .push(export_btc.width(Length::Shrink))
+                .push(export_sats.width(Length::Shrink));
         } else {
-            col = col.push(
-                Row::new()
-                    .push(Container::new(Row::new()).width(Length::Fill))
+            header = header.push(Container::new(Row::new()).width(Length::Fill));
+        }
+
+        col = col
+            .push(
+                header
                     .push(
                         pick_list::PickList::new(
                             &mut self.pick_filter,
                             &VaultsFilter::ALL[..],
-                            Some(VaultsFilter::new(vault_status_filter)),
+                            VaultsFilter::new(&filter.statuses),
                             |filter| {
                                 Message::FilterVaults(VaultFilterMessage::Status(filter.statuses()))
                             },
@@ -130,18 +220,232 @@ impl VaultsView {
                         .width(Length::Units(200))
                         .style(TransparentPickListStyle),
                     )
+                    .spacing(10)
+                    .align_items(Align::Center),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        form::Form::new(
+                            &mut self.search_input,
+                            "Search by outpoint, txid or address",
+                            &form::Value {
+                                value: filter.search.clone(),
+                                valid: true,
+                            },
+                            |search| Message::FilterVaults(VaultFilterMessage::Search(search)),
+                        )
+                        .padding(10)
+                        .size(15)
+                        .render()
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        pick_list::PickList::new(
+                            &mut self.pick_sort,
+                            &VaultSort::ALL[..],
+                            Some(filter.sort),
+                            |sort| Message::FilterVaults(VaultFilterMessage::Sort(sort)),
+                        )
+                        .text_size(15)
+                        .padding(10)
+                        .width(Length::Units(200))
+                        .style(TransparentPickListStyle),
+                    )
+                    .push(
+                        button::transparent(
+                            &mut self.criteria_button,
+                            button::button_content(
+                                None,
+                                if filter.show_criteria {
+                                    "Hide filters"
+                                } else {
+                                    "More filters"
+                                },
+                            ),
+                        )
+                        .on_press(Message::FilterVaults(VaultFilterMessage::ToggleCriteria)),
+                    )
+                    .push(
+                        button::transparent(
+                            &mut self.reset_button,
+                            button::button_content(None, "Reset"),
+                        )
+                        .on_press(Message::FilterVaults(VaultFilterMessage::Reset)),
+                    )
+                    .spacing(10)
                     .align_items(Align::Center),
             );
+
+        if filter.show_criteria {
+            col = col.push(criteria(
+                ctx,
+                filter,
+                &mut self.pick_date,
+                &mut self.min_amount_input,
+                &mut self.max_amount_input,
+                &mut self.after_input,
+                &mut self.before_input,
+            ));
+        }
+
+        let mut content = Column::new().push(col.spacing(20)).spacing(20);
+        if !loading {
+            let after = matching.saturating_sub(first + vaults.len());
+            let mut list = blank_rows(first);
+            for vault in vaults {
+                list = list.push(
+                    Container::new(vault)
+                        .width(Length::Fill)
+                        .height(Length::Units(VAULT_ROW_HEIGHT as u16)),
+                );
+            }
+            content = content.push(scroll(
+                &mut self.scroll,
+                Container::new(list.push(blank_rows(after))),
+            ));
         }
 
         layout::dashboard(
             navbar(layout::navbar_warning(warning)),
             self.sidebar.view(ctx),
-            layout::main_section(Container::new(scroll(
-                &mut self.scroll,
-                Container::new(col.spacing(20)),
-            ))),
+            layout::main_section(Container::new(content)),
         )
         .into()
     }
 }
+
+/// blank_rows takes the place of the vault list rows out of the scrolled window.
+fn blank_rows<'a>(rows: usize) -> Column<'a, Message> {
+    let mut height = rows * VAULT_ROW_HEIGHT;
+    let mut col = Column::new();
+    while height > 0 {
+        let h = height.min(MAX_SPACE_HEIGHT);
+        col = col.push(Space::with_height(Length::Units(h as u16)));
+        height -= h;
+    }
+    col
+}
+
+/// criteria displays the status, amount and date filters.
+fn criteria<'a>(
+    ctx: &Context,
+    filter: &VaultFilter,
+    pick_date: &'a mut pick_list::State<VaultDate>,
+    min_amount_input: &'a mut text_input::State,
+    max_amount_input: &'a mut text_input::State,
+    after_input: &'a mut text_input::State,
+    before_input: &'a mut text_input::State,
+) -> Container<'a, Message> {
+    let mut statuses = Column::new().spacing(10);
+    for row in VaultStatus::ALL.chunks(4) {
+        let mut checkboxes = Row::new().spacing(10);
+        for status in row {
+            let s = status.clone();
+            checkboxes = checkboxes.push(
+                Container::new(
+                    Checkbox::new(
+                        filter.statuses.contains(status),
+                        status.to_string(),
+                        move |selected| {
+                            Message::FilterVaults(VaultFilterMessage::ToggleStatus(
+                                s.clone(),
+                                selected,
+                            ))
+                        },
+                    )
+                    .text_size(15),
+                )
+                .width(Length::FillPortion(1)),
+            );
+        }
+        statuses = statuses.push(checkboxes);
+    }
+
+    card::simple(Container::new(
+        Column::new()
+            .push(text::bold(text::simple("Status")))
+            .push(statuses)
+            .push(separation().width(Length::Fill))
+            .push(text::bold(text::simple(&format!(
+                "Amount ({})",
+                ctx.converter.unit
+            ))))
+            .push(
+                Row::new()
+                    .push(
+                        form::Form::new(
+                            min_amount_input,
+                            "Minimum",
+                            &filter.min_amount,
+                            |amount| Message::FilterVaults(VaultFilterMessage::MinAmount(amount)),
+                        )
+                        .warning("Please enter a valid amount")
+                        .padding(10)
+                        .size(15)
+                        .render(),
+                    )
+                    .push(
+                        form::Form::new(
+                            max_amount_input,
+                            "Maximum",
+                            &filter.max_amount,
+                            |amount| Message::FilterVaults(VaultFilterMessage::MaxAmount(amount)),
+                        )
+                        .warning("Please enter a valid amount")
+                        .padding(10)
+                        .size(15)
+                        .render(),
+                    )
+                    .spacing(10),
+            )
+            .push(separation().width(Length::Fill))
+            .push(
+                Row::new()
+                    .push(text::bold(text::simple("Date")).width(Length::Fill))
+                    .push(
+                        pick_list::PickList::new(
+                            pick_date,
+                            &VaultDate::ALL[..],
+                            Some(filter.date),
+                            |date| Message::FilterVaults(VaultFilterMessage::Date(date)),
+                        )
+                        .text_size(15)
+                        .padding(10)
+                        .width(Length::Units(200))
+                        .style(TransparentPickListStyle),
+                    )
+                    .align_items(Align::Center),
+            )
+            .push(
+                Row::new()
+                    .push(
+                        form::Form::new(
+                            after_input,
+                            "After, ex: 2021-01-31",
+                            &filter.after,
+                            |date| Message::FilterVaults(VaultFilterMessage::After(date)),
+                        )
+                        .warning("Please enter a date with the YYYY-MM-DD format")
+                        .padding(10)
+                        .size(15)
+                        .render(),
+                    )
+                    .push(
+                        form::Form::new(
+                            before_input,
+                            "Before, ex: 2021-12-31",
+                            &filter.before,
+                            |date| Message::FilterVaults(VaultFilterMessage::Before(date)),
+                        )
+                        .warning("Please enter a date with the YYYY-MM-DD format")
+                        .padding(10)
+                        .size(15)
+                        .render(),
+                    )
+                    .spacing(10),
+            )
+            .spacing(20),
+    ))
+    .width(Length::Fill)
+}
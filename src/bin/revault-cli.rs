@@ -0,0 +1,515 @@
+//! revault-cli prints the state of the wallet without the graphical interface,
+//! for example over SSH. It is read-only and finds its configuration like the GUI.
+use std::error::Error;
+use std::io::Read;
+use std::path::PathBuf;
+
+use bitcoin::{
+    base64, consensus, hashes::hex::ToHex, util::psbt::PartiallySignedTransaction as Psbt, Network,
+};
+use chrono::NaiveDateTime;
+use serde_json::{json, Value};
+
+use revault_gui::{
+    app, cli,
+    conversion::Converter,
+    revault,
+    revaultd::{
+        config::Config as RevaultdConfig,
+        model::{SpendTx, Vault, VaultStatus},
+        RevaultD,
+    },
+};
+
+const USAGE: &str = "Usage: revault-cli [OPTIONS] <COMMAND>
+
+Options:
+    --conf <path>           GUI configuration file
+    --datadir <path>        Directory of the configuration files, default is ~/.revault
+    --network <network>     Use the profile of the network in the datadir:
+                            bitcoin, testnet or regtest
+    --json                  Print the result in JSON instead of a table
+    -v, --version           Print the version and exit
+    -h, --help              Print this help and exit
+
+Commands:
+    balance                 Print the balance of the vaults by status
+    list-vaults [<status>...]
+                            List the vaults, all of them if no status is given
+    list-spend-txs          List the spend transactions
+    decode-psbt [<base64>]  Decode a PSBT, read from stdin if it is not given";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Help,
+    Version,
+    Balance,
+    ListVaults(Vec<VaultStatus>),
+    ListSpendTxs,
+    DecodePsbt(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Args {
+    /// options shared with the GUI to find the configuration.
+    config: cli::Args,
+    json: bool,
+    command: Command,
+}
+
+impl Args {
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut config = cli::Args::default();
+        let mut json = false;
+        let mut command: Option<Command> = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let option = cli::Arg::new(&arg);
+            let mut value = || option.value(&mut args);
+            match option.name.as_str() {
+                "-h" | "--help" => return Ok(Self::with_command(config, Command::Help)),
+                "-v" | "--version" => return Ok(Self::with_command(config, Command::Version)),
+                "--conf" => config.conf = Some(PathBuf::from(value()?)),
+                "--datadir" => config.datadir = Some(PathBuf::from(value()?)),
+                "--network" => config.network = Some(cli::parse_network(&value()?)?),
+                "--json" => json = true,
+                // "-" is the PSBT read from stdin.
+                n if n.starts_with('-') && n != "-" => {
+                    return Err(format!("Unknown argument '{}'", arg))
+                }
+                n => command = Some(parse_command(command.take(), n)?),
+            }
+        }
+
+        config.check_conf()?;
+        Ok(Self {
+            config,
+            json,
+            command: command.ok_or_else(|| "Missing command".to_string())?,
+        })
+    }
+
+    fn with_command(config: cli::Args, command: Command) -> Self {
+        Self {
+            config,
+            json: false,
+            command,
+        }
+    }
+}
+
+/// parse_command parses the positional argument, which is either a command
+/// or an argument of the previous command.
+fn parse_command(command: Option<Command>, arg: &str) -> Result<Command, String> {
+    match (command, arg) {
+        (None, "balance") => Ok(Command::Balance),
+        (None, "list-vaults") => Ok(Command::ListVaults(Vec::new())),
+        (None, "list-spend-txs") => Ok(Command::ListSpendTxs),
+        (None, "decode-psbt") => Ok(Command::DecodePsbt(None)),
+        (Some(Command::ListVaults(mut statuses)), status) => {
+            statuses.push(parse_status(status)?);
+            Ok(Command::ListVaults(statuses))
+        }
+        (Some(Command::DecodePsbt(None)), psbt) => Ok(Command::DecodePsbt(Some(psbt.to_string()))),
+        _ => Err(format!("Unknown argument '{}'", arg)),
+    }
+}
+
+/// parse_status parses a vault status written like in the revaultd API.
+fn parse_status(status: &str) -> Result<VaultStatus, String> {
+    serde_json::from_value(json!(status)).map_err(|_| format!("Unknown vault status '{}'", status))
+}
+
+fn status_name(status: &VaultStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| status.to_string())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n", USAGE);
+            return Err(e.into());
+        }
+    };
+
+    match args.command {
+        Command::Help => println!("{}", USAGE),
+        Command::Version => println!("revault-cli {}", env!("CARGO_PKG_VERSION")),
+        Command::DecodePsbt(ref psbt) => {
+            // The network is only used to display the addresses, the PSBT can be
+            // decoded without any configuration.
+            let network = load_config(&args.config)
+                .map(|(_, revaultd_config)| revaultd_config.bitcoind_config.network)
+                .unwrap_or_else(|_| args.config.network.unwrap_or(Network::Bitcoin));
+            let psbt = match psbt {
+                Some(psbt) if psbt != "-" => psbt.clone(),
+                _ => {
+                    let mut psbt = String::new();
+                    std::io::stdin().read_to_string(&mut psbt)?;
+                    psbt
+                }
+            };
+            let psbt = decode_psbt(psbt.trim())?;
+            print(args.json, psbt_json(&psbt, network), || {
+                psbt_table(&psbt, network)
+            });
+        }
+        Command::Balance => {
+            let (config, revaultd) = connect(&args.config)?;
+            let converter = converter(&config, &revaultd);
+            let vaults = list_vaults(&revaultd, &[])?;
+            let balance = balance(&vaults);
+            print(
+                args.json,
+                json!(balance
+                    .iter()
+                    .map(|(status, count, amount)| json!({
+                        "status": status_name(status),
+                        "count": count,
+                        "amount": amount,
+                    }))
+                    .collect::<Vec<Value>>()),
+                || {
+                    table(
+                        &["status", "vaults", "amount"],
+                        balance
+                            .iter()
+                            .map(|(status, count, amount)| {
+                                vec![
+                                    status.to_string(),
+                                    count.to_string(),
+                                    format!("{} {}", converter.converts(*amount), converter.unit),
+                                ]
+                            })
+                            .collect(),
+                    )
+                },
+            );
+        }
+        Command::ListVaults(ref statuses) => {
+            let (config, revaultd) = connect(&args.config)?;
+            let converter = converter(&config, &revaultd);
+            let vaults = list_vaults(&revaultd, statuses)?;
+            print(
+                args.json,
+                json!(vaults
+                    .iter()
+                    .map(|vault| json!({
+                        "outpoint": vault.outpoint(),
+                        "status": status_name(&vault.status),
+                        "amount": vault.amount,
+                        "address": vault.address,
+                        "derivation_index": vault.derivation_index,
+                        "received_at": vault.received_at,
+                        "updated_at": vault.updated_at,
+                    }))
+                    .collect::<Vec<Value>>()),
+                || {
+                    table(
+                        &["outpoint", "status", "amount", "updated"],
+                        vaults
+                            .iter()
+                            .map(|vault| {
+                                vec![
+                                    vault.outpoint(),
+                                    vault.status.to_string(),
+                                    format!(
+                                        "{} {}",
+                                        converter.converts(vault.amount),
+                                        converter.unit
+                                    ),
+                                    format_timestamp(vault.updated_at),
+                                ]
+                            })
+                            .collect(),
+                    )
+                },
+            );
+        }
+        Command::ListSpendTxs => {
+            let (config, revaultd) = connect(&args.config)?;
+            let converter = converter(&config, &revaultd);
+            let spend_txs = revaultd.list_spend_txs(None).map_err(|e| e.to_string())?;
+            let spend_txs = spend_txs.spend_txs;
+            print(
+                args.json,
+                json!(spend_txs
+                    .iter()
+                    .map(|tx| json!({
+                        "txid": tx.psbt.global.unsigned_tx.txid().to_string(),
+                        "deposit_outpoints": tx.deposit_outpoints,
+                        "amount": spent_amount(tx),
+                        "fees": revault::fees(&tx.psbt),
+                    }))
+                    .collect::<Vec<Value>>()),
+                || {
+                    table(
+                        &["txid", "vaults", "amount", "fees"],
+                        spend_txs
+                            .iter()
+                            .map(|tx| {
+                                vec![
+                                    tx.psbt.global.unsigned_tx.txid().to_string(),
+                                    tx.deposit_outpoints.len().to_string(),
+                                    format!(
+                                        "{} {}",
+                                        converter.converts(spent_amount(tx)),
+                                        converter.unit
+                                    ),
+                                    revault::fees(&tx.psbt)
+                                        .map(|fees| {
+                                            format!(
+                                                "{} {}",
+                                                converter.converts(fees),
+                                                converter.unit
+                                            )
+                                        })
+                                        .unwrap_or_else(|| "unknown".to_string()),
+                                ]
+                            })
+                            .collect(),
+                    )
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// load_config reads the GUI configuration found like the GUI does,
+/// and the revaultd configuration it points to.
+fn load_config(args: &cli::Args) -> Result<(app::Config, RevaultdConfig), String> {
+    let path = args.config_path()?;
+    let config =
+        app::Config::from_file(&path).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))?;
+    let revaultd_config = RevaultdConfig::from_file(&config.revaultd_config_path)
+        .map_err(|e| format!("{}: {}", config.revaultd_config_path.to_string_lossy(), e))?;
+    Ok((config, revaultd_config))
+}
+
+/// connect connects to the running revaultd, it is never started by revault-cli.
+fn connect(args: &cli::Args) -> Result<(app::Config, RevaultD), String> {
+    let (config, revaultd_config) = load_config(args)?;
+    let revaultd = RevaultD::new(&revaultd_config)
+        .map_err(|e| format!("Failed to connect to revaultd: {}", e))?;
+    Ok((config, revaultd))
+}
+
+fn converter(config: &app::Config, revaultd: &RevaultD) -> Converter {
+    Converter::new(revaultd.network(), config.display_unit.unwrap_or_default())
+}
+
+fn list_vaults(revaultd: &RevaultD, statuses: &[VaultStatus]) -> Result<Vec<Vault>, String> {
+    let statuses = if statuses.is_empty() {
+        None
+    } else {
+        Some(statuses)
+    };
+    revaultd
+        .list_vaults(statuses, None)
+        .map(|res| res.vaults)
+        .map_err(|e| e.to_string())
+}
+
+/// balance returns the number of vaults and their total amount by status,
+/// for the statuses of at least one vault.
+fn balance(vaults: &[Vault]) -> Vec<(VaultStatus, usize, u64)> {
+    VaultStatus::ALL
+        .iter()
+        .filter_map(|status| {
+            let vaults: Vec<&Vault> = vaults.iter().filter(|v| v.status == *status).collect();
+            if vaults.is_empty() {
+                None
+            } else {
+                Some((
+                    status.clone(),
+                    vaults.len(),
+                    vaults.iter().map(|v| v.amount).sum(),
+                ))
+            }
+        })
+        .collect()
+}
+
+/// spent_amount is the amount of the outputs of the spend transaction,
+/// without the change and the CPFP outputs.
+fn spent_amount(tx: &SpendTx) -> u64 {
+    tx.psbt
+        .global
+        .unsigned_tx
+        .output
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) != tx.change_index && *i != tx.cpfp_index)
+        .map(|(_, output)| output.value)
+        .sum()
+}
+
+fn decode_psbt(psbt: &str) -> Result<Psbt, String> {
+    let bytes = base64::decode(psbt).map_err(|e| format!("Invalid base64: {}", e))?;
+    consensus::deserialize(&bytes).map_err(|e| format!("Invalid PSBT: {}", e))
+}
+
+fn output_address(script: &bitcoin::Script, network: Network) -> String {
+    bitcoin::Address::from_script(script, network)
+        .map(|address| address.to_string())
+        .unwrap_or_else(|| script.as_bytes().to_hex())
+}
+
+fn psbt_json(psbt: &Psbt, network: Network) -> Value {
+    let tx = &psbt.global.unsigned_tx;
+    json!({
+        "txid": tx.txid().to_string(),
+        "inputs": tx.input.iter().zip(&psbt.inputs).map(|(txin, input)| json!({
+            "outpoint": txin.previous_output.to_string(),
+            "value": input.witness_utxo.as_ref().map(|utxo| utxo.value),
+            "signatures": input.partial_sigs.len(),
+        })).collect::<Vec<Value>>(),
+        "outputs": tx.output.iter().map(|output| json!({
+            "address": output_address(&output.script_pubkey, network),
+            "value": output.value,
+        })).collect::<Vec<Value>>(),
+        "fees": revault::fees(psbt),
+    })
+}
+
+fn psbt_table(psbt: &Psbt, network: Network) -> String {
+    let tx = &psbt.global.unsigned_tx;
+    let inputs = table(
+        &["input", "value", "signatures"],
+        tx.input
+            .iter()
+            .zip(&psbt.inputs)
+            .map(|(txin, input)| {
+                vec![
+                    txin.previous_output.to_string(),
+                    input
+                        .witness_utxo
+                        .as_ref()
+                        .map(|utxo| format!("{} sats", utxo.value))
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    input.partial_sigs.len().to_string(),
+                ]
+            })
+            .collect(),
+    );
+    let outputs = table(
+        &["output", "value"],
+        tx.output
+            .iter()
+            .map(|output| {
+                vec![
+                    output_address(&output.script_pubkey, network),
+                    format!("{} sats", output.value),
+                ]
+            })
+            .collect(),
+    );
+    format!(
+        "txid: {}\nfees: {}\n\n{}\n\n{}",
+        tx.txid(),
+        revault::fees(psbt)
+            .map(|fees| format!("{} sats", fees))
+            .unwrap_or_else(|| "unknown".to_string()),
+        inputs,
+        outputs
+    )
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// print prints the value in JSON, or else the table.
+fn print<F: FnOnce() -> String>(json: bool, value: Value, table: F) {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).expect("JSON values are serializable")
+        );
+    } else {
+        println!("{}", table());
+    }
+}
+
+/// table aligns the rows in columns under the headers.
+fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(headers.to_vec())];
+    for row in &rows {
+        lines.push(line(row.iter().map(|c| c.as_str()).collect()));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        let args = parse(&[
+            "--network=testnet",
+            "--json",
+            "list-vaults",
+            "active",
+            "spent",
+        ])
+        .unwrap();
+        assert_eq!(args.config.network, Some(Network::Testnet));
+        assert!(args.json);
+        assert_eq!(
+            args.command,
+            Command::ListVaults(vec![VaultStatus::Active, VaultStatus::Spent])
+        );
+
+        assert_eq!(
+            parse(&["decode-psbt", "-"]).unwrap().command,
+            Command::DecodePsbt(Some("-".to_string()))
+        );
+        assert_eq!(
+            parse(&["balance", "--help"]).unwrap().command,
+            Command::Help
+        );
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["list-vaults", "unknown"]).is_err());
+        assert!(parse(&["balance", "list-spend-txs"]).is_err());
+        assert!(parse(&["decode-psbt", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn align_table() {
+        assert_eq!(
+            table(
+                &["status", "amount"],
+                vec![vec!["Active".to_string(), "1.00000000".to_string()]]
+            ),
+            "status  amount\nActive  1.00000000"
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bitcoin::Network;
+use miniscript::{Descriptor, DescriptorPublicKey};
+
+use crate::{
+    app::{
+        self,
+        config::{Overrides, DEFAULT_FILE_NAME},
+    },
+    conversion::FiatSource,
+    export::AmountFormat,
+    installer::append_network_suffix,
+    revaultd::{
+        config::{self as revaultd_config, default_datadir},
+        model::VaultStatus,
+        RevaultD,
+    },
+};
+
+pub const USAGE: &str = "Usage: revault-gui [OPTIONS] [COMMAND]
+
+Options:
+    --conf <path>           GUI configuration file
+    --datadir <path>        Directory of the configuration files, default is ~/.revault
+    --network <network>     Use the profile of the network in the datadir:
+                            bitcoin, testnet or regtest
+    --log-level <level>     Log level: info, debug or trace
+    --revaultd-path <path>  revaultd binary started if revaultd is not running
+    --no-daemon-start       Do not start revaultd if it is not running
+    -v, --version           Print the version and exit
+    -h, --help              Print this help and exit
+
+Commands (the GUI is launched if none is given):
+    check-config            Check the GUI and revaultd configurations
+                            and the connection to revaultd
+    print-descriptors       Print the descriptors of the revaultd configuration
+    export-vaults [--format <btc|sats>]
+                            Export the vaults and their transactions to csv files
+                            in the datadir of revaultd";
+
+pub const LOG_LEVELS: [&str; 3] = ["info", "debug", "trace"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Version,
+    CheckConfig,
+    PrintDescriptors,
+    ExportVaults { format: AmountFormat },
+}
+
+/// Arg is an argument of the command line. Options accept both
+/// `--option value` and `--option=value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    pub name: String,
+    inline_value: Option<String>,
+}
+
+impl Arg {
+    pub fn new(arg: &str) -> Self {
+        match arg.find('=') {
+            Some(i) if arg.starts_with("--") => Self {
+                name: arg[..i].to_string(),
+                inline_value: Some(arg[i + 1..].to_string()),
+            },
+            _ => Self {
+                name: arg.to_string(),
+                inline_value: None,
+            },
+        }
+    }
+
+    /// value returns the value written after `=`, or else the next argument.
+    pub fn value<I: Iterator<Item = String>>(&self, args: &mut I) -> Result<String, String> {
+        self.inline_value
+            .clone()
+            .or_else(|| args.next())
+            .ok_or_else(|| format!("Missing value for '{}'", self.name))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    pub conf: Option<PathBuf>,
+    pub datadir: Option<PathBuf>,
+    pub network: Option<Network>,
+    pub log_level: Option<String>,
+    pub overrides: Overrides,
+    /// The GUI is launched if there is no command.
+    pub command: Option<Command>,
+}
+
+impl Args {
+    /// parse parses the arguments, without the name of the binary.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let option = Arg::new(&arg);
+            let mut value = || option.value(&mut args);
+            match option.name.as_str() {
+                "-h" | "--help" => parsed.set_command(Command::Help)?,
+                "-v" | "--version" => parsed.set_command(Command::Version)?,
+                "--conf" => parsed.conf = Some(PathBuf::from(value()?)),
+                "--datadir" => parsed.datadir = Some(PathBuf::from(value()?)),
+                "--network" => parsed.network = Some(parse_network(&value()?)?),
+                "--log-level" => {
+                    let level = value()?;
+                    if !LOG_LEVELS.contains(&level.as_str()) {
+                        return Err(format!("Unknown log level '{}'", level));
+                    }
+                    parsed.log_level = Some(level);
+                }
+                "--revaultd-path" => parsed.overrides.revaultd_path = Some(PathBuf::from(value()?)),
+                "--no-daemon-start" => parsed.overrides.no_daemon_start = true,
+                "--format" => match &mut parsed.command {
+                    Some(Command::ExportVaults { format }) => {
+                        *format = match value()?.as_str() {
+                            "btc" => AmountFormat::Bitcoin,
+                            "sats" => AmountFormat::Sats,
+                            f => return Err(format!("Unknown format '{}'", f)),
+                        }
+                    }
+                    _ => return Err("'--format' is an option of export-vaults".to_string()),
+                },
+                "check-config" => parsed.set_command(Command::CheckConfig)?,
+                "print-descriptors" => parsed.set_command(Command::PrintDescriptors)?,
+                "export-vaults" => parsed.set_command(Command::ExportVaults {
+                    format: AmountFormat::Bitcoin,
+                })?,
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        parsed.check_conf()?;
+        Ok(parsed)
+    }
+
+    /// check_conf returns an error if the configuration file is given
+    /// with the options used to find it.
+    pub fn check_conf(&self) -> Result<(), String> {
+        if self.conf.is_some() && (self.datadir.is_some() || self.network.is_some()) {
+            return Err("'--conf' cannot be combined with '--datadir' or '--network'".to_string());
+        }
+        Ok(())
+    }
+
+    fn set_command(&mut self, command: Command) -> Result<(), String> {
+        if let Some(previous) = &self.command {
+            // Help and version win over the other commands.
+            if *previous == Command::Help || *previous == Command::Version {
+                return Ok(());
+            }
+            if command != Command::Help && command != Command::Version {
+                return Err("Only one command can be given".to_string());
+            }
+        }
+        self.command = Some(command);
+        Ok(())
+    }
+
+    pub fn datadir(&self) -> Result<PathBuf, String> {
+        match &self.datadir {
+            Some(datadir) => Ok(datadir.clone()),
+            None => {
+                default_datadir().map_err(|_| "Could not locate the default datadir".to_string())
+            }
+        }
+    }
+
+    /// config_path returns the path of the GUI configuration file given by the options.
+    /// Like the GUI, it falls back to the only profile of the datadir if there is
+    /// no default configuration file and no network is given.
+    pub fn config_path(&self) -> Result<PathBuf, String> {
+        if let Some(conf) = &self.conf {
+            return Ok(conf.clone());
+        }
+        let datadir = self.datadir()?;
+        let mut path = datadir.clone();
+        path.push(append_network_suffix(
+            DEFAULT_FILE_NAME,
+            &self.network.unwrap_or(Network::Bitcoin),
+        ));
+        if self.network.is_none() && !path.exists() {
+            let mut profiles = app::config::profiles(&datadir);
+            if profiles.len() == 1 {
+                return Ok(profiles.remove(0).path);
+            }
+        }
+        Ok(path)
+    }
+}
+
+pub fn parse_network(network: &str) -> Result<Network, String> {
+    match network {
+        "mainnet" => Ok(Network::Bitcoin),
+        _ => Network::from_str(network).map_err(|_| format!("Unknown network '{}'", network)),
+    }
+}
+
+/// run executes a command without launching the GUI.
+pub fn run(command: Command, args: &Args) -> Result<(), Box<dyn Error>> {
+    let path = args.config_path()?;
+    let config =
+        app::Config::from_file(&path).map_err(|e| format!("{}: {}", path.to_string_lossy(), e))?;
+    let revaultd_config = revaultd_config::Config::from_file(&config.revaultd_config_path)
+        .map_err(|e| format!("{}: {}", config.revaultd_config_path.to_string_lossy(), e))?;
+    match command {
+        Command::CheckConfig => check_config(&path, &config, &revaultd_config),
+        Command::PrintDescriptors => {
+            let scripts = &revaultd_config.scripts_config;
+            println!("deposit: {}", scripts.deposit_descriptor);
+            println!("unvault: {}", scripts.unvault_descriptor);
+            println!("cpfp: {}", scripts.cpfp_descriptor);
+            if let Some(checksum) = scripts.deposit_descriptor_checksum() {
+                println!("deposit descriptor checksum: {}", checksum);
+            }
+            if let Some(csv) = scripts.unvault_csv() {
+                println!("unvault timelock: {} blocks", csv);
+            }
+            Ok(())
+        }
+        Command::ExportVaults { format } => {
+            let revaultd = RevaultD::new(&revaultd_config).map_err(|e| e.to_string())?;
+            let vaults = revaultd
+                .list_vaults(Some(&VaultStatus::ALL), None)
+                .map_err(|e| e.to_string())?
+                .vaults;
+            let fiat = config
+                .fiat
+                .as_ref()
+                .map(FiatSource::from_config)
+                .transpose()
+                .map_err(|e| format!("Fiat valuation: {}", e))?;
+            let paths = iced::futures::executor::block_on(app::export_vaults(
+                Arc::new(revaultd),
+                vaults,
+                format,
+                fiat,
+            ))
+            .map_err(|e| e.to_string())?;
+            for path in paths {
+                println!("{}", path.to_string_lossy());
+            }
+            Ok(())
+        }
+        Command::Help | Command::Version => Ok(()),
+    }
+}
+
+/// check_config prints one line per check and fails if one of them failed.
+fn check_config(
+    path: &Path,
+    config: &app::Config,
+    revaultd_config: &revaultd_config::Config,
+) -> Result<(), Box<dyn Error>> {
+    let mut errors = 0;
+    let mut check = |name: &str, res: Result<String, String>| match res {
+        Ok(msg) => println!("[ok] {}: {}", name, msg),
+        Err(msg) => {
+            errors += 1;
+            println!("[error] {}: {}", name, msg);
+        }
+    };
+
+    check("GUI configuration", Ok(path.to_string_lossy().to_string()));
+    check(
+        "revaultd configuration",
+        Ok(config.revaultd_config_path.to_string_lossy().to_string()),
+    );
+    if let Some(revaultd_path) = config.revaultd_path() {
+        check(
+            "revaultd binary",
+            if revaultd_path.exists() {
+                Ok(revaultd_path.to_string_lossy().to_string())
+            } else {
+                Err(format!("{} not found", revaultd_path.to_string_lossy()))
+            },
+        );
+    }
+
+    let bitcoind = &revaultd_config.bitcoind_config;
+    check(
+        "bitcoind cookie",
+        if bitcoind.cookie_path.exists() {
+            Ok(bitcoind.cookie_path.to_string_lossy().to_string())
+        } else {
+            Err(format!(
+                "{} not found",
+                bitcoind.cookie_path.to_string_lossy()
+            ))
+        },
+    );
+
+    let scripts = &revaultd_config.scripts_config;
+    for (name, descriptor) in &[
+        ("deposit descriptor", &scripts.deposit_descriptor),
+        ("unvault descriptor", &scripts.unvault_descriptor),
+        ("cpfp descriptor", &scripts.cpfp_descriptor),
+    ] {
+        check(
+            name,
+            Descriptor::<DescriptorPublicKey>::from_str(descriptor)
+                .map(|_| "valid".to_string())
+                .map_err(|e| e.to_string()),
+        );
+    }
+
+    check(
+        "coordinator",
+        if !revaultd_config::is_valid_host(&revaultd_config.coordinator_host) {
+            Err(format!("invalid host {}", revaultd_config.coordinator_host))
+        } else if !revaultd_config::is_valid_noise_key(&revaultd_config.coordinator_noise_key) {
+            Err("noise key must be 32 bytes long".to_string())
+        } else {
+            Ok(revaultd_config.coordinator_host.clone())
+        },
+    );
+    if let Some(manager) = &revaultd_config.manager_config {
+        for cosigner in &manager.cosigners {
+            check(
+                "cosigner",
+                if !revaultd_config::is_valid_host(&cosigner.host) {
+                    Err(format!("invalid host {}", cosigner.host))
+                } else if !revaultd_config::is_valid_noise_key(&cosigner.noise_key) {
+                    Err("noise key must be 32 bytes long".to_string())
+                } else {
+                    Ok(cosigner.host.clone())
+                },
+            );
+        }
+    }
+    if let Some(stakeholder) = &revaultd_config.stakeholder_config {
+        for watchtower in &stakeholder.watchtowers {
+            check(
+                "watchtower",
+                if revaultd_config::is_valid_noise_key(&watchtower.noise_key) {
+                    Ok(watchtower.host.clone())
+                } else {
+                    Err("noise key must be 32 bytes long".to_string())
+                },
+            );
+        }
+    }
+
+    check(
+        "revaultd",
+        RevaultD::new(revaultd_config)
+            .and_then(|revaultd| revaultd.get_info())
+            .map(|info| {
+                format!(
+                    "version {}, network {}, block height {}, sync {:.0}%",
+                    info.version,
+                    info.network,
+                    info.blockheight,
+                    info.sync * 100.0
+                )
+            })
+            .map_err(|e| e.to_string()),
+    );
+
+    if errors > 0 {
+        return Err(format!("{} check(s) failed", errors).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+
+        let args = parse(&[
+            "--datadir=/tmp/revault",
+            "--network",
+            "testnet",
+            "--log-level",
+            "debug",
+            "--no-daemon-start",
+            "export-vaults",
+            "--format",
+            "sats",
+        ])
+        .unwrap();
+        assert_eq!(args.datadir, Some(PathBuf::from("/tmp/revault")));
+        assert_eq!(args.network, Some(Network::Testnet));
+        assert_eq!(args.log_level, Some("debug".to_string()));
+        assert!(args.overrides.no_daemon_start);
+        assert_eq!(
+            args.command,
+            Some(Command::ExportVaults {
+                format: AmountFormat::Sats
+            })
+        );
+        assert_eq!(
+            args.config_path().unwrap(),
+            PathBuf::from("/tmp/revault/revault_gui_testnet.toml")
+        );
+
+        assert_eq!(
+            parse(&["check-config", "--help"]).unwrap().command,
+            Some(Command::Help)
+        );
+        assert!(parse(&["--conf", "gui.toml", "--datadir", "/tmp"]).is_err());
+        assert!(parse(&["--conf"]).is_err());
+        assert!(parse(&["--log-level", "warn"]).is_err());
+        assert!(parse(&["--format", "sats"]).is_err());
+        assert!(parse(&["check-config", "print-descriptors"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+}
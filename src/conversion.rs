@@ -1,40 +1,624 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
 use bitcoin::Network;
+use serde::{Deserialize, Serialize};
 
 /// Converter purpose is to give a Conversion from a given amount in satoshis according to its
 /// parameters.
 pub struct Converter {
     pub unit: Unit,
+    /// fiat rate used to display the fiat value of amounts, if the user configured one.
+    pub fiat: Option<FiatRate>,
 }
 
 impl Converter {
-    pub fn new(bitcoin_network: Network) -> Self {
-        let unit = match bitcoin_network {
-            Network::Testnet => Unit::TestnetBitcoin,
-            Network::Bitcoin => Unit::Bitcoin,
-            Network::Regtest => Unit::RegtestBitcoin,
-        };
-        Self { unit }
+    pub fn new(bitcoin_network: Network, denomination: Denomination) -> Self {
+        Self {
+            unit: Unit {
+                network: bitcoin_network,
+                denomination,
+            },
+            fiat: None,
+        }
     }
 
-    /// converts amount in satoshis to BTC float.
-    pub fn converts(&self, amount: u64) -> f64 {
-        bitcoin::Amount::from_sat(amount).as_btc()
+    /// converts amount in satoshis to a string in the display denomination.
+    pub fn converts(&self, amount: u64) -> String {
+        self.unit.denomination.format(amount)
+    }
+
+    /// parses an amount written in the display denomination to satoshis.
+    pub fn parse(&self, amount: &str) -> Result<u64, ParseAmountError> {
+        self.unit.denomination.parse(amount)
+    }
+
+    /// returns the fiat value of the amount in satoshis with its currency,
+    /// if a fiat rate is known.
+    pub fn fiat(&self, amount: u64) -> Option<String> {
+        self.fiat.as_ref().map(|rate| rate.format(amount))
     }
 }
 
-/// Unit is the bitcoin ticker according to the network used.
-pub enum Unit {
-    TestnetBitcoin,
-    RegtestBitcoin,
-    Bitcoin,
+/// Unit is the bitcoin ticker according to the network used and the display denomination.
+pub struct Unit {
+    pub network: Network,
+    pub denomination: Denomination,
 }
 
 impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let prefix = match self.network {
+            Network::Testnet => "t",
+            Network::Regtest => "r",
+            Network::Bitcoin => "",
+        };
+        match self.denomination {
+            Denomination::Bitcoin => write!(f, "{}BTC", prefix),
+            Denomination::MilliBitcoin => write!(f, "{}mBTC", prefix),
+            Denomination::Bits => write!(f, "{}bits", prefix),
+            Denomination::Satoshi => write!(f, "{}sats", prefix),
+        }
+    }
+}
+
+/// Denomination is the unit chosen by the user to display and input the amounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Denomination {
+    #[serde(rename = "btc")]
+    Bitcoin,
+    #[serde(rename = "mbtc")]
+    MilliBitcoin,
+    #[serde(rename = "bits")]
+    Bits,
+    #[serde(rename = "sats")]
+    Satoshi,
+}
+
+/// Maximum amount of satoshis that will ever exist.
+const MAX_MONEY: u64 = 21_000_000 * 100_000_000;
+
+impl Denomination {
+    pub const ALL: [Denomination; 4] = [
+        Denomination::Bitcoin,
+        Denomination::MilliBitcoin,
+        Denomination::Bits,
+        Denomination::Satoshi,
+    ];
+
+    /// number of decimals of an amount in satoshis written in the denomination.
+    pub fn decimals(&self) -> u32 {
+        match self {
+            Self::Bitcoin => 8,
+            Self::MilliBitcoin => 5,
+            Self::Bits => 2,
+            Self::Satoshi => 0,
+        }
+    }
+
+    /// formats the amount in satoshis with all its decimals and
+    /// a comma separating the thousands of the integer part.
+    pub fn format(&self, amount: u64) -> String {
+        let decimals = self.decimals();
+        let size = 10_u64.pow(decimals);
+        let integer = group_thousands(amount / size);
+        if decimals == 0 {
+            integer
+        } else {
+            format!(
+                "{}.{:0width$}",
+                integer,
+                amount % size,
+                width = decimals as usize
+            )
+        }
+    }
+
+    /// parses an amount written in the denomination to satoshis.
+    /// Commas are only accepted as thousands separators of the integer part.
+    pub fn parse(&self, amount: &str) -> Result<u64, ParseAmountError> {
+        let amount = amount.trim();
+        let mut parts = amount.splitn(2, '.');
+        let integer = parts.next().unwrap_or_default();
+        let fraction = parts.next().unwrap_or_default();
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(ParseAmountError::Invalid);
+        }
+
+        let integer = ungroup_thousands(integer)?;
+        if !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ParseAmountError::Invalid);
+        }
+
+        let decimals = self.decimals() as usize;
+        if fraction.len() > decimals {
+            return Err(ParseAmountError::TooPrecise);
+        }
+
+        let integer: u64 = if integer.is_empty() {
+            0
+        } else {
+            integer.parse().map_err(|_| ParseAmountError::TooBig)?
+        };
+        let fraction: u64 = if fraction.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", fraction, width = decimals)
+                .parse()
+                .map_err(|_| ParseAmountError::Invalid)?
+        };
+
+        integer
+            .checked_mul(10_u64.pow(decimals as u32))
+            .and_then(|amount| amount.checked_add(fraction))
+            .filter(|amount| *amount <= MAX_MONEY)
+            .ok_or(ParseAmountError::TooBig)
+    }
+}
+
+impl Default for Denomination {
+    fn default() -> Self {
+        Self::Bitcoin
+    }
+}
+
+impl std::fmt::Display for Denomination {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::TestnetBitcoin => write!(f, "tBTC"),
-            Self::RegtestBitcoin => write!(f, "rBTC"),
             Self::Bitcoin => write!(f, "BTC"),
+            Self::MilliBitcoin => write!(f, "mBTC"),
+            Self::Bits => write!(f, "bits"),
+            Self::Satoshi => write!(f, "sats"),
+        }
+    }
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// removes the thousands separators of the integer part,
+/// and checks that the groups are well-formed.
+fn ungroup_thousands(integer: &str) -> Result<String, ParseAmountError> {
+    let groups: Vec<&str> = integer.split(',').collect();
+    for (i, group) in groups.iter().enumerate() {
+        if !group.chars().all(|c| c.is_ascii_digit())
+            || (groups.len() > 1 && (group.is_empty() || group.len() > 3))
+            || (i > 0 && group.len() != 3)
+        {
+            return Err(ParseAmountError::Invalid);
         }
     }
+    Ok(groups.concat())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseAmountError {
+    Invalid,
+    TooPrecise,
+    TooBig,
+}
+
+impl std::fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "Invalid amount"),
+            Self::TooPrecise => write!(f, "Amount has too many decimals"),
+            Self::TooBig => write!(f, "Amount is too big"),
+        }
+    }
+}
+
+impl std::error::Error for ParseAmountError {}
+
+/// Interval between two fetches of the current fiat rate.
+pub const RATE_REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+
+/// FiatRate is the price of one bitcoin in a fiat currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiatRate {
+    pub currency: String,
+    pub price: f64,
+}
+
+impl FiatRate {
+    /// converts amount in satoshis to its fiat value.
+    pub fn converts(&self, amount: u64) -> f64 {
+        bitcoin::Amount::from_sat(amount).as_btc() * self.price
+    }
+
+    pub fn format(&self, amount: u64) -> String {
+        format!("{:.2} {}", self.converts(amount), self.currency)
+    }
+}
+
+/// PriceSource gives the price of one bitcoin in a fiat currency.
+/// Calls may be blocking and must be done in an async command.
+pub trait PriceSource: Send + Sync {
+    /// currencies supported by the source.
+    fn currencies(&self) -> Vec<String>;
+    /// current price of one bitcoin in the given currency.
+    fn price(&self, currency: &str) -> Result<f64, PriceError>;
+    /// price of one bitcoin in the given currency at the given unix timestamp.
+    fn historical_price(&self, currency: &str, timestamp: i64) -> Result<f64, PriceError>;
+}
+
+/// ManualPriceSource uses the rates written by the user in the configuration file.
+/// Historical prices are the same as the current ones.
+pub struct ManualPriceSource {
+    rates: HashMap<String, f64>,
+}
+
+impl ManualPriceSource {
+    pub fn new(rates: HashMap<String, f64>) -> Self {
+        Self { rates }
+    }
+}
+
+impl PriceSource for ManualPriceSource {
+    fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self.rates.keys().cloned().collect();
+        currencies.sort();
+        currencies
+    }
+
+    fn price(&self, currency: &str) -> Result<f64, PriceError> {
+        self.rates
+            .get(currency)
+            .cloned()
+            .ok_or_else(|| PriceError::UnknownCurrency(currency.to_string()))
+    }
+
+    fn historical_price(&self, currency: &str, _timestamp: i64) -> Result<f64, PriceError> {
+        self.price(currency)
+    }
+}
+
+/// FilePriceSource reads a price history from a file with one price per line:
+/// `<unix timestamp>,<currency>,<price of one bitcoin>`.
+/// Empty lines and lines starting with `#` are ignored.
+pub struct FilePriceSource {
+    /// prices by currency, sorted by timestamp.
+    prices: HashMap<String, Vec<(i64, f64)>>,
+}
+
+impl FilePriceSource {
+    pub fn from_file(path: &Path) -> Result<Self, PriceError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| PriceError::ReadingFile(format!("{:?}: {}", path, e)))?;
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<Self, PriceError> {
+        let mut prices: HashMap<String, Vec<(i64, f64)>> = HashMap::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() != 3 {
+                return Err(PriceError::ReadingFile(format!(
+                    "line {}: expected '<timestamp>,<currency>,<price>'",
+                    i + 1
+                )));
+            }
+            let timestamp = fields[0].parse::<i64>().map_err(|e| {
+                PriceError::ReadingFile(format!("line {}: invalid timestamp: {}", i + 1, e))
+            })?;
+            let price = fields[2].parse::<f64>().map_err(|e| {
+                PriceError::ReadingFile(format!("line {}: invalid price: {}", i + 1, e))
+            })?;
+            prices
+                .entry(fields[1].to_string())
+                .or_insert_with(Vec::new)
+                .push((timestamp, price));
+        }
+        for history in prices.values_mut() {
+            history.sort_by_key(|(timestamp, _)| *timestamp);
+        }
+        Ok(Self { prices })
+    }
+
+    fn history(&self, currency: &str) -> Result<&Vec<(i64, f64)>, PriceError> {
+        self.prices
+            .get(currency)
+            .filter(|history| !history.is_empty())
+            .ok_or_else(|| PriceError::UnknownCurrency(currency.to_string()))
+    }
+}
+
+impl PriceSource for FilePriceSource {
+    fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self.prices.keys().cloned().collect();
+        currencies.sort();
+        currencies
+    }
+
+    fn price(&self, currency: &str) -> Result<f64, PriceError> {
+        let history = self.history(currency)?;
+        Ok(history[history.len() - 1].1)
+    }
+
+    /// returns the last known price before the timestamp,
+    /// or the oldest price if the timestamp is before the history.
+    fn historical_price(&self, currency: &str, timestamp: i64) -> Result<f64, PriceError> {
+        let history = self.history(currency)?;
+        Ok(history
+            .iter()
+            .rev()
+            .find(|(t, _)| *t <= timestamp)
+            .unwrap_or(&history[0])
+            .1)
+    }
+}
+
+/// HttpPriceSource fetches prices from the CoinGecko public API.
+#[cfg(feature = "http-price")]
+pub struct HttpPriceSource {
+    url: String,
+}
+
+#[cfg(feature = "http-price")]
+impl HttpPriceSource {
+    pub const DEFAULT_URL: &'static str = "https://api.coingecko.com/api/v3";
+    pub const CURRENCIES: [&'static str; 6] = ["USD", "EUR", "GBP", "CHF", "JPY", "CAD"];
+
+    pub fn new(url: Option<String>) -> Self {
+        Self {
+            url: url.unwrap_or_else(|| Self::DEFAULT_URL.to_string()),
+        }
+    }
+
+    fn get(&self, path: &str) -> Result<serde_json::Value, PriceError> {
+        ureq::get(&format!("{}{}", self.url, path))
+            .call()
+            .map_err(|e| PriceError::Request(e.to_string()))?
+            .into_json()
+            .map_err(|e| PriceError::Request(e.to_string()))
+    }
+}
+
+#[cfg(feature = "http-price")]
+impl PriceSource for HttpPriceSource {
+    fn currencies(&self) -> Vec<String> {
+        Self::CURRENCIES.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn price(&self, currency: &str) -> Result<f64, PriceError> {
+        let currency = currency.to_lowercase();
+        self.get(&format!(
+            "/simple/price?ids=bitcoin&vs_currencies={}",
+            currency
+        ))?
+        .get("bitcoin")
+        .and_then(|prices| prices.get(&currency))
+        .and_then(|price| price.as_f64())
+        .ok_or(PriceError::UnknownCurrency(currency))
+    }
+
+    fn historical_price(&self, currency: &str, timestamp: i64) -> Result<f64, PriceError> {
+        let currency = currency.to_lowercase();
+        let date = chrono::NaiveDateTime::from_timestamp(timestamp, 0).format("%d-%m-%Y");
+        self.get(&format!(
+            "/coins/bitcoin/history?date={}&localization=false",
+            date
+        ))?
+        .pointer(&format!("/market_data/current_price/{}", currency))
+        .and_then(|price| price.as_f64())
+        .ok_or(PriceError::UnknownCurrency(currency))
+    }
+}
+
+/// FiatConfig is the fiat section of the GUI configuration file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FiatConfig {
+    /// Currency used to display fiat values, like "USD".
+    pub currency: String,
+    /// Source of the prices.
+    pub source: PriceSourceKind,
+    /// Rates of one bitcoin by currency for the manual source.
+    pub rates: Option<HashMap<String, f64>>,
+    /// Path to the prices history file for the file source.
+    pub prices_path: Option<PathBuf>,
+    /// API url for the http source.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSourceKind {
+    Manual,
+    File,
+    Http,
+}
+
+/// FiatSource is the price source with the currency selected by the user.
+#[derive(Clone)]
+pub struct FiatSource {
+    pub currency: String,
+    pub source: Arc<dyn PriceSource>,
+}
+
+impl FiatSource {
+    pub fn from_config(config: &FiatConfig) -> Result<Self, PriceError> {
+        let source: Arc<dyn PriceSource> = match config.source {
+            PriceSourceKind::Manual => Arc::new(ManualPriceSource::new(
+                config.rates.clone().unwrap_or_default(),
+            )),
+            PriceSourceKind::File => {
+                let path = config.prices_path.as_ref().ok_or_else(|| {
+                    PriceError::Unsupported("file source requires prices_path".to_string())
+                })?;
+                Arc::new(FilePriceSource::from_file(path)?)
+            }
+            #[cfg(feature = "http-price")]
+            PriceSourceKind::Http => Arc::new(HttpPriceSource::new(config.url.clone())),
+            #[cfg(not(feature = "http-price"))]
+            PriceSourceKind::Http => {
+                return Err(PriceError::Unsupported(
+                    "http source requires the http-price feature".to_string(),
+                ))
+            }
+        };
+        Ok(Self {
+            currency: config.currency.clone(),
+            source,
+        })
+    }
+
+    pub fn rate(&self) -> Result<FiatRate, PriceError> {
+        Ok(FiatRate {
+            currency: self.currency.clone(),
+            price: self.source.price(&self.currency)?,
+        })
+    }
+
+    pub fn historical_rate(&self, timestamp: i64) -> Result<FiatRate, PriceError> {
+        Ok(FiatRate {
+            currency: self.currency.clone(),
+            price: self.source.historical_price(&self.currency, timestamp)?,
+        })
+    }
+}
+
+impl std::fmt::Debug for FiatSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FiatSource")
+            .field("currency", &self.currency)
+            .finish()
+    }
+}
+
+/// fetches the current rate of the fiat source.
+pub async fn get_fiat_rate(fiat: FiatSource) -> Result<FiatRate, PriceError> {
+    fiat.rate()
+}
+
+#[derive(Debug, Clone)]
+pub enum PriceError {
+    UnknownCurrency(String),
+    ReadingFile(String),
+    Request(String),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for PriceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnknownCurrency(c) => write!(f, "No price for currency {}", c),
+            Self::ReadingFile(e) => write!(f, "Error while reading prices file: {}", e),
+            Self::Request(e) => write!(f, "Price request failed: {}", e),
+            Self::Unsupported(e) => write!(f, "Unsupported price source: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PriceError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_price_source() {
+        let source = FilePriceSource::parse(
+            "# timestamp,currency,price\n\
+             1600000000,USD,10000\n\
+             1500000000,USD,2000.5\n\
+             1600000000,EUR,9000\n",
+        )
+        .unwrap();
+        assert_eq!(source.currencies(), vec!["EUR", "USD"]);
+        assert_eq!(source.price("USD").unwrap(), 10000.0);
+        assert_eq!(source.historical_price("USD", 1550000000).unwrap(), 2000.5);
+        assert_eq!(source.historical_price("USD", 1400000000).unwrap(), 2000.5);
+        assert!(source.price("GBP").is_err());
+    }
+
+    #[test]
+    fn denomination_format() {
+        assert_eq!(Denomination::Bitcoin.format(123_456_789), "1.23456789");
+        assert_eq!(Denomination::Bitcoin.format(0), "0.00000000");
+        assert_eq!(
+            Denomination::MilliBitcoin.format(123_456_789),
+            "1,234.56789"
+        );
+        assert_eq!(Denomination::Bits.format(123_456_789), "1,234,567.89");
+        assert_eq!(Denomination::Satoshi.format(123_456_789), "123,456,789");
+        assert_eq!(Denomination::Satoshi.format(100), "100");
+    }
+
+    #[test]
+    fn denomination_parse() {
+        assert_eq!(Denomination::Bitcoin.parse("0.1"), Ok(10_000_000));
+        assert_eq!(Denomination::Bitcoin.parse(".00000001"), Ok(1));
+        assert_eq!(Denomination::Bitcoin.parse("1,000"), Ok(100_000_000_000));
+        assert_eq!(Denomination::MilliBitcoin.parse("1.5"), Ok(150_000));
+        assert_eq!(Denomination::Bits.parse("12.34"), Ok(1_234));
+        assert_eq!(Denomination::Satoshi.parse("123,456"), Ok(123_456));
+        assert_eq!(
+            Denomination::Bitcoin.parse("0.000000001"),
+            Err(ParseAmountError::TooPrecise)
+        );
+        assert_eq!(
+            Denomination::Satoshi.parse("1.5"),
+            Err(ParseAmountError::TooPrecise)
+        );
+        assert_eq!(
+            Denomination::Bitcoin.parse("0,5"),
+            Err(ParseAmountError::Invalid)
+        );
+        assert_eq!(
+            Denomination::Bitcoin.parse(""),
+            Err(ParseAmountError::Invalid)
+        );
+        assert_eq!(
+            Denomination::Bitcoin.parse("21000001"),
+            Err(ParseAmountError::TooBig)
+        );
+    }
+
+    #[test]
+    fn unit_display() {
+        let unit = |network, denomination| Unit {
+            network,
+            denomination,
+        };
+        assert_eq!(
+            unit(Network::Bitcoin, Denomination::Bits).to_string(),
+            "bits"
+        );
+        assert_eq!(
+            unit(Network::Testnet, Denomination::Bitcoin).to_string(),
+            "tBTC"
+        );
+        assert_eq!(
+            unit(Network::Testnet, Denomination::Bits).to_string(),
+            "tbits"
+        );
+        assert_eq!(
+            unit(Network::Regtest, Denomination::Satoshi).to_string(),
+            "rsats"
+        );
+    }
+
+    #[test]
+    fn fiat_rate_format() {
+        let rate = FiatRate {
+            currency: "USD".to_string(),
+            price: 50000.0,
+        };
+        assert_eq!(rate.format(150_000), "75.00 USD");
+    }
 }
@@ -0,0 +1,644 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bitcoin::Transaction;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Serialize, Serializer};
+
+use crate::{
+    conversion::{Converter, Denomination, FiatRate, FiatSource, Unit},
+    revaultd::model::{BroadcastedTransaction, Vault, VaultTransactions},
+};
+
+/// AmountFormat is the unit used to write the amounts of the exported records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountFormat {
+    Sats,
+    Bitcoin,
+}
+
+/// Amount is an exported amount in satoshis, written in its format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Amount {
+    Sats(u64),
+    Bitcoin(u64),
+}
+
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Sats(amount) => write!(f, "{}", amount),
+            Self::Bitcoin(amount) => {
+                write!(f, "{}.{:08}", amount / 100_000_000, amount % 100_000_000)
+            }
+        }
+    }
+}
+
+/// The satoshis are a JSON number, the bitcoins a string which keeps the
+/// eight decimals exact.
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Sats(amount) => serializer.serialize_u64(*amount),
+            Self::Bitcoin(_) => serializer.collect_str(self),
+        }
+    }
+}
+
+/// VaultRecord is the exported representation of a vault and its onchain transactions.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultRecord {
+    pub outpoint: String,
+    pub address: String,
+    pub amount: Amount,
+    pub unit: String,
+    pub derivation_index: u32,
+    pub status: String,
+    pub received_at: String,
+    pub updated_at: String,
+    /// fiat value of the amount at the price of the day the vault was received.
+    pub fiat_value: Option<f64>,
+    pub fiat_currency: Option<String>,
+    pub transactions: Vec<TransactionRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionRecord {
+    pub kind: &'static str,
+    pub txid: String,
+    pub blockheight: Option<u64>,
+    pub received_at: String,
+    /// fee is None if one of the spent outputs is unknown, like for the deposit transaction.
+    pub fee: Option<Amount>,
+}
+
+pub struct Exporter {
+    converter: Converter,
+    format: AmountFormat,
+    fiat: Option<FiatSource>,
+}
+
+impl Exporter {
+    pub fn new(converter: Converter, format: AmountFormat, fiat: Option<FiatSource>) -> Self {
+        Self {
+            converter,
+            format,
+            fiat,
+        }
+    }
+
+    /// daily_rates fetches the price of each day a vault was received, once per
+    /// day: the vaults of the same day share the price of the end of the day.
+    /// An unknown price is logged and left empty in the records.
+    fn daily_rates(&self, vaults: &[Vault]) -> HashMap<NaiveDate, FiatRate> {
+        let mut rates = HashMap::new();
+        let fiat = match &self.fiat {
+            Some(fiat) => fiat,
+            None => return rates,
+        };
+        let mut days: Vec<NaiveDate> = vaults.iter().map(|v| day(v.received_at)).collect();
+        days.sort();
+        days.dedup();
+        for day in days {
+            let timestamp = day.and_hms(23, 59, 59).timestamp();
+            match fiat.historical_rate(timestamp) {
+                Ok(rate) => {
+                    rates.insert(day, rate);
+                }
+                Err(e) => tracing::warn!("No fiat price for {}: {}", day, e),
+            }
+        }
+        rates
+    }
+
+    fn amount(&self, sats: u64) -> Amount {
+        match self.format {
+            AmountFormat::Sats => Amount::Sats(sats),
+            AmountFormat::Bitcoin => Amount::Bitcoin(sats),
+        }
+    }
+
+    fn unit(&self) -> String {
+        match self.format {
+            AmountFormat::Sats => Unit {
+                network: self.converter.unit.network,
+                denomination: Denomination::Satoshi,
+            }
+            .to_string(),
+            AmountFormat::Bitcoin => self.converter.unit.to_string(),
+        }
+    }
+
+    /// records joins the vaults with their onchain transactions.
+    pub fn records(&self, vaults: &[Vault], txs: &[VaultTransactions]) -> Vec<VaultRecord> {
+        let rates = self.daily_rates(vaults);
+        let txs: HashMap<&str, &VaultTransactions> = txs
+            .iter()
+            .map(|txs| (txs.vault_outpoint.as_str(), txs))
+            .collect();
+
+        vaults
+            .iter()
+            .map(|vault| {
+                let outpoint = vault.outpoint();
+                let transactions = txs
+                    .get(outpoint.as_str())
+                    .map(|txs| self.transaction_records(txs))
+                    .unwrap_or_default();
+                VaultRecord {
+                    address: vault.address.clone(),
+                    amount: self.amount(vault.amount),
+                    unit: self.unit(),
+                    derivation_index: vault.derivation_index,
+                    status: vault.status.to_string(),
+                    received_at: format_timestamp(vault.received_at),
+                    updated_at: format_timestamp(vault.updated_at),
+                    fiat_value: rates
+                        .get(&day(vault.received_at))
+                        .map(|rate| (rate.converts(vault.amount) * 100.0).round() / 100.0),
+                    fiat_currency: self.fiat.as_ref().map(|fiat| fiat.currency.clone()),
+                    outpoint,
+                    transactions,
+                }
+            })
+            .collect()
+    }
+
+    fn transaction_records(&self, txs: &VaultTransactions) -> Vec<TransactionRecord> {
+        // Only the transactions of the vault are known, the fee of a transaction
+        // spending other coins cannot be computed.
+        let known: Vec<&Transaction> = vec![Some(&txs.deposit), txs.unvault.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|tx| &tx.tx)
+            .collect();
+
+        vec![
+            ("deposit", Some(&txs.deposit)),
+            ("unvault", txs.unvault.as_ref()),
+            ("spend", txs.spend.as_ref()),
+            ("cancel", txs.cancel.as_ref()),
+            ("emergency", txs.emergency.as_ref()),
+            ("unvault_emergency", txs.unvault_emergency.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(kind, tx)| tx.map(|tx| (kind, tx)))
+        .map(
+            |(kind, tx): (&'static str, &BroadcastedTransaction)| TransactionRecord {
+                kind,
+                txid: tx.tx.txid().to_string(),
+                blockheight: tx.blockheight,
+                received_at: format_timestamp(tx.received_at),
+                fee: fee(&tx.tx, &known).map(|fee| self.amount(fee)),
+            },
+        )
+        .collect()
+    }
+}
+
+/// fee returns the fee paid by the transaction if all its spent outputs are known.
+pub fn fee(tx: &Transaction, known: &[&Transaction]) -> Option<u64> {
+    let mut input_amount: u64 = 0;
+    for input in &tx.input {
+        let previous = known
+            .iter()
+            .find(|known_tx| known_tx.txid() == input.previous_output.txid)?;
+        input_amount += previous
+            .output
+            .get(input.previous_output.vout as usize)?
+            .value;
+    }
+    let output_amount: u64 = tx.output.iter().map(|output| output.value).sum();
+    input_amount.checked_sub(output_amount)
+}
+
+fn day(timestamp: i64) -> NaiveDate {
+    NaiveDateTime::from_timestamp(timestamp, 0).date()
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// escape quotes a CSV field if it contains a separator, a quote or a line break.
+fn escape(field: &str) -> String {
+    if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_line(fields: &[String]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|f| escape(f))
+        .collect::<Vec<String>>()
+        .join(",");
+    line.push('\n');
+    line
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// vaults_csv returns one line per vault.
+pub fn vaults_csv(records: &[VaultRecord]) -> String {
+    let mut csv = csv_line(
+        &[
+            "outpoint",
+            "address",
+            "amount",
+            "unit",
+            "derivation_index",
+            "status",
+            "received_at",
+            "updated_at",
+            "fiat_value",
+            "fiat_currency",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<String>>(),
+    );
+    for record in records {
+        csv.push_str(&csv_line(&[
+            record.outpoint.clone(),
+            record.address.clone(),
+            record.amount.to_string(),
+            record.unit.clone(),
+            record.derivation_index.to_string(),
+            record.status.clone(),
+            record.received_at.clone(),
+            record.updated_at.clone(),
+            optional(&record.fiat_value),
+            optional(&record.fiat_currency),
+        ]));
+    }
+    csv
+}
+
+/// transactions_csv returns one line per onchain transaction of each vault.
+pub fn transactions_csv(records: &[VaultRecord]) -> String {
+    let mut csv = csv_line(
+        &[
+            "vault_outpoint",
+            "kind",
+            "txid",
+            "blockheight",
+            "received_at",
+            "fee",
+            "unit",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<String>>(),
+    );
+    for record in records {
+        for tx in &record.transactions {
+            csv.push_str(&csv_line(&[
+                record.outpoint.clone(),
+                tx.kind.to_string(),
+                tx.txid.clone(),
+                optional(&tx.blockheight),
+                tx.received_at.clone(),
+                optional(&tx.fee),
+                record.unit.clone(),
+            ]));
+        }
+    }
+    csv
+}
+
+/// export writes the records in the given directory as CSV and JSON files
+/// and returns the paths of the created files.
+pub fn export(dir: &Path, records: &[VaultRecord]) -> Result<Vec<PathBuf>, Error> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::CannotCreateDir(e.to_string()))?;
+
+    let suffix = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let json =
+        serde_json::to_string_pretty(records).map_err(|e| Error::CannotWriteFile(e.to_string()))?;
+
+    let files = vec![
+        (format!("vaults_{}.csv", suffix), vaults_csv(records)),
+        (
+            format!("transactions_{}.csv", suffix),
+            transactions_csv(records),
+        ),
+        (format!("vaults_{}.json", suffix), json),
+    ];
+
+    let mut paths = Vec::new();
+    for (name, content) in files {
+        let mut path = dir.to_path_buf();
+        path.push(name);
+        let mut file =
+            std::fs::File::create(&path).map_err(|e| Error::CannotCreateFile(e.to_string()))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| Error::CannotWriteFile(e.to_string()))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// DepositSlip is the printable instructions to deposit funds to a vault.
+#[derive(Debug, Clone)]
+pub struct DepositSlip {
+    pub address: String,
+    pub derivation_index: u32,
+    /// BIP21 payment request encoded in the QR code.
+    pub uri: String,
+    pub network: bitcoin::Network,
+    /// BIP380 checksum of the deposit descriptor.
+    pub descriptor_checksum: String,
+    /// amount requested, written with its unit.
+    pub amount: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Size in pixels of a module of the deposit slip QR code.
+const QR_MODULE_SIZE: usize = 6;
+
+/// Minimum width in pixels of the deposit slip, a long payment request
+/// or a long line widens it.
+const SLIP_MIN_WIDTH: usize = 600;
+
+/// Horizontal margin in pixels of the deposit slip.
+const SLIP_MARGIN: usize = 40;
+
+/// Approximate width in pixels of a character of the monospace text.
+const SLIP_CHAR_WIDTH: usize = 9;
+
+/// deposit_slip_svg renders the deposit slip as a printable SVG document.
+pub fn deposit_slip_svg(slip: &DepositSlip) -> Result<String, Error> {
+    let code = qrcode::QrCode::new(slip.uri.as_bytes())
+        .map_err(|e| Error::CannotCreateQRCode(e.to_string()))?;
+    let width = code.width();
+    let qr_size = (width + 8) * QR_MODULE_SIZE;
+    let qr_y = 110;
+
+    let mut lines = vec![
+        ("Network", slip.network.to_string()),
+        ("Address", slip.address.clone()),
+        ("Derivation index", slip.derivation_index.to_string()),
+        (
+            "Deposit descriptor checksum",
+            slip.descriptor_checksum.clone(),
+        ),
+    ];
+    if let Some(amount) = &slip.amount {
+        lines.push(("Amount", amount.clone()));
+    }
+    if let Some(label) = &slip.label {
+        lines.push(("Label", label.clone()));
+    }
+    let page_height = qr_y + qr_size + 40 + lines.len() * 50;
+    let longest_line = lines
+        .iter()
+        .map(|(_, value)| value.chars().count())
+        .max()
+        .unwrap_or(0);
+    let page_width = SLIP_MIN_WIDTH
+        .max(qr_size + 2 * SLIP_MARGIN)
+        .max(longest_line * SLIP_CHAR_WIDTH + 2 * SLIP_MARGIN);
+    let qr_x = (page_width - qr_size) / 2;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <text x=\"{x}\" y=\"60\" font-family=\"sans-serif\" font-size=\"28\" \
+         text-anchor=\"middle\">Revault deposit slip</text>\n",
+        w = page_width,
+        h = page_height,
+        x = page_width / 2,
+    );
+
+    svg.push_str(&format!(
+        "<g transform=\"translate({} {})\">\n",
+        qr_x + 4 * QR_MODULE_SIZE,
+        qr_y + 4 * QR_MODULE_SIZE
+    ));
+    for (i, color) in code.to_colors().iter().enumerate() {
+        if *color == qrcode::Color::Dark {
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\"/>\n",
+                (i % width) * QR_MODULE_SIZE,
+                (i / width) * QR_MODULE_SIZE,
+                s = QR_MODULE_SIZE
+            ));
+        }
+    }
+    svg.push_str("</g>\n");
+
+    let mut y = qr_y + qr_size + 40;
+    for (title, value) in lines {
+        svg.push_str(&format!(
+            "<text x=\"{x}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\" \
+             fill=\"#555555\">{}</text>\n\
+             <text x=\"{x}\" y=\"{}\" font-family=\"monospace\" font-size=\"14\">{}</text>\n",
+            y,
+            xml_escape(title),
+            y + 20,
+            xml_escape(&value),
+            x = SLIP_MARGIN,
+        ));
+        y += 50;
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// xml_escape escapes the characters reserved in the SVG text content.
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// export_deposit_slip writes the deposit slip in the given directory
+/// as a SVG file and returns its path.
+pub fn export_deposit_slip(dir: &Path, slip: &DepositSlip) -> Result<PathBuf, Error> {
+    std::fs::create_dir_all(dir).map_err(|e| Error::CannotCreateDir(e.to_string()))?;
+    let svg = deposit_slip_svg(slip)?;
+
+    let mut path = dir.to_path_buf();
+    path.push(format!(
+        "deposit_slip_{}_{}.svg",
+        slip.derivation_index,
+        chrono::Utc::now().format("%Y%m%d_%H%M%S")
+    ));
+    let mut file =
+        std::fs::File::create(&path).map_err(|e| Error::CannotCreateFile(e.to_string()))?;
+    file.write_all(svg.as_bytes())
+        .map_err(|e| Error::CannotWriteFile(e.to_string()))?;
+    Ok(path)
+}
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    CannotCreateDir(String),
+    CannotCreateFile(String),
+    CannotWriteFile(String),
+    CannotCreateQRCode(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::CannotCreateDir(e) => write!(f, "Failed to create export directory: {}", e),
+            Self::CannotCreateFile(e) => write!(f, "Failed to create export file: {}", e),
+            Self::CannotWriteFile(e) => write!(f, "Failed to write export file: {}", e),
+            Self::CannotCreateQRCode(e) => write!(f, "Failed to create QR code: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitcoin_amount() {
+        assert_eq!(Amount::Bitcoin(150_000_000).to_string(), "1.50000000");
+        assert_eq!(Amount::Bitcoin(1).to_string(), "0.00000001");
+        assert_eq!(
+            Amount::Bitcoin(2_099_999_997_690_000).to_string(),
+            "20999999.97690000"
+        );
+        assert_eq!(
+            serde_json::to_string(&Amount::Bitcoin(10)).unwrap(),
+            "\"0.00000010\""
+        );
+        assert_eq!(serde_json::to_string(&Amount::Sats(10)).unwrap(), "10");
+    }
+
+    #[test]
+    fn escape_csv_fields() {
+        assert_eq!(escape("tb1qxyz"), "tb1qxyz");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn deposit_slip() {
+        let slip = DepositSlip {
+            address: "tb1qxyz".to_string(),
+            derivation_index: 7,
+            uri: "bitcoin:tb1qxyz?label=A%26B".to_string(),
+            network: bitcoin::Network::Testnet,
+            descriptor_checksum: "0a1b2c3d".to_string(),
+            amount: None,
+            label: Some("A&B".to_string()),
+        };
+        let svg = deposit_slip_svg(&slip).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">tb1qxyz</text>"));
+        assert!(svg.contains(">0a1b2c3d</text>"));
+        assert!(svg.contains(">A&amp;B</text>"));
+        assert!(!svg.contains(">Amount</text>"));
+    }
+
+    #[test]
+    fn deposit_slip_long_label() {
+        let label = "x".repeat(1000);
+        let slip = DepositSlip {
+            address: "tb1qxyz".to_string(),
+            derivation_index: 7,
+            uri: format!("bitcoin:tb1qxyz?label={}", label),
+            network: bitcoin::Network::Testnet,
+            descriptor_checksum: "0a1b2c3d".to_string(),
+            amount: None,
+            label: Some(label),
+        };
+        // The QR code is larger than the minimum width of the slip.
+        let svg = deposit_slip_svg(&slip).unwrap();
+        assert!(svg.contains(&format!(
+            "width=\"{}\"",
+            1000 * SLIP_CHAR_WIDTH + 2 * SLIP_MARGIN
+        )));
+    }
+
+    /// CountingSource counts the historical prices requested.
+    struct CountingSource(std::sync::atomic::AtomicUsize);
+
+    impl crate::conversion::PriceSource for CountingSource {
+        fn currencies(&self) -> Vec<String> {
+            vec!["USD".to_string()]
+        }
+        fn price(&self, _currency: &str) -> Result<f64, crate::conversion::PriceError> {
+            Ok(20000.0)
+        }
+        fn historical_price(
+            &self,
+            _currency: &str,
+            _timestamp: i64,
+        ) -> Result<f64, crate::conversion::PriceError> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(20000.0)
+        }
+    }
+
+    #[test]
+    fn fiat_value_once_per_day() {
+        let source = std::sync::Arc::new(CountingSource(Default::default()));
+        let exporter = Exporter::new(
+            Converter::new(bitcoin::Network::Testnet, Default::default()),
+            AmountFormat::Sats,
+            Some(FiatSource {
+                currency: "USD".to_string(),
+                source: source.clone(),
+            }),
+        );
+        let vault = |received_at: i64| Vault {
+            address: "tb1qxyz".to_string(),
+            amount: 50_000_000,
+            derivation_index: 0,
+            received_at,
+            status: crate::revaultd::model::VaultStatus::Active,
+            txid: "abcd".to_string(),
+            updated_at: received_at,
+            vout: 0,
+        };
+        // 2021-01-01 00:00, 2021-01-01 12:00 and 2021-01-02 00:00.
+        let vaults = vec![vault(1609459200), vault(1609502400), vault(1609545600)];
+        let records = exporter.records(&vaults, &[]);
+        assert_eq!(source.0.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(records.iter().all(|r| r.fiat_value == Some(10000.0)));
+    }
+
+    #[test]
+    fn vaults_csv_lines() {
+        let records = vec![VaultRecord {
+            outpoint: "abcd:0".to_string(),
+            address: "tb1qxyz".to_string(),
+            amount: Amount::Sats(42),
+            unit: "sats".to_string(),
+            derivation_index: 3,
+            status: "Active".to_string(),
+            received_at: "2021-01-01 00:00:00".to_string(),
+            updated_at: "2021-01-02 00:00:00".to_string(),
+            fiat_value: Some(21.5),
+            fiat_currency: Some("USD".to_string()),
+            transactions: Vec::new(),
+        }];
+        assert_eq!(
+            vaults_csv(&records),
+            "outpoint,address,amount,unit,derivation_index,status,received_at,updated_at,\
+             fiat_value,fiat_currency\n\
+             abcd:0,tb1qxyz,42,sats,3,Active,2021-01-01 00:00:00,2021-01-02 00:00:00,21.5,USD\n"
+        );
+        assert_eq!(
+            transactions_csv(&records),
+            "vault_outpoint,kind,txid,blockheight,received_at,fee,unit\n"
+        );
+    }
+}
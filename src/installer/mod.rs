@@ -91,7 +91,8 @@ impl Installer {
     pub fn new(destination_path: PathBuf) -> (Installer, Command<Message>) {
         let mut config = revaultd_config::Config::new();
         config.data_dir = Some(destination_path);
-        config.daemon = Some(true);
+        // revaultd runs as a child of the GUI, supervised by it.
+        config.daemon = Some(false);
         (
             Installer {
                 config,
@@ -131,7 +132,7 @@ impl Installer {
                 // reset config
                 let mut config = revaultd_config::Config::new();
                 config.data_dir = self.config.data_dir.clone();
-                config.daemon = Some(true);
+                config.daemon = Some(false);
                 self.config = config;
 
                 self.update_steps(role);
@@ -156,7 +157,8 @@ impl Installer {
     }
 }
 
-fn append_network_suffix(name: &str, network: &bitcoin::Network) -> String {
+/// append_network_suffix names the configuration files of the networks other than bitcoin.
+pub fn append_network_suffix(name: &str, network: &bitcoin::Network) -> String {
     if *network == bitcoin::Network::Bitcoin {
         name.to_string()
     } else {
@@ -1,8 +1,6 @@
 use std::cmp::Ordering;
-use std::net::SocketAddr;
 use std::str::FromStr;
 
-use bitcoin::hashes::hex::FromHex;
 use bitcoin::util::bip32::ExtendedPubKey;
 use iced::{button::State as Button, scrollable, Element};
 use miniscript::DescriptorPublicKey;
@@ -462,15 +460,8 @@ impl Step for DefineCosigners {
 
     fn apply(&mut self, _ctx: &mut Context, config: &mut config::Config) -> bool {
         for cosigner in &mut self.cosigners {
-            if let Ok(bytes) = Vec::from_hex(&cosigner.noise_key.value) {
-                if bytes.len() != 32 {
-                    cosigner.noise_key.valid = false;
-                }
-            } else {
-                cosigner.noise_key.valid = false;
-            }
-
-            cosigner.host.valid = SocketAddr::from_str(&cosigner.host.value).is_ok();
+            cosigner.noise_key.valid = config::is_valid_noise_key(&cosigner.noise_key.value);
+            cosigner.host.valid = config::is_valid_host(&cosigner.host.value);
         }
 
         if self
@@ -3,11 +3,9 @@ pub mod manager;
 pub mod stakeholder;
 
 use std::cmp::Ordering;
-use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use bitcoin::hashes::hex::FromHex;
 use bitcoin::util::bip32::ExtendedPubKey;
 use iced::{button::State as Button, scrollable, Element};
 use miniscript::DescriptorPublicKey;
@@ -313,15 +311,8 @@ impl Step for DefineCoordinator {
     }
 
     fn apply(&mut self, _ctx: &mut Context, config: &mut config::Config) -> bool {
-        if let Ok(bytes) = Vec::from_hex(&self.noise_key.value) {
-            if bytes.len() != 32 {
-                self.noise_key.valid = false;
-            }
-        } else {
-            self.noise_key.valid = false;
-        }
-
-        self.host.valid = SocketAddr::from_str(&self.host.value).is_ok();
+        self.noise_key.valid = config::is_valid_noise_key(&self.noise_key.value);
+        self.host.valid = config::is_valid_host(&self.host.value);
 
         if !self.host.valid || !self.noise_key.valid {
             return false;
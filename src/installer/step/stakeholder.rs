@@ -1,7 +1,6 @@
 use std::cmp::Ordering;
 use std::str::FromStr;
 
-use bitcoin::hashes::hex::FromHex;
 use bitcoin::util::bip32::ExtendedPubKey;
 use iced::Element;
 use miniscript::DescriptorPublicKey;
@@ -16,6 +15,7 @@ use crate::{
         },
         view,
     },
+    revault::{parse_address, AddressError},
     revaultd::config,
     ui::component::form,
 };
@@ -395,22 +395,18 @@ impl Step for DefineEmergencyAddress {
     }
 
     fn apply(&mut self, _ctx: &mut Context, config: &mut config::Config) -> bool {
-        match bitcoin::Address::from_str(&self.address.value) {
+        match parse_address(&self.address.value, config.bitcoind_config.network) {
             Ok(address) => {
-                if address.network != config.bitcoind_config.network {
-                    self.warning = Some(format!(
-                        "address is not not usable with the specified bitcoind network: {}",
-                        config.bitcoind_config.network
-                    ));
-                    return false;
-                }
                 if let Some(stakeholder_config) = &mut config.stakeholder_config {
-                    stakeholder_config.emergency_address = self.address.value.clone();
+                    stakeholder_config.emergency_address = address.to_string();
                 }
                 self.address.valid = true;
                 true
             }
-            Err(_) => {
+            Err(e) => {
+                if let AddressError::WrongNetwork(_) = e {
+                    self.warning = Some(e.to_string());
+                }
                 self.address.valid = false;
                 false
             }
@@ -513,13 +509,7 @@ impl Step for DefineWatchtowers {
 
     fn apply(&mut self, _ctx: &mut Context, config: &mut config::Config) -> bool {
         for watchtower in &mut self.watchtowers {
-            if let Ok(bytes) = Vec::from_hex(&watchtower.noise_key.value) {
-                if bytes.len() != 32 {
-                    watchtower.noise_key.valid = false;
-                }
-            } else {
-                watchtower.noise_key.valid = false;
-            }
+            watchtower.noise_key.valid = config::is_valid_noise_key(&watchtower.noise_key.value);
         }
 
         if let Some(stakeholder_config) = &mut config.stakeholder_config {
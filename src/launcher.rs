@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use iced::{button::State as Button, Align, Column, Container, Element, Length};
+
+use crate::{
+    app::config::Profile,
+    ui::component::{button, card, image::revault_colored_logo, text},
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Profile),
+    /// Install a new profile in the datadir.
+    Install(PathBuf),
+}
+
+/// Launcher lets the user pick one of the profiles found in the datadir.
+pub struct Launcher {
+    datadir: PathBuf,
+    profiles: Vec<(Profile, Button)>,
+    install_button: Button,
+    /// error of the last profile that failed to load.
+    warning: Option<String>,
+}
+
+impl Launcher {
+    pub fn new(datadir: PathBuf, profiles: Vec<Profile>) -> Self {
+        Self {
+            datadir,
+            profiles: profiles
+                .into_iter()
+                .map(|profile| (profile, Button::default()))
+                .collect(),
+            install_button: Button::default(),
+            warning: None,
+        }
+    }
+
+    pub fn with_warning(mut self, warning: String) -> Self {
+        self.warning = Some(warning);
+        self
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let mut profiles = Column::new().spacing(10).align_items(Align::Center);
+        if let Some(warning) = &self.warning {
+            profiles = profiles.push(
+                card::alert_warning(Container::new(text::small(warning))).width(Length::Units(400)),
+            );
+        }
+        for (profile, state) in &mut self.profiles {
+            profiles = profiles.push(
+                button::white_card_button(
+                    state,
+                    Container::new(
+                        Column::new()
+                            .push(text::bold(text::simple(&profile.name)))
+                            .push(text::small(&profile.path.to_string_lossy())),
+                    ),
+                )
+                .on_press(Message::Select(profile.clone()))
+                .width(Length::Units(400)),
+            );
+        }
+        Container::new(
+            Column::new()
+                .push(Container::new(
+                    revault_colored_logo()
+                        .width(Length::Units(300))
+                        .height(Length::Units(150)),
+                ))
+                .push(text::simple("Select a wallet"))
+                .push(profiles)
+                .push(
+                    button::transparent(
+                        &mut self.install_button,
+                        button::button_content(None, "Install a new wallet"),
+                    )
+                    .on_press(Message::Install(self.datadir.clone())),
+                )
+                .width(Length::Fill)
+                .padding(50)
+                .spacing(30)
+                .align_items(Align::Center),
+        )
+        .center_x()
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into()
+    }
+}
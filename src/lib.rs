@@ -0,0 +1,10 @@
+pub mod app;
+pub mod cli;
+pub mod conversion;
+pub mod export;
+pub mod installer;
+pub mod launcher;
+pub mod revault;
+pub mod revaultd;
+pub mod revocation;
+pub mod ui;
@@ -3,75 +3,62 @@ use std::path::PathBuf;
 
 use iced::{executor, Application, Clipboard, Command, Element, Settings, Subscription};
 use tracing_subscriber::filter::EnvFilter;
-extern crate serde;
-extern crate serde_json;
-
-mod app;
-mod conversion;
-mod installer;
-mod revault;
-mod revaultd;
-mod ui;
-
-use app::{
-    config::{ConfigError, DEFAULT_FILE_NAME},
-    App,
-};
-use installer::Installer;
-use revaultd::config::default_datadir;
-
-enum Args {
-    ConfigPath(PathBuf),
-    DatadirPath(PathBuf),
-    None,
-}
-
-fn parse_args(args: Vec<String>) -> Result<Args, Box<dyn Error>> {
-    if args.len() == 1 {
-        return Ok(Args::None);
-    }
 
-    if args.len() == 3 {
-        if args[1] == "--conf" {
-            return Ok(Args::ConfigPath(PathBuf::from(args[2].to_owned())));
-        } else if args[1] == "--datadir" {
-            return Ok(Args::DatadirPath(PathBuf::from(args[2].to_owned())));
-        }
-    }
-
-    println!("Usage:\n'--conf <configuration file path>'\n'--datadir <datadir path>'");
-    Err(format!("Unknown arguments '{:?}'.", args).into())
-}
+use revault_gui::{
+    app::{
+        self,
+        config::{ConfigError, Overrides, DEFAULT_FILE_NAME},
+        App,
+    },
+    cli,
+    installer::{self, Installer},
+    launcher::{self, Launcher},
+};
 
-fn log_level_from_config(config: &app::Config) -> Result<EnvFilter, Box<dyn Error>> {
-    if let Some(level) = &config.log_level {
-        match level.as_ref() {
-            "info" => EnvFilter::try_new("revault_gui=info").map_err(|e| e.into()),
-            "debug" => EnvFilter::try_new("revault_gui=debug").map_err(|e| e.into()),
-            "trace" => EnvFilter::try_new("revault_gui=trace").map_err(|e| e.into()),
-            _ => Err(format!("Unknown loglevel '{:?}'.", level).into()),
-        }
-    } else if let Some(true) = config.debug {
-        EnvFilter::try_new("revault_gui=debug").map_err(|e| e.into())
-    } else {
-        EnvFilter::try_new("revault_gui=info").map_err(|e| e.into())
+/// log_level returns the filter of the level given on the command line,
+/// or else of the level of the configuration.
+fn log_level(
+    level: Option<&str>,
+    config: Option<&app::Config>,
+) -> Result<EnvFilter, Box<dyn Error>> {
+    let level = match (level, config) {
+        (Some(level), _) => level,
+        (None, Some(config)) => match &config.log_level {
+            Some(level) => level.as_str(),
+            None if config.debug == Some(true) => "debug",
+            None => "info",
+        },
+        (None, None) => "info",
+    };
+    if !cli::LOG_LEVELS.contains(&level) {
+        return Err(format!("Unknown loglevel '{:?}'.", level).into());
     }
+    EnvFilter::try_new(format!("revault_gui={}", level)).map_err(|e| e.into())
 }
 
+/// The launcher and the installer keep the command line overrides
+/// for the application they start.
 pub enum GUI {
-    Installer(Installer),
+    Launcher(Launcher, Overrides),
+    Installer(Installer, Overrides),
     App(App),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    Launch(launcher::Message),
     Install(installer::Message),
-    Run(app::Message),
+    /// message of the application with its generation, the messages of the
+    /// profile used before a switch are dropped.
+    Run(u64, app::Message),
 }
 
 pub enum Config {
-    Run(app::Config),
-    Install(PathBuf),
+    /// Run the application with the configuration read from the given path.
+    Run(app::Config, PathBuf),
+    Install(PathBuf, Overrides),
+    /// Let the user pick one of the profiles of the datadir.
+    Launch(PathBuf, Vec<app::config::Profile>, Overrides),
 }
 
 impl Application for GUI {
@@ -81,21 +68,33 @@ impl Application for GUI {
 
     fn title(&self) -> String {
         match self {
-            Self::Installer(_) => String::from("Revault Installer"),
+            Self::Launcher(..) => String::from("Revault GUI"),
+            Self::Installer(..) => String::from("Revault Installer"),
             Self::App(_) => String::from("Revault GUI"),
         }
     }
 
     fn new(config: Config) -> (GUI, Command<Self::Message>) {
         match config {
-            Config::Install(path) => {
+            Config::Install(path, overrides) => {
                 let (install, command) = Installer::new(path);
-                (GUI::Installer(install), command.map(Message::Install))
+                (
+                    GUI::Installer(install, overrides),
+                    command.map(Message::Install),
+                )
             }
-            Config::Run(cfg) => {
-                let (application, command) = App::new(cfg);
-                (GUI::App(application), command.map(Message::Run))
+            Config::Run(cfg, path) => {
+                let (application, command) = App::new(cfg, path);
+                let generation = application.generation();
+                (
+                    GUI::App(application),
+                    command.map(move |msg| Message::Run(generation, msg)),
+                )
             }
+            Config::Launch(datadir, profiles, overrides) => (
+                GUI::Launcher(Launcher::new(datadir, profiles), overrides),
+                Command::none(),
+            ),
         }
     }
 
@@ -104,74 +103,162 @@ impl Application for GUI {
         message: Self::Message,
         clipboard: &mut Clipboard,
     ) -> Command<Self::Message> {
-        if let Message::Install(installer::Message::Exit(path)) = message {
-            let cfg = app::Config::from_file(&path).unwrap();
-            let (application, command) = App::new(cfg);
-            *self = GUI::App(application);
-            return command.map(Message::Run);
+        let overrides = match self {
+            Self::Launcher(_, overrides) | Self::Installer(_, overrides) => overrides.clone(),
+            Self::App(_) => Overrides::default(),
+        };
+        match message {
+            Message::Install(installer::Message::Exit(path))
+            | Message::Launch(launcher::Message::Select(app::config::Profile { path, .. })) => {
+                let mut cfg = match app::Config::from_file(&path) {
+                    Ok(cfg) => cfg,
+                    Err(e) => {
+                        // The file was removed or broken after the profiles were listed.
+                        let datadir = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+                        let profiles = app::config::profiles(&datadir);
+                        let launcher = Launcher::new(datadir, profiles).with_warning(format!(
+                            "Failed to load {}: {}",
+                            path.to_string_lossy(),
+                            e
+                        ));
+                        *self = GUI::Launcher(launcher, overrides);
+                        return Command::none();
+                    }
+                };
+                cfg.overrides = overrides;
+                let (application, command) = App::new(cfg, path);
+                let generation = application.generation();
+                *self = GUI::App(application);
+                return command.map(move |msg| Message::Run(generation, msg));
+            }
+            Message::Launch(launcher::Message::Install(datadir)) => {
+                let (install, command) = Installer::new(datadir);
+                *self = GUI::Installer(install, overrides);
+                return command.map(Message::Install);
+            }
+            _ => {}
         }
         match (self, message) {
-            (Self::Installer(i), Message::Install(msg)) => {
+            (Self::Installer(i, _), Message::Install(msg)) => {
                 i.update(msg, clipboard).map(Message::Install)
             }
-            (Self::App(i), Message::Run(msg)) => i.update(msg, clipboard).map(Message::Run),
+            (Self::App(i), Message::Run(generation, msg)) => {
+                if generation != i.generation() {
+                    return Command::none();
+                }
+                let command = i.update(msg, clipboard);
+                let generation = i.generation();
+                command.map(move |msg| Message::Run(generation, msg))
+            }
             _ => Command::none(),
         }
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
         match self {
-            Self::Installer(v) => v.subscription().map(Message::Install),
-            Self::App(v) => v.subscription().map(Message::Run),
+            Self::Launcher(..) => Subscription::none(),
+            Self::Installer(v, _) => v.subscription().map(Message::Install),
+            Self::App(v) => v
+                .subscription()
+                .with(v.generation())
+                .map(|(generation, msg)| Message::Run(generation, msg)),
         }
     }
 
     fn view(&mut self) -> Element<Self::Message> {
         match self {
-            Self::Installer(v) => v.view().map(Message::Install),
-            Self::App(v) => v.view().map(Message::Run),
+            Self::Launcher(v, _) => v.view().map(Message::Launch),
+            Self::Installer(v, _) => v.view().map(Message::Install),
+            Self::App(v) => {
+                let generation = v.generation();
+                v.view().map(move |msg| Message::Run(generation, msg))
+            }
         }
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = std::env::args().collect();
-
-    let config = match parse_args(args)? {
-        Args::ConfigPath(path) => Config::Run(app::Config::from_file(&path)?),
-        Args::None => {
-            let path = app::Config::default_path()
-                .map_err(|e| format!("Failed to find revault GUI config: {}", e))?;
-
-            match app::Config::from_file(&path) {
-                Ok(cfg) => Config::Run(cfg),
-                Err(ConfigError::NotFound) => {
-                    let default_datadir_path =
-                        default_datadir().expect("Unexpected filesystem error");
-                    Config::Install(default_datadir_path)
-                }
-                Err(e) => {
-                    return Err(format!("Failed to read configuration file: {}", e).into());
-                }
+/// datadir_config runs the default profile of the datadir, or lets the user pick one
+/// if there are many. The installer is launched if there is none.
+fn datadir_config(path: PathBuf, overrides: Overrides) -> Result<Config, Box<dyn Error>> {
+    let datadir_path = path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .ok_or_else(|| format!("Invalid configuration path {}", path.to_string_lossy()))?;
+    let profiles = app::config::profiles(&datadir_path);
+    match app::Config::from_file(&path) {
+        Ok(cfg) if profiles.len() <= 1 => Ok(Config::Run(cfg, path)),
+        Ok(_) => Ok(Config::Launch(datadir_path, profiles, overrides)),
+        Err(ConfigError::NotFound) => match profiles.len() {
+            0 => Ok(Config::Install(datadir_path, overrides)),
+            1 => {
+                let profile = profiles.into_iter().next().unwrap();
+                Ok(Config::Run(
+                    app::Config::from_file(&profile.path)?,
+                    profile.path,
+                ))
             }
+            _ => Ok(Config::Launch(datadir_path, profiles, overrides)),
+        },
+        Err(e) => Err(format!("Failed to read configuration file: {}", e).into()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n", cli::USAGE);
+            return Err(e.into());
         }
-        Args::DatadirPath(datadir_path) => {
-            let mut path = datadir_path.clone();
-            path.push(DEFAULT_FILE_NAME);
-            match app::Config::from_file(&path) {
-                Ok(cfg) => Config::Run(cfg),
-                Err(ConfigError::NotFound) => Config::Install(datadir_path),
-                Err(e) => {
-                    return Err(format!("Failed to read configuration file: {}", e).into());
-                }
-            }
+    };
+
+    match args.command.clone() {
+        Some(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Some(cli::Command::Version) => {
+            println!("revault-gui {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
         }
+        Some(command) => {
+            tracing::subscriber::set_global_default(
+                tracing_subscriber::FmtSubscriber::builder()
+                    .with_env_filter(log_level(args.log_level.as_deref(), None)?)
+                    .finish(),
+            )?;
+            return cli::run(command, &args);
+        }
+        None => {}
+    }
+
+    let mut config = if let Some(path) = &args.conf {
+        Config::Run(app::Config::from_file(path)?, path.clone())
+    } else if args.network.is_some() {
+        let path = args.config_path()?;
+        match app::Config::from_file(&path) {
+            Ok(cfg) => Config::Run(cfg, path),
+            Err(ConfigError::NotFound) => Config::Install(args.datadir()?, args.overrides.clone()),
+            Err(e) => return Err(format!("Failed to read configuration file: {}", e).into()),
+        }
+    } else if let Some(datadir_path) = &args.datadir {
+        let mut path = datadir_path.clone();
+        path.push(DEFAULT_FILE_NAME);
+        datadir_config(path, args.overrides.clone())?
+    } else {
+        let path = app::Config::default_path()
+            .map_err(|e| format!("Failed to find revault GUI config: {}", e))?;
+        datadir_config(path, args.overrides.clone())?
     };
 
-    let level = if let Config::Run(cfg) = &config {
-        log_level_from_config(&cfg)?
+    if let Config::Run(cfg, _) = &mut config {
+        cfg.overrides = args.overrides.clone();
+    }
+
+    let level = if let Config::Run(cfg, _) = &config {
+        log_level(args.log_level.as_deref(), Some(cfg))?
     } else {
-        EnvFilter::try_new("revault_gui=info").unwrap()
+        log_level(args.log_level.as_deref(), None)?
     };
 
     tracing::subscriber::set_global_default(
@@ -180,7 +267,14 @@ fn main() -> Result<(), Box<dyn Error>> {
             .finish(),
     )?;
 
-    if let Err(e) = GUI::run(Settings::with_flags(config)) {
+    let mut settings = Settings::with_flags(config);
+    if let Config::Run(cfg, _) = &settings.flags {
+        if let Some(size) = cfg.preferences.window_size {
+            settings.window.size = (size.width, size.height);
+        }
+    }
+
+    if let Err(e) = GUI::run(settings) {
         return Err(format!("Failed to launch UI: {}", e).into());
     };
     Ok(())
@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Role {
     Manager,
     Stakeholder,
@@ -32,3 +36,261 @@ pub enum TransactionKind {
     Cancel,
     Spend,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    Invalid(String),
+    WrongNetwork(bitcoin::Network),
+}
+
+impl std::fmt::Display for AddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "Invalid address: {}", e),
+            Self::WrongNetwork(network) => {
+                write!(f, "Address is not usable on the {} network", network)
+            }
+        }
+    }
+}
+
+/// parse_address parses the address and checks that it can be used on the network.
+/// Testnet and regtest share the same base58 prefixes, a base58 testnet address
+/// is then accepted on regtest.
+pub fn parse_address(
+    address: &str,
+    network: bitcoin::Network,
+) -> Result<bitcoin::Address, AddressError> {
+    let address = bitcoin::Address::from_str(address.trim())
+        .map_err(|e| AddressError::Invalid(e.to_string()))?;
+    let base58 = !matches!(
+        address.payload,
+        bitcoin::util::address::Payload::WitnessProgram { .. }
+    );
+    if address.network == network
+        || (base58
+            && network == bitcoin::Network::Regtest
+            && address.network == bitcoin::Network::Testnet)
+    {
+        Ok(address)
+    } else {
+        Err(AddressError::WrongNetwork(network))
+    }
+}
+
+/// pays_only_to returns true if all the outputs of the psbt pay the address.
+pub fn pays_only_to(
+    psbt: &bitcoin::util::psbt::PartiallySignedTransaction,
+    address: &bitcoin::Address,
+) -> bool {
+    let outputs = &psbt.global.unsigned_tx.output;
+    let script_pubkey = address.script_pubkey();
+    !outputs.is_empty() && outputs.iter().all(|o| o.script_pubkey == script_pubkey)
+}
+
+/// bip21_uri returns the BIP21 payment request of the address
+/// with the optional amount in satoshis and label.
+pub fn bip21_uri(address: &bitcoin::Address, amount: Option<u64>, label: Option<&str>) -> String {
+    let mut params = Vec::new();
+    if let Some(amount) = amount {
+        let btc = format!("{}.{:08}", amount / 100_000_000, amount % 100_000_000);
+        params.push(format!(
+            "amount={}",
+            btc.trim_end_matches('0').trim_end_matches('.')
+        ));
+    }
+    if let Some(label) = label.filter(|l| !l.is_empty()) {
+        params.push(format!("label={}", percent_encode(label)));
+    }
+    if params.is_empty() {
+        format!("bitcoin:{}", address)
+    } else {
+        format!("bitcoin:{}?{}", address, params.join("&"))
+    }
+}
+
+/// percent_encode escapes the characters of a URI query value
+/// that are not unreserved (RFC 3986).
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// fees returns the fees paid by the transaction of the psbt,
+/// or None if the value of an input is unknown or if the values overflow.
+pub fn fees(psbt: &bitcoin::util::psbt::PartiallySignedTransaction) -> Option<u64> {
+    let mut inputs_value: u64 = 0;
+    for input in &psbt.inputs {
+        inputs_value = inputs_value.checked_add(input.witness_utxo.as_ref()?.value)?;
+    }
+    let outputs_value = psbt
+        .global
+        .unsigned_tx
+        .output
+        .iter()
+        .try_fold(0u64, |sum, output| sum.checked_add(output.value))?;
+    inputs_value.checked_sub(outputs_value)
+}
+
+/// values_overflow returns true if the sum of the inputs values or the sum
+/// of the outputs values of the psbt overflows.
+pub fn values_overflow(psbt: &bitcoin::util::psbt::PartiallySignedTransaction) -> bool {
+    let inputs = psbt.inputs.iter().try_fold(0u64, |sum, input| {
+        sum.checked_add(
+            input
+                .witness_utxo
+                .as_ref()
+                .map(|utxo| utxo.value)
+                .unwrap_or(0),
+        )
+    });
+    let outputs = psbt
+        .global
+        .unsigned_tx
+        .output
+        .iter()
+        .try_fold(0u64, |sum, output| sum.checked_add(output.value));
+    inputs.is_none() || outputs.is_none()
+}
+
+/// Average time between two blocks, used to estimate the wall-clock deadlines.
+pub const BLOCK_INTERVAL_SECS: i64 = 600;
+
+/// Urgency of a cancel before the unvault timelock expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    /// More than WARNING_BLOCKS blocks remain.
+    Normal,
+    /// WARNING_BLOCKS blocks or less remain.
+    Warning,
+    /// CRITICAL_BLOCKS blocks or less remain.
+    Critical,
+    /// The timelock expired, the funds can be spent by the managers.
+    Expired,
+}
+
+impl Urgency {
+    pub const WARNING_BLOCKS: u64 = 36;
+    pub const CRITICAL_BLOCKS: u64 = 6;
+}
+
+/// UnvaultTimelock is the relative timelock (CSV) of the unvault output
+/// during which the stakeholders can still cancel the unvault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnvaultTimelock {
+    /// CSV of the unvault descriptor, in blocks.
+    pub csv: u32,
+    /// height of the block containing the unvault transaction,
+    /// None if the unvault transaction is not confirmed yet.
+    pub unvault_height: Option<u64>,
+}
+
+impl UnvaultTimelock {
+    pub fn new(csv: u32, unvault_height: Option<u64>) -> Self {
+        Self {
+            csv,
+            unvault_height,
+        }
+    }
+
+    /// started is false while the unvault transaction is unconfirmed.
+    pub fn started(&self) -> bool {
+        self.unvault_height.is_some()
+    }
+
+    /// remaining_blocks returns the number of blocks to be mined on top of
+    /// the tip before the unvault output can be spent.
+    pub fn remaining_blocks(&self, tip: u64) -> u64 {
+        match self.unvault_height {
+            // The unvault tx has one confirmation when it is in the tip.
+            Some(height) => (height + self.csv as u64).saturating_sub(tip + 1),
+            None => self.csv as u64,
+        }
+    }
+
+    /// deadline estimates the unix timestamp at which the timelock expires.
+    pub fn deadline(&self, tip: u64, now: i64) -> i64 {
+        now + self.remaining_blocks(tip) as i64 * BLOCK_INTERVAL_SECS
+    }
+
+    pub fn urgency(&self, tip: u64) -> Urgency {
+        if !self.started() {
+            return Urgency::Normal;
+        }
+        match self.remaining_blocks(tip) {
+            0 => Urgency::Expired,
+            n if n <= Urgency::CRITICAL_BLOCKS => Urgency::Critical,
+            n if n <= Urgency::WARNING_BLOCKS => Urgency::Warning,
+            _ => Urgency::Normal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip21() {
+        let address =
+            bitcoin::Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").unwrap();
+        assert_eq!(
+            bip21_uri(&address, None, None),
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+        );
+        assert_eq!(
+            bip21_uri(&address, Some(150_000_000), Some("Deposit #1")),
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=1.5&label=Deposit%20%231"
+        );
+        assert_eq!(
+            bip21_uri(&address, Some(100_000_000), Some("")),
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=1"
+        );
+        assert_eq!(
+            bip21_uri(&address, Some(1), None),
+            "bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq?amount=0.00000001"
+        );
+    }
+
+    #[test]
+    fn unvault_timelock() {
+        let timelock = UnvaultTimelock::new(144, None);
+        assert_eq!(timelock.remaining_blocks(1000), 144);
+        assert_eq!(timelock.urgency(1000), Urgency::Normal);
+
+        let timelock = UnvaultTimelock::new(144, Some(1000));
+        assert_eq!(timelock.remaining_blocks(1000), 143);
+        assert_eq!(timelock.deadline(1000, 0), 143 * BLOCK_INTERVAL_SECS);
+        assert_eq!(timelock.urgency(1000), Urgency::Normal);
+        assert_eq!(timelock.urgency(1110), Urgency::Warning);
+        assert_eq!(timelock.urgency(1140), Urgency::Critical);
+        assert_eq!(timelock.remaining_blocks(1143), 0);
+        assert_eq!(timelock.urgency(1143), Urgency::Expired);
+        assert_eq!(timelock.remaining_blocks(2000), 0);
+    }
+
+    #[test]
+    fn address_network() {
+        let mainnet = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+        let testnet = "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn";
+        assert!(parse_address(mainnet, bitcoin::Network::Bitcoin).is_ok());
+        assert_eq!(
+            parse_address(mainnet, bitcoin::Network::Testnet),
+            Err(AddressError::WrongNetwork(bitcoin::Network::Testnet))
+        );
+        assert!(parse_address(testnet, bitcoin::Network::Testnet).is_ok());
+        assert!(parse_address(testnet, bitcoin::Network::Regtest).is_ok());
+        assert!(parse_address(testnet, bitcoin::Network::Bitcoin).is_err());
+        assert!(matches!(
+            parse_address("not an address", bitcoin::Network::Bitcoin),
+            Err(AddressError::Invalid(_))
+        ));
+    }
+}
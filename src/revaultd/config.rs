@@ -1,8 +1,13 @@
-use bitcoin::{util::bip32, Network};
+use bitcoin::{hashes::hex::FromHex, util::bip32, Network};
+use miniscript::{Descriptor, DescriptorPublicKey, ForEachKey};
+use revault_tx::scripts::UnvaultDescriptor;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
+    io::Write,
     net::SocketAddr,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 // This file is adapted from github.com/re-vault/revaultd:
@@ -55,6 +60,92 @@ pub struct ScriptsConfig {
     pub cpfp_descriptor: String,
 }
 
+impl ScriptsConfig {
+    /// unvault_csv parses the unvault descriptor and returns its relative
+    /// timelock in blocks, or None if the descriptor is invalid.
+    pub fn unvault_csv(&self) -> Option<u32> {
+        UnvaultDescriptor::from_str(&self.unvault_descriptor)
+            .ok()
+            .map(|descriptor| descriptor.csv_value())
+    }
+
+    /// deposit_address derives the deposit descriptor at the given index
+    /// and returns its address, or None if the descriptor is invalid.
+    pub fn deposit_address(&self, index: u32, network: Network) -> Option<bitcoin::Address> {
+        Descriptor::<DescriptorPublicKey>::from_str(&self.deposit_descriptor)
+            .ok()?
+            .derive(index)
+            .address(network)
+    }
+
+    /// deposit_addresses derives the deposit descriptor at the given indexes, the
+    /// descriptor is parsed once. It returns None if the descriptor is invalid.
+    pub fn deposit_addresses(
+        &self,
+        indexes: std::ops::Range<u32>,
+        network: Network,
+    ) -> Option<Vec<(u32, bitcoin::Address)>> {
+        let descriptor =
+            Descriptor::<DescriptorPublicKey>::from_str(&self.deposit_descriptor).ok()?;
+        Some(
+            indexes
+                .filter_map(|i| descriptor.derive(i).address(network).map(|addr| (i, addr)))
+                .collect(),
+        )
+    }
+
+    /// deposit_descriptor_checksum returns the BIP380 checksum of the deposit
+    /// descriptor written in its canonical form, or None if the descriptor is invalid.
+    pub fn deposit_descriptor_checksum(&self) -> Option<String> {
+        let descriptor =
+            Descriptor::<DescriptorPublicKey>::from_str(&self.deposit_descriptor).ok()?;
+        let descriptor = descriptor.to_string();
+        descriptor_checksum(descriptor.split('#').next().unwrap_or_default())
+    }
+
+    /// deposit_derivation_paths returns for each stakeholder key of the deposit
+    /// descriptor the path deriving the address at the given index, prefixed by
+    /// the master fingerprint if the key origin is known or else by the xpub
+    /// fingerprint, in order to verify the address with a signing device.
+    pub fn deposit_derivation_paths(&self, index: u32) -> Vec<String> {
+        let descriptor = match Descriptor::<DescriptorPublicKey>::from_str(&self.deposit_descriptor)
+        {
+            Ok(descriptor) => descriptor,
+            Err(_) => return Vec::new(),
+        };
+        let mut paths = Vec::new();
+        descriptor.for_each_key(|key| {
+            if let DescriptorPublicKey::XPub(xpub) = key.as_key() {
+                let (fingerprint, mut path) = match &xpub.origin {
+                    Some((fingerprint, origin)) => {
+                        (fingerprint.to_string(), origin.as_ref().to_vec())
+                    }
+                    None => (xpub.xkey.fingerprint().to_string(), Vec::new()),
+                };
+                path.extend(xpub.derivation_path.as_ref());
+                let mut path: Vec<String> = path.iter().map(|child| child.to_string()).collect();
+                path.push(index.to_string());
+                paths.push(format!("{}/{}", fingerprint, path.join("/")));
+            }
+            true
+        });
+        paths
+    }
+
+    /// deposit_address_index returns the first derivation index lower than the limit
+    /// at which the deposit descriptor derives the address.
+    pub fn deposit_address_index(
+        &self,
+        address: &bitcoin::Address,
+        network: Network,
+        limit: u32,
+    ) -> Option<u32> {
+        let descriptor =
+            Descriptor::<DescriptorPublicKey>::from_str(&self.deposit_descriptor).ok()?;
+        (0..limit).find(|i| descriptor.derive(*i).address(network).as_ref() == Some(address))
+    }
+}
+
 /// Static informations we require to operate
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -82,6 +173,22 @@ pub struct Config {
 
 pub const DEFAULT_FILE_NAME: &str = "revaultd.toml";
 
+/// Log levels accepted by revaultd.
+pub const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// is_valid_host returns true if the host of a coordinator, cosigner
+/// or watchtower is a socket address.
+pub fn is_valid_host(host: &str) -> bool {
+    SocketAddr::from_str(host).is_ok()
+}
+
+/// is_valid_noise_key returns true if the noise key is 32 bytes encoded in hex.
+pub fn is_valid_noise_key(key: &str) -> bool {
+    Vec::<u8>::from_hex(key)
+        .map(|bytes| bytes.len() == 32)
+        .unwrap_or(false)
+}
+
 impl Config {
     pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
         let config = std::fs::read(path)
@@ -97,20 +204,99 @@ impl Config {
         Ok(config)
     }
 
-    /// default revaultd socket path is .revault/bitcoin/revaultd_rpc
-    pub fn socket_path(&self) -> Result<PathBuf, ConfigError> {
-        let mut path = if let Some(ref datadir) = self.data_dir {
-            datadir.clone()
+    /// Writes the configuration to the file atomically: the content is written to
+    /// a temporary file renamed over the previous one, which is kept as a backup.
+    /// The original document is edited in place: its comments and the keys this
+    /// model does not know are kept. It is rewritten only if it cannot be edited.
+    pub fn to_file(&self, path: &Path) -> Result<(), ConfigError> {
+        // Step needed because of ValueAfterTable error in the toml serialize implementation.
+        let value = toml::Value::try_from(self).map_err(|e| {
+            ConfigError::WritingFile(format!("Serializing configuration file: {}", e))
+        })?;
+
+        let original = std::fs::read_to_string(path).unwrap_or_default();
+        let content = toml::from_str::<toml::value::Table>(&original)
+            .ok()
+            .and_then(|document| {
+                let previous = toml::from_str::<Config>(&original)
+                    .ok()
+                    .and_then(|c| toml::Value::try_from(c).ok())?;
+                let merged = merge_toml(&document, previous.as_table()?, value.as_table()?);
+                update_toml(&original, &merged)
+            })
+            .unwrap_or_else(|| value.to_string());
+
+        if path.exists() {
+            std::fs::copy(path, path.with_extension("toml.bak")).map_err(|e| {
+                ConfigError::WritingFile(format!("Backing up configuration file: {}", e))
+            })?;
+        }
+
+        write_atomic(path, content.as_bytes())
+            .map_err(|e| ConfigError::WritingFile(format!("Writing configuration file: {}", e)))
+    }
+
+    /// returns the custom data directory or the default one.
+    pub fn data_dir(&self) -> Result<PathBuf, ConfigError> {
+        if let Some(ref datadir) = self.data_dir {
+            Ok(datadir.clone())
         } else {
             default_datadir().map_err(|_| {
                 ConfigError::Unexpected("Could not locate the default datadir.".to_owned())
-            })?
-        };
+            })
+        }
+    }
+
+    /// default revaultd socket path is .revault/bitcoin/revaultd_rpc
+    pub fn socket_path(&self) -> Result<PathBuf, ConfigError> {
+        let mut path = self.data_dir()?;
         path.push(&self.bitcoind_config.network.to_string());
         path.push("revaultd_rpc");
         Ok(path)
     }
 
+    /// revaultd writes its log file in the network directory when it runs as a daemon.
+    pub fn log_path(&self) -> Result<PathBuf, ConfigError> {
+        let mut path = self.data_dir()?;
+        path.push(&self.bitcoind_config.network.to_string());
+        path.push("log");
+        Ok(path)
+    }
+
+    /// redacted writes the configuration without the keys, the addresses and the
+    /// descriptors, so that it can be shared with the support.
+    pub fn redacted(&self) -> Result<String, ConfigError> {
+        let mut value =
+            toml::Value::try_from(self).map_err(|e| ConfigError::Unexpected(e.to_string()))?;
+        redact(&mut value);
+        Ok(value.to_string())
+    }
+
+    /// redact_line hides in a log line the values of the redacted fields of the
+    /// configuration, the extended keys and the addresses.
+    pub fn redact_line(&self, line: &str) -> String {
+        let mut line = line.to_string();
+        if let Ok(value) = toml::Value::try_from(self) {
+            let mut values = Vec::new();
+            redacted_values(&value, &mut values);
+            for value in values.iter().filter(|v| !v.is_empty()) {
+                line = line.replace(value.as_str(), "<redacted>");
+            }
+        }
+        let words: Vec<String> = line
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| {
+                bip32::ExtendedPubKey::from_str(word).is_ok()
+                    || bitcoin::Address::from_str(word).is_ok()
+            })
+            .map(|word| word.to_string())
+            .collect();
+        for word in words {
+            line = line.replace(&word, "<redacted>");
+        }
+        line
+    }
+
     /// default_config_path returns the default config location of the revault deamon.
     pub fn default_path() -> Result<PathBuf, ConfigError> {
         let mut datadir = default_datadir().map_err(|_| {
@@ -149,12 +335,379 @@ impl Config {
     }
 }
 
+/// Fields of the configuration hidden by Config::redacted.
+const REDACTED_FIELDS: [&str; 9] = [
+    "cookie_path",
+    "data_dir",
+    "xpub",
+    "noise_key",
+    "coordinator_noise_key",
+    "emergency_address",
+    "deposit_descriptor",
+    "unvault_descriptor",
+    "cpfp_descriptor",
+];
+
+/// redacted_values lists the string values of the redacted fields.
+fn redacted_values(value: &toml::Value, values: &mut Vec<String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                match value {
+                    toml::Value::String(s) if REDACTED_FIELDS.contains(&key.as_str()) => {
+                        values.push(s.clone())
+                    }
+                    _ => redacted_values(value, values),
+                }
+            }
+        }
+        toml::Value::Array(values_) => values_.iter().for_each(|v| redacted_values(v, values)),
+        _ => {}
+    }
+}
+
+fn redact(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = toml::Value::String("<redacted>".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        toml::Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// write_atomic writes the content to a temporary file next to the path and renames
+/// it over the path, so that a crash never leaves a truncated file.
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut file = std::fs::File::create(&tmp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// update_toml returns the original document with the values of the given table.
+/// The comments and the order of the original keys are kept, new keys are added
+/// at the end of their table. The keys of the document missing from the table are
+/// removed. It returns None if the original document cannot be updated line by line,
+/// for example if a value spans multiple lines or if a key is dotted.
+pub fn update_toml(original: &str, values: &toml::value::Table) -> Option<String> {
+    let mut entries = Vec::new();
+    let mut tables = Vec::new();
+    flatten_table(String::new(), values, &mut entries, &mut tables);
+
+    // The first block is the root table, then one block per table header.
+    let mut blocks: Vec<(String, Vec<String>)> = vec![(String::new(), Vec::new())];
+    for line in original.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("[[") {
+            return None;
+        }
+        if trimmed.starts_with('[') {
+            let name = parse_table_name(trimmed[1..].split(']').next()?)?;
+            blocks.push((name, vec![line.to_string()]));
+        } else {
+            blocks.last_mut()?.1.push(line.to_string());
+        }
+    }
+
+    // Tables written inline in the original document.
+    let mut inlined: HashSet<String> = HashSet::new();
+    let mut written: HashSet<(String, String)> = HashSet::new();
+    let mut output: Vec<String> = Vec::new();
+    for (table, lines) in &blocks {
+        if !table.is_empty() && !tables.contains(table) {
+            // The table was removed, its keys are dropped with it.
+            continue;
+        }
+        let mut block = Vec::new();
+        for line in lines {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('[') {
+                block.push(line.clone());
+                continue;
+            }
+            let (key, i) = parse_key(trimmed)?;
+            let value = trimmed[i + 1..].trim();
+            if value.starts_with("\"\"\"") || value.starts_with("'''") {
+                return None;
+            }
+            let old = parse_value(value)?;
+            match entries.iter().find(|(t, k, _)| t == table && *k == key) {
+                Some((_, _, new)) => {
+                    written.insert((table.clone(), key.to_string()));
+                    if let toml::Value::Table(_) = new {
+                        inlined.insert(table_path(table, key));
+                    }
+                    if &&old == new {
+                        block.push(line.clone());
+                    } else {
+                        // The indentation, the key and the comment of the line are kept.
+                        let indent = &line[..line.len() - line.trim_start().len()];
+                        let comment = value_comment(value, &old)
+                            .map(|c| format!(" {}", c))
+                            .unwrap_or_default();
+                        block.push(format!(
+                            "{}{} = {}{}",
+                            indent,
+                            trimmed[..i].trim(),
+                            inline_value(new),
+                            comment
+                        ));
+                    }
+                }
+                // The key was removed from the table.
+                None => continue,
+            }
+        }
+
+        let missing: Vec<String> = entries
+            .iter()
+            .filter(|(t, k, v)| {
+                t == table
+                    && !v.is_table()
+                    && !written.contains(&(t.clone(), k.clone()))
+                    && !is_inlined(&inlined, t)
+            })
+            .map(|(_, k, v)| format!("{} = {}", toml_key(k), inline_value(v)))
+            .collect();
+        // New keys are inserted after the last key of the table.
+        let position = block
+            .iter()
+            .rposition(|l| {
+                let l = l.trim();
+                !l.is_empty() && !l.starts_with('#')
+            })
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        block.splice(position..position, missing);
+        output.extend(block);
+    }
+
+    let existing: Vec<&String> = blocks.iter().map(|(name, _)| name).collect();
+    for table in &tables {
+        if existing.contains(&table) || is_inlined(&inlined, table) {
+            continue;
+        }
+        if !output.is_empty() && !output.last()?.trim().is_empty() {
+            output.push(String::new());
+        }
+        output.push(format!("[{}]", table));
+        for (_, k, v) in entries
+            .iter()
+            .filter(|(t, _, v)| t == table && !v.is_table())
+        {
+            output.push(format!("{} = {}", toml_key(k), inline_value(v)));
+        }
+    }
+
+    let mut content = output.join("\n");
+    content.push('\n');
+    Some(content)
+}
+
+/// parse_key returns the unquoted key of the `key = value` line and the position
+/// of its `=`, or None if the key is dotted.
+fn parse_key(line: &str) -> Option<(String, usize)> {
+    let quote = line.chars().next().filter(|c| *c == '"' || *c == '\'');
+    let (key, rest) = match quote {
+        Some(q) => {
+            let end = line[1..].find(q)? + 1;
+            let key = &line[1..end];
+            if q == '"' && key.contains('\\') {
+                return None;
+            }
+            (key.to_string(), end + 1)
+        }
+        None => {
+            let end = line.find('=')?;
+            let key = line[..end].trim();
+            if key.is_empty() || !key.chars().all(is_bare_key_char) {
+                return None;
+            }
+            (key.to_string(), end)
+        }
+    };
+    let i = rest + line[rest..].find('=')?;
+    if !line[rest..i].trim().is_empty() {
+        return None;
+    }
+    Some((key, i))
+}
+
+/// parse_table_name returns the name of the table header with its keys written
+/// like toml_key does, or None if a key cannot be parsed.
+fn parse_table_name(name: &str) -> Option<String> {
+    let mut keys = Vec::new();
+    let mut rest = name.trim();
+    loop {
+        let end = match rest.chars().next() {
+            Some(q) if q == '"' || q == '\'' => {
+                let end = rest[1..].find(q)? + 1;
+                if q == '"' && rest[1..end].contains('\\') {
+                    return None;
+                }
+                keys.push(toml_key(&rest[1..end]));
+                end + 1
+            }
+            _ => {
+                let end = rest.find('.').unwrap_or_else(|| rest.len());
+                let key = rest[..end].trim();
+                if key.is_empty() || !key.chars().all(is_bare_key_char) {
+                    return None;
+                }
+                keys.push(key.to_string());
+                end
+            }
+        };
+        rest = rest[end..].trim_start();
+        if rest.is_empty() {
+            return Some(keys.join("."));
+        }
+        rest = rest.strip_prefix('.')?.trim_start();
+    }
+}
+
+/// parse_value parses the value of a `key = value` line, with its trailing comment.
+fn parse_value(value: &str) -> Option<toml::Value> {
+    toml::from_str::<toml::value::Table>(&format!("v = {}", value))
+        .ok()?
+        .remove("v")
+}
+
+/// value_comment returns the trailing comment of the value: the text after the
+/// first `#` from which the value parses alone.
+fn value_comment<'a>(value: &'a str, parsed: &toml::Value) -> Option<&'a str> {
+    value
+        .match_indices('#')
+        .map(|(i, _)| i)
+        .find(|i| parse_value(&value[..*i]).as_ref() == Some(parsed))
+        .map(|i| &value[i..])
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// toml_key quotes the key if it is not a bare key.
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(is_bare_key_char) {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+/// flatten_table lists the (table, key, value) entries of the table
+/// and the paths of its sub tables.
+fn flatten_table<'a>(
+    path: String,
+    table: &'a toml::value::Table,
+    entries: &mut Vec<(String, String, &'a toml::Value)>,
+    tables: &mut Vec<String>,
+) {
+    for (key, value) in table {
+        entries.push((path.clone(), key.clone(), value));
+    }
+    for (key, value) in table {
+        if let toml::Value::Table(t) = value {
+            let sub = table_path(&path, &toml_key(key));
+            tables.push(sub.clone());
+            flatten_table(sub, t, entries, tables);
+        }
+    }
+}
+
+/// inline_value writes the value on a single line, tables are written as inline tables.
+fn inline_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::Table(table) => format!(
+            "{{ {} }}",
+            table
+                .iter()
+                .map(|(k, v)| format!("{} = {}", toml_key(k), inline_value(v)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        toml::Value::Array(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(inline_value)
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        _ => value.to_string(),
+    }
+}
+
+fn table_path(table: &str, key: &str) -> String {
+    if table.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", table, key)
+    }
+}
+
+fn is_inlined(inlined: &HashSet<String>, table: &str) -> bool {
+    inlined
+        .iter()
+        .any(|t| table == t || table.starts_with(&format!("{}.", t)))
+}
+
+/// merge_toml returns the document with the values of the new model. The keys
+/// of the document that the model does not know are kept, the keys of the
+/// previous model missing from the new one are removed.
+pub fn merge_toml(
+    document: &toml::value::Table,
+    previous: &toml::value::Table,
+    new: &toml::value::Table,
+) -> toml::value::Table {
+    let empty = toml::value::Table::new();
+    let mut merged = toml::value::Table::new();
+    for (key, value) in document {
+        match (new.get(key), value) {
+            (Some(toml::Value::Table(new)), toml::Value::Table(document)) => {
+                let previous = previous
+                    .get(key)
+                    .and_then(|v| v.as_table())
+                    .unwrap_or(&empty);
+                merged.insert(
+                    key.clone(),
+                    toml::Value::Table(merge_toml(document, previous, new)),
+                );
+            }
+            (Some(new), _) => {
+                merged.insert(key.clone(), new.clone());
+            }
+            (None, _) if previous.contains_key(key) => {}
+            (None, _) => {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for (key, value) in new {
+        if !merged.contains_key(key) {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    merged
+}
+
 // From github.com/revault/revaultd:
 // Get the absolute path to the revault configuration folder.
 ///
@@ -187,6 +740,7 @@ pub fn default_datadir() -> Result<PathBuf, ()> {
 pub enum ConfigError {
     NotFound,
     ReadingFile(String),
+    WritingFile(String),
     Unexpected(String),
 }
 
@@ -197,9 +751,143 @@ impl std::fmt::Display for ConfigError {
             Self::ReadingFile(e) => {
                 write!(f, "Revaultd Configuration error while reading file: {}", e)
             }
+            Self::WritingFile(e) => {
+                write!(f, "Revaultd Configuration error while writing file: {}", e)
+            }
             Self::Unexpected(e) => write!(f, "Revaultd Configuration error unexpected: {}", e),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
+
+/// Characters of a descriptor, by group of 32 as defined by BIP380.
+const DESCRIPTOR_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}\
+                                  IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~\
+                                  ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// Characters of a descriptor checksum.
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn descriptor_polymod(c: u64, value: u64) -> u64 {
+    const GENERATORS: [u64; 5] = [
+        0xf5dee51989,
+        0xa9fdca3312,
+        0x1bab10e32d,
+        0x3706b1677a,
+        0x644d626ffd,
+    ];
+    let c0 = c >> 35;
+    let mut c = ((c & 0x7ffffffff) << 5) ^ value;
+    for (i, generator) in GENERATORS.iter().enumerate() {
+        if (c0 >> i) & 1 == 1 {
+            c ^= generator;
+        }
+    }
+    c
+}
+
+/// descriptor_checksum computes the BIP380 checksum of a descriptor without
+/// checksum, it returns None if the descriptor has an invalid character.
+fn descriptor_checksum(descriptor: &str) -> Option<String> {
+    let mut c = 1;
+    let mut class = 0;
+    let mut class_count = 0;
+    for ch in descriptor.chars() {
+        let position = DESCRIPTOR_CHARSET.find(ch)? as u64;
+        c = descriptor_polymod(c, position & 31);
+        class = class * 3 + (position >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            c = descriptor_polymod(c, class);
+            class = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        c = descriptor_polymod(c, class);
+    }
+    for _ in 0..8 {
+        c = descriptor_polymod(c, 0);
+    }
+    c ^= 1;
+    Some(
+        (0..8)
+            .map(|j| CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum_descriptor() {
+        assert_eq!(
+            descriptor_checksum("raw(deadbeef)"),
+            Some("89f8spxm".to_string())
+        );
+        assert_eq!(descriptor_checksum("raw(deadbeef)\u{e9}"), None);
+    }
+
+    #[test]
+    fn deposit_derivation_paths() {
+        let scripts = ScriptsConfig {
+            deposit_descriptor: "wsh(multi(2,[aabbccdd/48'/1']xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8/0/*,xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB/*))".to_string(),
+            unvault_descriptor: String::new(),
+            cpfp_descriptor: String::new(),
+        };
+        assert_eq!(
+            scripts.deposit_derivation_paths(5),
+            vec!["aabbccdd/48'/1'/0/5".to_string(), "bd16bee5/5".to_string()]
+        );
+    }
+
+    #[test]
+    fn merge_toml_keeps_unknown_keys() {
+        let document: toml::value::Table = toml::from_str(
+            "unknown = 1\nremoved = 2\n[bitcoind]\nkept = 3\nnetwork = \"regtest\"\n",
+        )
+        .unwrap();
+        let previous: toml::value::Table =
+            toml::from_str("removed = 2\n[bitcoind]\nnetwork = \"regtest\"\n").unwrap();
+        let new: toml::value::Table =
+            toml::from_str("added = 4\n[bitcoind]\nnetwork = \"bitcoin\"\n").unwrap();
+        let merged: toml::value::Table =
+            toml::from_str("unknown = 1\nadded = 4\n[bitcoind]\nkept = 3\nnetwork = \"bitcoin\"\n")
+                .unwrap();
+        assert_eq!(merge_toml(&document, &previous, &new), merged);
+    }
+
+    #[test]
+    fn redact_config() {
+        let mut config = Config::new();
+        config.coordinator_host = "127.0.0.1:8383".to_string();
+        config.coordinator_noise_key = "secret_coordinator_key".to_string();
+        config.scripts_config.deposit_descriptor = "secret_descriptor".to_string();
+        config.manager_config = Some(ManagerConfig {
+            xpub: bip32::ExtendedPubKey::from_str(
+                "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+            )
+            .unwrap(),
+            cosigners: vec![CosignerConfig {
+                host: "127.0.0.1:8384".to_string(),
+                noise_key: "secret_cosigner_key".to_string(),
+            }],
+        });
+
+        let redacted = config.redacted().unwrap();
+        assert!(redacted.contains("127.0.0.1:8383"));
+        assert!(redacted.contains("127.0.0.1:8384"));
+        assert!(!redacted.contains("secret"));
+        assert!(!redacted.contains("xpub661"));
+
+        config.bitcoind_config.cookie_path = PathBuf::from("/secret/.cookie");
+        assert!(!config.redacted().unwrap().contains("/secret/.cookie"));
+        let line = config.redact_line(
+            "using /secret/.cookie, deposit to bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+        );
+        assert_eq!(line, "using <redacted>, deposit to <redacted>");
+    }
+}
@@ -1,8 +1,10 @@
 use serde_json::json;
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::path::Path;
-use std::process::Command;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use bitcoin::{base64, consensus, util::psbt::PartiallySignedTransaction as Psbt};
 use serde::de::DeserializeOwned;
@@ -12,6 +14,8 @@ use tracing::{debug, error, info, span, Level};
 mod client;
 pub mod config;
 pub mod model;
+pub mod supervisor;
+pub mod version;
 
 use client::Client;
 use config::Config;
@@ -19,6 +23,7 @@ use model::{
     DepositAddress, RevocationTransactions, SpendTransaction, SpendTx, SpendTxStatus,
     UnvaultTransaction, Vault, VaultStatus, VaultTransactions,
 };
+use version::{Version, LISTVAULTS_OUTPOINTS, MAX_SUPPORTED, MIN_SUPPORTED};
 
 #[derive(Debug, Clone)]
 pub enum RevaultDError {
@@ -27,6 +32,17 @@ pub enum RevaultDError {
     RPCError(String),
     IOError(std::io::ErrorKind),
     NoAnswerError,
+    /// revaultd answers with a version the GUI does not support.
+    IncompatibleVersion(String),
+}
+
+impl RevaultDError {
+    /// is_transport returns true if the request did not reach revaultd or its
+    /// answer was lost: the connection to the daemon is broken.
+    /// No answer is not a transport error, revaultd does not answer to stop.
+    pub fn is_transport(&self) -> bool {
+        matches!(self, Self::IOError(_))
+    }
 }
 
 impl std::fmt::Display for RevaultDError {
@@ -37,6 +53,11 @@ impl std::fmt::Display for RevaultDError {
             Self::UnexpectedError(e) => write!(f, "Revaultd unexpected error: {}", e),
             Self::NoAnswerError => write!(f, "Revaultd returned no answer"),
             Self::IOError(kind) => write!(f, "Revaultd io error: {:?}", kind),
+            Self::IncompatibleVersion(version) => write!(
+                f,
+                "Revaultd version {} is not supported (supported: >= {}, < {})",
+                version, MIN_SUPPORTED, MAX_SUPPORTED
+            ),
         }
     }
 }
@@ -45,6 +66,11 @@ impl std::fmt::Display for RevaultDError {
 pub struct RevaultD {
     client: Client,
     pub config: Config,
+    /// version reported by revaultd at connection, the requests are encoded
+    /// for this version.
+    pub version: Version,
+    /// connection_lost is set once a request failed with a transport error.
+    connection_lost: Arc<AtomicBool>,
 }
 
 impl RevaultD {
@@ -60,20 +86,36 @@ impl RevaultD {
         })?;
 
         let client = Client::new(socket_path);
-        let revaultd = RevaultD {
+        let mut revaultd = RevaultD {
             client,
             config: config.to_owned(),
+            version: Version::default(),
+            connection_lost: Arc::new(AtomicBool::new(false)),
         };
 
         debug!("Connecting to revaultd");
 
-        revaultd.get_info()?;
+        // The version is checked before anything else is deserialized, the
+        // answers of an unsupported revaultd may not match the GUI models.
+        let info = revaultd.get_info()?;
+        revaultd.version = info
+            .version
+            .parse::<Version>()
+            .ok()
+            .filter(Version::supported)
+            .ok_or_else(|| RevaultDError::IncompatibleVersion(info.version.clone()))?;
 
-        info!("Connected to revaultd");
+        info!("Connected to revaultd {}", revaultd.version);
 
         Ok(revaultd)
     }
 
+    /// connection_lost returns true if a request failed with a transport error,
+    /// whatever the command that sent it.
+    pub fn connection_lost(&self) -> bool {
+        self.connection_lost.load(Ordering::Relaxed)
+    }
+
     pub fn network(&self) -> bitcoin::Network {
         self.config.bitcoind_config.network
     }
@@ -92,11 +134,15 @@ impl RevaultD {
             .and_then(|res| res.into_result())
             .map_err(|e| {
                 error!("method {} failed: {}", method, e);
-                match e {
+                let e = match e {
                     client::error::Error::Io(e) => RevaultDError::IOError(e.kind()),
                     client::error::Error::NoErrorOrResult => RevaultDError::NoAnswerError,
                     _ => RevaultDError::RPCError(format!("method {} failed: {}", method, e)),
+                };
+                if e.is_transport() {
+                    self.connection_lost.store(true, Ordering::Relaxed);
                 }
+                e
             })
     }
 
@@ -105,6 +151,12 @@ impl RevaultD {
         self.call("getdepositaddress", Option::<Request>::None)
     }
 
+    /// stop asks revaultd to shut down.
+    pub fn stop(&self) -> Result<(), RevaultDError> {
+        let _res: serde_json::value::Value = self.call("stop", Option::<Request>::None)?;
+        Ok(())
+    }
+
     pub fn get_info(&self) -> Result<GetInfoResponse, RevaultDError> {
         self.call("getinfo", Option::<Request>::None)
     }
@@ -115,8 +167,18 @@ impl RevaultD {
         outpoints: Option<&Vec<String>>,
     ) -> Result<ListVaultsResponse, RevaultDError> {
         let mut args = vec![json!(statuses.unwrap_or(&[]))];
-        if let Some(outpoints) = outpoints {
-            args.push(json!(outpoints));
+        match outpoints {
+            Some(outpoints) if self.version >= LISTVAULTS_OUTPOINTS => {
+                args.push(json!(outpoints));
+            }
+            // Older versions do not take the outpoints, the vaults are filtered here.
+            Some(outpoints) => {
+                let mut res: ListVaultsResponse = self.call("listvaults", Some(args))?;
+                res.vaults
+                    .retain(|vault| outpoints.contains(&vault.outpoint()));
+                return Ok(res);
+            }
+            None => {}
         }
         self.call("listvaults", Some(args))
     }
@@ -258,51 +320,3 @@ pub struct ListOnchainTransactionsResponse {
 pub struct ListSpendTransactionsResponse {
     pub spend_txs: Vec<SpendTx>,
 }
-
-// RevaultD can start only if a config path is given.
-pub async fn start_daemon(config_path: &Path, revaultd_path: &Path) -> Result<(), RevaultDError> {
-    debug!("starting revaultd daemon");
-    let mut child = Command::new(revaultd_path)
-        .arg("--conf")
-        .arg(config_path.to_path_buf().into_os_string().as_os_str())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| {
-            RevaultDError::StartError(format!("Failed to launched revaultd: {}", e.to_string()))
-        })?;
-
-    debug!("waiting for revaultd daemon status");
-
-    let tries_timeout = std::time::Duration::from_secs(1);
-    let start = std::time::Instant::now();
-
-    while start.elapsed() < tries_timeout {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                if !status.success() {
-                    // FIXME: there should be a better way to collect the output...
-                    let output = child.wait_with_output().unwrap();
-                    return Err(RevaultDError::StartError(format!(
-                        "Error revaultd terminated with status: {} and stderr:\n{:?}",
-                        status.to_string(),
-                        String::from_utf8_lossy(&output.stderr),
-                    )));
-                } else {
-                    info!("revaultd daemon started");
-                    return Ok(());
-                }
-            }
-            Ok(None) => continue,
-            Err(e) => {
-                return Err(RevaultDError::StartError(format!(
-                    "Child did not terminate: {}",
-                    e.to_string()
-                )));
-            }
-        }
-    }
-
-    Err(RevaultDError::StartError(
-        "Child did not terminate, do you have `daemon=false` in Revault conf?".to_string(),
-    ))
-}
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Deserialize)]
 pub struct DepositAddress {
     pub address: bitcoin::Address,
+    /// derivation index of the address, older revaultd versions do not report it.
+    #[serde(default)]
+    pub index: Option<u32>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -112,6 +115,25 @@ impl std::fmt::Display for VaultStatus {
 }
 
 impl VaultStatus {
+    pub const ALL: [VaultStatus; 16] = [
+        Self::Unconfirmed,
+        Self::Funded,
+        Self::Securing,
+        Self::Secured,
+        Self::Activating,
+        Self::Active,
+        Self::Unvaulting,
+        Self::Unvaulted,
+        Self::Canceling,
+        Self::Canceled,
+        Self::EmergencyVaulting,
+        Self::EmergencyVaulted,
+        Self::UnvaultEmergencyVaulting,
+        Self::UnvaultEmergencyVaulted,
+        Self::Spending,
+        Self::Spent,
+    ];
+
     pub const DEPOSIT_AND_CURRENT: [VaultStatus; 11] = [
         Self::Funded,
         Self::Securing,
@@ -157,6 +179,13 @@ impl VaultStatus {
         Self::Spending,
     ];
 
+    pub const EMERGENCY: [VaultStatus; 4] = [
+        Self::EmergencyVaulting,
+        Self::EmergencyVaulted,
+        Self::UnvaultEmergencyVaulting,
+        Self::UnvaultEmergencyVaulted,
+    ];
+
     pub const MOVED: [VaultStatus; 4] = [
         Self::Canceled,
         Self::EmergencyVaulted,
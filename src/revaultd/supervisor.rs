@@ -0,0 +1,416 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info, warn};
+
+use super::{config::Config, RevaultD, RevaultDError};
+
+/// Number of lines of the revaultd output kept in memory.
+pub const LOG_CAPACITY: usize = 1000;
+
+/// Interval between two checks of the child process.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Delay before the first restart after a crash, doubled after each crash.
+const BACKOFF_MIN: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// The supervisor gives up after this number of crashes in a row.
+const MAX_RESTARTS: u32 = 5;
+
+/// A child running longer than this is considered healthy again,
+/// the count of crashes in a row is reset.
+const HEALTHY_AFTER: Duration = Duration::from_secs(120);
+
+/// Time given to revaultd to answer on its socket once started.
+pub const START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time given to revaultd to shut down before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaemonStatus {
+    NotStarted,
+    Running,
+    /// revaultd crashed and will be restarted after the delay.
+    Restarting {
+        attempt: u32,
+        delay: Duration,
+    },
+    /// revaultd forked itself because of `daemon = true` in its
+    /// configuration, it cannot be supervised.
+    Detached,
+    Stopped,
+    Failed(String),
+}
+
+impl std::fmt::Display for DaemonStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::NotStarted => write!(f, "not started"),
+            Self::Running => write!(f, "running"),
+            Self::Restarting { attempt, delay } => write!(
+                f,
+                "crashed, restart {}/{} in {}s",
+                attempt,
+                MAX_RESTARTS,
+                delay.as_secs()
+            ),
+            Self::Detached => write!(f, "running in the background"),
+            Self::Stopped => write!(f, "stopped"),
+            Self::Failed(e) => write!(f, "failed: {}", e),
+        }
+    }
+}
+
+/// State of the revaultd RPC socket before revaultd is started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketState {
+    Absent,
+    /// The socket file is left by a revaultd that did not shut down properly.
+    Stale,
+    /// An instance of revaultd is already listening on the socket.
+    Running,
+}
+
+/// check_socket finds if a revaultd instance already listens on the socket
+/// of the configuration.
+pub fn check_socket(config: &Config) -> Result<SocketState, RevaultDError> {
+    let socket_path = config.socket_path().map_err(|e| {
+        RevaultDError::UnexpectedError(format!("Failed to find revaultd socket path: {}", e))
+    })?;
+    if !socket_path.exists() {
+        return Ok(SocketState::Absent);
+    }
+    match RevaultD::new(config) {
+        Ok(_) => Ok(SocketState::Running),
+        Err(RevaultDError::IOError(std::io::ErrorKind::ConnectionRefused)) => {
+            Ok(SocketState::Stale)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Supervisor runs revaultd as a child of the GUI, captures its output and
+/// restarts it with a backoff if it crashes. The clones share the same child,
+/// which is stopped once the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct Supervisor {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    config_path: PathBuf,
+    revaultd_path: PathBuf,
+    child: Option<Child>,
+    /// thread stopping the child on a request of the user, joined on drop.
+    stopping: Option<JoinHandle<()>>,
+    status: DaemonStatus,
+    logs: VecDeque<String>,
+}
+
+impl Supervisor {
+    pub fn new(config_path: PathBuf, revaultd_path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                config_path,
+                revaultd_path,
+                child: None,
+                stopping: None,
+                status: DaemonStatus::NotStarted,
+                logs: VecDeque::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn status(&self) -> DaemonStatus {
+        self.lock().status.clone()
+    }
+
+    /// stop stops the revaultd started by the supervisor from a background
+    /// thread, it is not restarted. The thread is joined once the last clone
+    /// of the supervisor is dropped.
+    pub fn stop(&self) {
+        let mut inner = self.lock();
+        if let Some(mut child) = inner.child.take() {
+            inner.status = DaemonStatus::Stopped;
+            let config_path = inner.config_path.clone();
+            inner.stopping = Some(std::thread::spawn(move || stop(&mut child, &config_path)));
+        }
+    }
+
+    /// logs returns the last lines written by revaultd on stdout and stderr.
+    pub fn logs(&self) -> Vec<String> {
+        self.lock().logs.iter().cloned().collect()
+    }
+
+    /// start spawns revaultd, unless the child is already running. A stale socket
+    /// is removed, and an instance not started by the GUI is left untouched.
+    pub fn start(&self) -> Result<(), RevaultDError> {
+        let mut inner = self.lock();
+        if inner.child.is_some() {
+            return Ok(());
+        }
+        let config = Config::from_file(&inner.config_path)
+            .map_err(|e| RevaultDError::StartError(e.to_string()))?;
+        match check_socket(&config)? {
+            SocketState::Running => {
+                return Err(RevaultDError::StartError(
+                    "an instance of revaultd is already running".to_string(),
+                ))
+            }
+            SocketState::Stale => {
+                let socket_path = config.socket_path().map_err(|e| {
+                    RevaultDError::UnexpectedError(format!(
+                        "Failed to find revaultd socket path: {}",
+                        e
+                    ))
+                })?;
+                warn!(
+                    "removing the stale revaultd socket {}",
+                    socket_path.to_string_lossy()
+                );
+                std::fs::remove_file(&socket_path).map_err(|e| {
+                    RevaultDError::StartError(format!("Failed to remove stale socket: {}", e))
+                })?;
+            }
+            SocketState::Absent => {}
+        }
+
+        spawn(&mut inner, Arc::downgrade(&self.inner))?;
+        let detached = config.daemon == Some(true);
+        if detached {
+            warn!(
+                "revaultd is configured with 'daemon = true', it will not be restarted \
+                or stopped by the GUI. Set 'daemon = false' to supervise it."
+            );
+        }
+        std::thread::spawn({
+            let inner = Arc::downgrade(&self.inner);
+            move || monitor(inner, detached)
+        });
+        Ok(())
+    }
+
+    /// wait_ready waits until revaultd answers on its socket. It fails if revaultd
+    /// exits before, with the last lines of its output.
+    pub fn wait_ready(&self, timeout: Duration) -> Result<RevaultD, RevaultDError> {
+        let config = Config::from_file(&self.lock().config_path)
+            .map_err(|e| RevaultDError::StartError(e.to_string()))?;
+        let start = Instant::now();
+        loop {
+            match RevaultD::new(&config) {
+                Ok(revaultd) => return Ok(revaultd),
+                Err(e @ RevaultDError::IncompatibleVersion(_)) => return Err(e),
+                Err(e) => debug!("revaultd is not ready yet: {}", e),
+            }
+            match self.status() {
+                DaemonStatus::Running | DaemonStatus::Detached => {}
+                status => {
+                    return Err(RevaultDError::StartError(format!(
+                        "revaultd {}\n{}",
+                        status,
+                        self.logs().join("\n")
+                    )))
+                }
+            }
+            if start.elapsed() > timeout {
+                return Err(RevaultDError::StartError(
+                    "revaultd did not answer in time".to_string(),
+                ));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for Inner {
+    /// revaultd is stopped before the drop returns: the GUI process may exit
+    /// right after, which would kill a background thread before the end of the stop.
+    /// The GUI calls Supervisor::stop when it must not wait, like on a switch of profile.
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            stop(&mut child, &self.config_path);
+        }
+        if let Some(stopping) = self.stopping.take() {
+            if stopping.join().is_err() {
+                warn!("Failed to wait for revaultd to stop");
+            }
+        }
+    }
+}
+
+fn spawn(inner: &mut Inner, weak: Weak<Mutex<Inner>>) -> Result<(), RevaultDError> {
+    debug!("starting revaultd");
+    let mut child = Command::new(&inner.revaultd_path)
+        .arg("--conf")
+        .arg(inner.config_path.as_os_str())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| RevaultDError::StartError(format!("Failed to launch revaultd: {}", e)))?;
+    if let Some(stdout) = child.stdout.take() {
+        capture(stdout, weak.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        capture(stderr, weak);
+    }
+    inner.child = Some(child);
+    inner.status = DaemonStatus::Running;
+    info!("revaultd started");
+    Ok(())
+}
+
+/// capture reads the lines of the output until the child closes it.
+fn capture<R: Read + Send + 'static>(output: R, inner: Weak<Mutex<Inner>>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => return,
+            };
+            debug!(target: "revaultd", "{}", line);
+            let inner = match inner.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+            let mut inner = inner.lock().unwrap_or_else(|e| e.into_inner());
+            if inner.logs.len() == LOG_CAPACITY {
+                inner.logs.pop_front();
+            }
+            inner.logs.push_back(line);
+        }
+    });
+}
+
+/// monitor watches the child until it stops, and restarts it if it crashed.
+fn monitor(weak: Weak<Mutex<Inner>>, detached: bool) {
+    let mut crashes = 0;
+    let mut started_at = Instant::now();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let inner = match weak.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+        let mut inner = inner.lock().unwrap_or_else(|e| e.into_inner());
+        let status = match inner.child.as_mut().map(|child| child.try_wait()) {
+            None => return,
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(status))) => status,
+            Some(Err(e)) => {
+                inner.status = DaemonStatus::Failed(e.to_string());
+                inner.child = None;
+                return;
+            }
+        };
+        inner.child = None;
+        if status.success() {
+            // revaultd either forked itself or was asked to stop.
+            inner.status = if detached {
+                DaemonStatus::Detached
+            } else {
+                DaemonStatus::Stopped
+            };
+            info!("revaultd exited: {}", status);
+            return;
+        }
+
+        if started_at.elapsed() > HEALTHY_AFTER {
+            crashes = 0;
+        }
+        crashes += 1;
+        warn!("revaultd crashed: {}", exit_reason(&status));
+        if crashes > MAX_RESTARTS {
+            inner.status = DaemonStatus::Failed(format!(
+                "{}, gave up after {} restarts",
+                exit_reason(&status),
+                MAX_RESTARTS
+            ));
+            return;
+        }
+        let delay = backoff(crashes);
+        inner.status = DaemonStatus::Restarting {
+            attempt: crashes,
+            delay,
+        };
+        drop(inner);
+
+        std::thread::sleep(delay);
+        let inner = match weak.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+        let mut inner = inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = spawn(&mut inner, weak.clone()) {
+            inner.status = DaemonStatus::Failed(e.to_string());
+            return;
+        }
+        started_at = Instant::now();
+    }
+}
+
+/// backoff returns the delay before the restart following the given crash.
+fn backoff(crashes: u32) -> Duration {
+    BACKOFF_MIN
+        .checked_mul(2_u32.saturating_pow(crashes.saturating_sub(1)))
+        .map(|delay| delay.min(BACKOFF_MAX))
+        .unwrap_or(BACKOFF_MAX)
+}
+
+fn exit_reason(status: &ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exit code {}", code),
+        None => "killed by a signal".to_string(),
+    }
+}
+
+/// stop asks revaultd to shut down, and kills it if it is still running
+/// after the timeout.
+fn stop(child: &mut Child, config_path: &Path) {
+    info!("stopping revaultd");
+    match Config::from_file(config_path)
+        .map_err(|e| RevaultDError::UnexpectedError(e.to_string()))
+        .and_then(|config| RevaultD::new(&config))
+        .and_then(|revaultd| revaultd.stop())
+    {
+        Ok(()) | Err(RevaultDError::NoAnswerError) => {}
+        Err(e) => warn!("Failed to ask revaultd to stop: {}", e),
+    }
+    let start = Instant::now();
+    while start.elapsed() < STOP_TIMEOUT {
+        if let Ok(Some(_)) = child.try_wait() {
+            return;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    warn!("revaultd did not stop in time, killing it");
+    if let Err(e) = child.kill() {
+        warn!("Failed to kill revaultd: {}", e);
+    }
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delays() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(5), Duration::from_secs(16));
+        assert_eq!(backoff(7), BACKOFF_MAX);
+        assert_eq!(backoff(40), BACKOFF_MAX);
+    }
+}
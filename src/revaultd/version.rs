@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+/// Oldest revaultd version supported by the GUI.
+pub const MIN_SUPPORTED: Version = Version::new(0, 1, 0);
+
+/// First revaultd version not supported anymore by the GUI.
+pub const MAX_SUPPORTED: Version = Version::new(0, 3, 0);
+
+/// First revaultd version filtering listvaults by outpoints,
+/// the older versions only take the statuses.
+pub const LISTVAULTS_OUTPOINTS: Version = Version::new(0, 2, 0);
+
+/// Version of revaultd as reported by getinfo, the pre-release and build
+/// metadata are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// supported returns true if the GUI knows how to talk to this version.
+    pub fn supported(&self) -> bool {
+        MIN_SUPPORTED <= *self && *self < MAX_SUPPORTED
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let core = s.split(|c| c == '-' || c == '+').next().unwrap_or(s);
+        let mut numbers = core.split('.').map(|n| {
+            n.parse::<u64>()
+                .map_err(|_| format!("Invalid version: {}", s))
+        });
+        let major = numbers
+            .next()
+            .unwrap_or_else(|| Err(format!("Invalid version: {}", s)))?;
+        let minor = numbers.next().unwrap_or(Ok(0))?;
+        let patch = numbers.next().unwrap_or(Ok(0))?;
+        if numbers.next().is_some() {
+            return Err(format!("Invalid version: {}", s));
+        }
+        Ok(Version::new(major, minor, patch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(Version::from_str("0.1.0"), Ok(Version::new(0, 1, 0)));
+        assert_eq!(Version::from_str("v0.2.1\n"), Ok(Version::new(0, 2, 1)));
+        assert_eq!(Version::from_str("0.2.0-rc1"), Ok(Version::new(0, 2, 0)));
+        assert_eq!(Version::from_str("0.2"), Ok(Version::new(0, 2, 0)));
+        assert!(Version::from_str("").is_err());
+        assert!(Version::from_str("0.2.x").is_err());
+        assert!(Version::from_str("0.1.0.1").is_err());
+    }
+
+    #[test]
+    fn supported_versions() {
+        assert!(!Version::new(0, 0, 9).supported());
+        assert!(Version::new(0, 1, 0).supported());
+        assert!(Version::new(0, 2, 5).supported());
+        assert!(!Version::new(0, 3, 0).supported());
+        assert!(!Version::new(1, 0, 0).supported());
+    }
+}
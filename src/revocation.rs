@@ -0,0 +1,596 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    consensus::encode, util::psbt::PartiallySignedTransaction as Psbt, Address, OutPoint,
+    SigHashType, Txid,
+};
+use revault_tx::transactions::{
+    CancelTransaction, EmergencyTransaction, RevaultTransaction, UnvaultEmergencyTransaction,
+};
+
+use crate::revault::{fees, pays_only_to, values_overflow, TransactionKind};
+use crate::revaultd::model::{RevocationTransactions, Vault};
+
+/// Maximum part of the spent amount the revocation transactions can pay as fees, in percent.
+const MAX_FEES_PERCENT: u64 = 10;
+
+/// CheckError is a failed sanity check of a revocation transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckError {
+    /// The psbt is rejected by revault_tx.
+    Invalid(TransactionKind, String),
+    /// The transaction does not spend the vault deposit or unvault output.
+    UnexpectedInput(TransactionKind),
+    /// The unvault transaction could not be fetched, the output spent by the
+    /// cancel and unvault emergency transactions cannot be checked.
+    UnknownUnvault(String),
+    /// The transaction does not pay the emergency address or the vault deposit address.
+    UnexpectedOutput(TransactionKind),
+    /// The value of a spent output is missing or is not the expected one.
+    UnexpectedInputValue(TransactionKind),
+    /// The transaction pays more fees than allowed.
+    Fees(TransactionKind, u64),
+    /// An input is not signed with SIGHASH_ALL|SIGHASH_ANYONECANPAY.
+    SigHashType(TransactionKind),
+}
+
+impl std::fmt::Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Invalid(kind, e) => write!(f, "{} is invalid: {}", name(kind), e),
+            Self::UnexpectedInput(kind) => {
+                write!(f, "{} does not spend the vault funds", name(kind))
+            }
+            Self::UnknownUnvault(e) => write!(
+                f,
+                "Unvault transaction is unknown, the funds spent by the revocation \
+                 transactions cannot be checked: {}",
+                e
+            ),
+            Self::UnexpectedOutput(kind) => match kind {
+                TransactionKind::Cancel => write!(
+                    f,
+                    "{} does not send the funds back to the vault deposit address",
+                    name(kind)
+                ),
+                _ => write!(
+                    f,
+                    "{} does not send the funds to the emergency address",
+                    name(kind)
+                ),
+            },
+            Self::UnexpectedInputValue(kind) => {
+                write!(f, "{} spends an unexpected amount", name(kind))
+            }
+            Self::Fees(kind, fees) => write!(
+                f,
+                "{} pays {} sats of fees, more than {}% of the vault amount",
+                name(kind),
+                fees,
+                MAX_FEES_PERCENT
+            ),
+            Self::SigHashType(kind) => write!(
+                f,
+                "{} must be signed with SIGHASH_ALL|SIGHASH_ANYONECANPAY",
+                name(kind)
+            ),
+        }
+    }
+}
+
+fn name(kind: &TransactionKind) -> &'static str {
+    match kind {
+        TransactionKind::Emergency => "Emergency transaction",
+        TransactionKind::EmergencyUnvault => "Unvault emergency transaction",
+        TransactionKind::Cancel => "Cancel transaction",
+        TransactionKind::Unvault => "Unvault transaction",
+        TransactionKind::Spend => "Spend transaction",
+    }
+}
+
+/// check_revocation_txs checks the revocation transactions of the vault before the
+/// stakeholder signs them:
+/// - the emergency transaction spends the vault deposit, the cancel and unvault emergency
+///   transactions spend the unvault output. The check fails if the unvault transaction
+///   is unknown, its error is given instead.
+/// - the emergency transactions pay the emergency address, the cancel transaction pays
+///   the deposit address derived from the deposit descriptor at the vault index, it is
+///   None if the descriptor is invalid. The address reported by revaultd is not trusted.
+/// - the fees are below MAX_FEES_PERCENT of the spent amount.
+/// - the inputs are signed with SIGHASH_ALL|SIGHASH_ANYONECANPAY.
+pub fn check_revocation_txs(
+    vault: &Vault,
+    txs: &RevocationTransactions,
+    unvault_tx: Result<&Psbt, String>,
+    emergency_address: &Address,
+    deposit_address: Option<&Address>,
+) -> Vec<CheckError> {
+    let mut errors = Vec::new();
+
+    if let Err(e) =
+        EmergencyTransaction::from_psbt_serialized(&encode::serialize(&txs.emergency_tx))
+    {
+        errors.push(CheckError::Invalid(
+            TransactionKind::Emergency,
+            e.to_string(),
+        ));
+    }
+    if let Err(e) = UnvaultEmergencyTransaction::from_psbt_serialized(&encode::serialize(
+        &txs.emergency_unvault_tx,
+    )) {
+        errors.push(CheckError::Invalid(
+            TransactionKind::EmergencyUnvault,
+            e.to_string(),
+        ));
+    }
+    if let Err(e) = CancelTransaction::from_psbt_serialized(&encode::serialize(&txs.cancel_tx)) {
+        errors.push(CheckError::Invalid(TransactionKind::Cancel, e.to_string()));
+    }
+
+    let deposit = Txid::from_str(&vault.txid).ok().map(|txid| OutPoint {
+        txid,
+        vout: vault.vout,
+    });
+    errors.extend(check_tx(
+        TransactionKind::Emergency,
+        &txs.emergency_tx,
+        deposit,
+        Some(vault.amount),
+        emergency_address,
+    ));
+
+    // The unvault output is the first output of the unvault transaction.
+    let unvault_tx = match unvault_tx {
+        Ok(tx) => Some(tx),
+        Err(e) => {
+            errors.push(CheckError::UnknownUnvault(e));
+            None
+        }
+    };
+    let unvault = unvault_tx.map(|tx| OutPoint {
+        txid: tx.global.unsigned_tx.txid(),
+        vout: 0,
+    });
+    let unvault_value =
+        unvault_tx.and_then(|tx| tx.global.unsigned_tx.output.first().map(|o| o.value));
+    errors.extend(check_tx(
+        TransactionKind::EmergencyUnvault,
+        &txs.emergency_unvault_tx,
+        unvault,
+        unvault_value,
+        emergency_address,
+    ));
+    match deposit_address {
+        Some(deposit_address) => errors.extend(check_tx(
+            TransactionKind::Cancel,
+            &txs.cancel_tx,
+            unvault,
+            unvault_value,
+            deposit_address,
+        )),
+        None => errors.push(CheckError::UnexpectedOutput(TransactionKind::Cancel)),
+    }
+
+    errors
+}
+
+/// check_tx checks that the revocation transaction spends only the given outpoint
+/// with the expected value, and pays only the given address.
+fn check_tx(
+    kind: TransactionKind,
+    psbt: &Psbt,
+    outpoint: Option<OutPoint>,
+    value: Option<u64>,
+    address: &Address,
+) -> Vec<CheckError> {
+    let mut errors = Vec::new();
+    let tx = &psbt.global.unsigned_tx;
+
+    if tx.input.len() != 1 || outpoint != Some(tx.input[0].previous_output) {
+        errors.push(CheckError::UnexpectedInput(kind));
+    }
+
+    if !pays_only_to(psbt, address) {
+        errors.push(CheckError::UnexpectedOutput(kind));
+    }
+
+    let spent = psbt
+        .inputs
+        .first()
+        .and_then(|input| input.witness_utxo.as_ref())
+        .map(|utxo| utxo.value);
+    match (spent, value) {
+        (None, _) => errors.push(CheckError::UnexpectedInputValue(kind)),
+        (Some(spent), Some(value)) if spent != value => {
+            errors.push(CheckError::UnexpectedInputValue(kind))
+        }
+        _ => {}
+    }
+
+    match (fees(psbt), spent) {
+        (Some(fees), Some(spent)) => {
+            match (fees.checked_mul(100), spent.checked_mul(MAX_FEES_PERCENT)) {
+                (Some(fees_percent), Some(max)) => {
+                    if fees_percent > max {
+                        errors.push(CheckError::Fees(kind, fees));
+                    }
+                }
+                _ => errors.push(CheckError::Invalid(kind, "values overflow".to_string())),
+            }
+        }
+        (None, Some(_)) if values_overflow(psbt) => {
+            errors.push(CheckError::Invalid(kind, "values overflow".to_string()))
+        }
+        // Outputs value is higher than the inputs value.
+        (None, Some(_)) => errors.push(CheckError::UnexpectedInputValue(kind)),
+        _ => {}
+    }
+
+    if psbt
+        .inputs
+        .iter()
+        .any(|input| input.sighash_type != Some(SigHashType::AllPlusAnyoneCanPay))
+    {
+        errors.push(CheckError::SigHashType(kind));
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revaultd::model::VaultStatus;
+    use bitcoin::{Script, Transaction, TxIn, TxOut};
+
+    fn psbt(previous_output: OutPoint, value: u64, output: TxOut) -> Psbt {
+        let mut psbt = Psbt::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output,
+                script_sig: bitcoin::Script::new(),
+                sequence: 0xFFFF_FFFF,
+                witness: Vec::new(),
+            }],
+            output: vec![output],
+        })
+        .unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value,
+            script_pubkey: bitcoin::Script::new(),
+        });
+        psbt.inputs[0].sighash_type = Some(SigHashType::AllPlusAnyoneCanPay);
+        psbt
+    }
+
+    fn outpoint(vout: u32) -> OutPoint {
+        OutPoint {
+            txid: Txid::from_str(
+                "e5a7d6d9c1b1e1e0d42e6c8eab4d1c7a2f6b1a3c2d1e0f9a8b7c6d5e4f3a2b1c",
+            )
+            .unwrap(),
+            vout,
+        }
+    }
+
+    /// revocation_txs builds with revault_tx the unvault transaction and the valid
+    /// revocation transactions of a 1 BTC vault, with the emergency and deposit addresses.
+    fn revocation_txs() -> (Vault, Psbt, RevocationTransactions, Address, Address) {
+        use revault_tx::{
+            bitcoin::{self as rtx, secp256k1},
+            miniscript::DescriptorTrait,
+            scripts::{
+                DerivedCpfpDescriptor, DerivedDepositDescriptor, DerivedUnvaultDescriptor,
+                EmergencyAddress,
+            },
+            transactions::UnvaultTransaction,
+            txins::{DepositTxIn, UnvaultTxIn},
+            txouts::{DepositTxOut, UnvaultTxOut},
+        };
+
+        let secp = secp256k1::Secp256k1::new();
+        let keys: Vec<rtx::PublicKey> = (1..=5u8)
+            .map(|i| {
+                rtx::PrivateKey {
+                    compressed: true,
+                    network: rtx::Network::Bitcoin,
+                    key: secp256k1::SecretKey::from_slice(&[i; 32]).unwrap(),
+                }
+                .public_key(&secp)
+            })
+            .collect();
+        let csv = 6;
+        let deposit_descriptor = DerivedDepositDescriptor::new(keys[0..2].to_vec()).unwrap();
+        let unvault_descriptor = DerivedUnvaultDescriptor::new(
+            keys[0..2].to_vec(),
+            vec![keys[2]],
+            1,
+            keys[3..5].to_vec(),
+            csv,
+        )
+        .unwrap();
+        let cpfp_descriptor = DerivedCpfpDescriptor::new(vec![keys[2]]).unwrap();
+        let emergency_address =
+            rtx::Address::p2wsh(&rtx::Script::from(vec![0x51]), rtx::Network::Bitcoin);
+
+        let deposit = rtx::OutPoint::from_str(&format!("{}:1", outpoint(1).txid)).unwrap();
+        let deposit_txin =
+            DepositTxIn::new(deposit, DepositTxOut::new(100_000_000, &deposit_descriptor));
+        let unvault_tx = UnvaultTransaction::new(
+            deposit_txin.clone(),
+            &unvault_descriptor,
+            &cpfp_descriptor,
+            0,
+        )
+        .unwrap()
+        .into_psbt();
+        let unvault_txin = || {
+            UnvaultTxIn::new(
+                rtx::OutPoint {
+                    txid: unvault_tx.global.unsigned_tx.txid(),
+                    vout: 0,
+                },
+                UnvaultTxOut::new(
+                    unvault_tx.global.unsigned_tx.output[0].value,
+                    &unvault_descriptor,
+                ),
+                csv,
+            )
+        };
+        let emergency_tx = EmergencyTransaction::new(
+            deposit_txin,
+            None,
+            EmergencyAddress::from(emergency_address.clone()).unwrap(),
+            0,
+        )
+        .unwrap()
+        .into_psbt();
+        let emergency_unvault_tx = UnvaultEmergencyTransaction::new(
+            unvault_txin(),
+            None,
+            EmergencyAddress::from(emergency_address.clone()).unwrap(),
+            0,
+        )
+        .into_psbt();
+        let cancel_tx =
+            CancelTransaction::new(unvault_txin(), None, &deposit_descriptor, 0).into_psbt();
+
+        // revault_tx depends on another version of rust-bitcoin.
+        let convert = |psbt: &rtx::util::psbt::PartiallySignedTransaction| -> Psbt {
+            encode::deserialize(&rtx::consensus::encode::serialize(psbt)).unwrap()
+        };
+        let deposit_address = deposit_descriptor
+            .inner()
+            .address(rtx::Network::Bitcoin)
+            .unwrap();
+        let vault = Vault {
+            address: deposit_address.to_string(),
+            amount: 100_000_000,
+            derivation_index: 0,
+            received_at: 0,
+            status: VaultStatus::Funded,
+            txid: deposit.txid.to_string(),
+            updated_at: 0,
+            vout: 1,
+        };
+        (
+            vault,
+            convert(&unvault_tx),
+            RevocationTransactions {
+                cancel_tx: convert(&cancel_tx),
+                emergency_tx: convert(&emergency_tx),
+                emergency_unvault_tx: convert(&emergency_unvault_tx),
+            },
+            Address::from_str(&emergency_address.to_string()).unwrap(),
+            Address::from_str(&deposit_address.to_string()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn check_valid_revocation_txs() {
+        let (vault, unvault_tx, txs, emergency, deposit) = revocation_txs();
+        assert_eq!(
+            check_revocation_txs(&vault, &txs, Ok(&unvault_tx), &emergency, Some(&deposit)),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn check_invalid_revocation_txs() {
+        let (vault, unvault_tx, txs, emergency, deposit) = revocation_txs();
+        let other = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").unwrap();
+
+        // The emergency transaction does not pay the emergency address.
+        let mut wrong_output = txs.clone();
+        wrong_output.emergency_tx.global.unsigned_tx.output[0].script_pubkey =
+            other.script_pubkey();
+        let errors = check_revocation_txs(
+            &vault,
+            &wrong_output,
+            Ok(&unvault_tx),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors.contains(&CheckError::UnexpectedOutput(TransactionKind::Emergency)));
+
+        // The unvault emergency transaction pays more than 10% of the vault amount as fees.
+        let mut high_fees = txs.clone();
+        high_fees.emergency_unvault_tx.global.unsigned_tx.output[0].value /= 2;
+        let errors = check_revocation_txs(
+            &vault,
+            &high_fees,
+            Ok(&unvault_tx),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors
+            .iter()
+            .any(|e| matches!(e, CheckError::Fees(TransactionKind::EmergencyUnvault, _))));
+
+        // The cancel transaction is signed with SIGHASH_ALL.
+        let mut wrong_sighash = txs;
+        wrong_sighash.cancel_tx.inputs[0].sighash_type = Some(SigHashType::All);
+        let errors = check_revocation_txs(
+            &vault,
+            &wrong_sighash,
+            Ok(&unvault_tx),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors.contains(&CheckError::SigHashType(TransactionKind::Cancel)));
+    }
+
+    #[test]
+    fn check_cancel_tx_output() {
+        let emergency = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").unwrap();
+        let deposit = Address::from_str("bc1qc7slrfxkknqcq2jevvvkdgvrt8080852dfjewde").unwrap();
+        // The address reported by a compromised revaultd.
+        let reported = Address::from_str("bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh").unwrap();
+        let vault = Vault {
+            address: reported.to_string(),
+            amount: 100_000,
+            derivation_index: 3,
+            received_at: 0,
+            status: VaultStatus::Funded,
+            txid: outpoint(1).txid.to_string(),
+            updated_at: 0,
+            vout: 1,
+        };
+        let unvault_tx = psbt(
+            outpoint(1),
+            100_000,
+            TxOut {
+                value: 99_000,
+                script_pubkey: Script::new(),
+            },
+        );
+        let unvault = OutPoint {
+            txid: unvault_tx.global.unsigned_tx.txid(),
+            vout: 0,
+        };
+        let pay = |address: &Address| TxOut {
+            value: 98_000,
+            script_pubkey: address.script_pubkey(),
+        };
+        let txs = |cancel_address: &Address| RevocationTransactions {
+            cancel_tx: psbt(unvault, 99_000, pay(cancel_address)),
+            emergency_tx: psbt(outpoint(1), 100_000, pay(&emergency)),
+            emergency_unvault_tx: psbt(unvault, 99_000, pay(&emergency)),
+        };
+
+        let errors = check_revocation_txs(
+            &vault,
+            &txs(&deposit),
+            Ok(&unvault_tx),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors.iter().all(|e| matches!(e, CheckError::Invalid(..))));
+
+        let errors = check_revocation_txs(
+            &vault,
+            &txs(&reported),
+            Ok(&unvault_tx),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors.contains(&CheckError::UnexpectedOutput(TransactionKind::Cancel)));
+
+        let errors = check_revocation_txs(
+            &vault,
+            &txs(&deposit),
+            Err("unknown vault".to_string()),
+            &emergency,
+            Some(&deposit),
+        );
+        assert!(errors.contains(&CheckError::UnknownUnvault("unknown vault".to_string())));
+        assert!(errors.contains(&CheckError::UnexpectedInput(TransactionKind::Cancel)));
+    }
+
+    #[test]
+    fn check_emergency_tx() {
+        let address = Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").unwrap();
+        let deposit = OutPoint {
+            txid: Txid::from_str(
+                "e5a7d6d9c1b1e1e0d42e6c8eab4d1c7a2f6b1a3c2d1e0f9a8b7c6d5e4f3a2b1c",
+            )
+            .unwrap(),
+            vout: 1,
+        };
+        let output = TxOut {
+            value: 99_000,
+            script_pubkey: address.script_pubkey(),
+        };
+
+        let tx = psbt(deposit, 100_000, output.clone());
+        assert!(check_tx(
+            TransactionKind::Emergency,
+            &tx,
+            Some(deposit),
+            Some(100_000),
+            &address
+        )
+        .is_empty());
+
+        let mut tx = psbt(deposit, 100_000, output.clone());
+        tx.inputs[0].sighash_type = Some(SigHashType::All);
+        assert_eq!(
+            check_tx(
+                TransactionKind::Emergency,
+                &tx,
+                Some(deposit),
+                Some(100_000),
+                &address
+            ),
+            vec![CheckError::SigHashType(TransactionKind::Emergency)]
+        );
+
+        let other = OutPoint {
+            txid: deposit.txid,
+            vout: 0,
+        };
+        let tx = psbt(other, 100_000, output.clone());
+        assert_eq!(
+            check_tx(
+                TransactionKind::Emergency,
+                &tx,
+                Some(deposit),
+                Some(100_000),
+                &address
+            ),
+            vec![CheckError::UnexpectedInput(TransactionKind::Emergency)]
+        );
+
+        let tx = psbt(
+            deposit,
+            100_000,
+            TxOut {
+                value: 50_000,
+                script_pubkey: address.script_pubkey(),
+            },
+        );
+        assert_eq!(
+            check_tx(
+                TransactionKind::Emergency,
+                &tx,
+                Some(deposit),
+                Some(100_000),
+                &address
+            ),
+            vec![CheckError::Fees(TransactionKind::Emergency, 50_000)]
+        );
+
+        let mut tx = psbt(deposit, 100_000, output);
+        tx.inputs[0].witness_utxo.as_mut().unwrap().value = u64::MAX;
+        assert!(check_tx(
+            TransactionKind::Emergency,
+            &tx,
+            Some(deposit),
+            None,
+            &address
+        )
+        .iter()
+        .any(|e| matches!(e, CheckError::Invalid(TransactionKind::Emergency, _))));
+    }
+}
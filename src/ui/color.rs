@@ -6,6 +6,12 @@ pub const BACKGROUND: Color = Color::from_rgb(
     0xF8 as f32 / 255.0,
 );
 
+pub const DARK_BACKGROUND: Color = Color::from_rgb(
+    0x20 as f32 / 255.0,
+    0x22 as f32 / 255.0,
+    0x27 as f32 / 255.0,
+);
+
 pub const FOREGROUND: Color = Color::WHITE;
 
 pub const SECONDARY: Color = Color::from_rgb(
@@ -52,6 +52,7 @@ impl container::StyleSheet for NavbarStyle {
             border_width: 1.0,
             border_color: color::SECONDARY,
             background: color::FOREGROUND.into(),
+            text_color: iced::Color::BLACK.into(),
             ..container::Style::default()
         }
     }
@@ -156,6 +157,7 @@ pub mod card {
                 border_color: iced::Color::BLACK,
                 border_width: 2.0,
                 background: color::FOREGROUND.into(),
+                text_color: iced::Color::BLACK.into(),
                 ..container::Style::default()
             }
         }
@@ -175,6 +177,7 @@ pub mod card {
                 border_color: color::PRIMARY,
                 border_width: 2.0,
                 background: color::FOREGROUND.into(),
+                text_color: iced::Color::BLACK.into(),
                 ..container::Style::default()
             }
         }
@@ -194,6 +197,7 @@ pub mod card {
                 border_color: color::SUCCESS,
                 border_width: 2.0,
                 background: color::FOREGROUND.into(),
+                text_color: iced::Color::BLACK.into(),
                 ..container::Style::default()
             }
         }
@@ -226,6 +230,7 @@ pub mod card {
             container::Style {
                 border_radius: 10.0,
                 background: color::FOREGROUND.into(),
+                text_color: iced::Color::BLACK.into(),
                 ..container::Style::default()
             }
         }
@@ -241,6 +246,7 @@ pub mod card {
             container::Style {
                 border_radius: 10.0,
                 background: color::FOREGROUND.into(),
+                text_color: iced::Color::BLACK.into(),
                 ..container::Style::default()
             }
         }
@@ -34,7 +34,6 @@ pub fn turnback_icon() -> Text {
     icon('\u{F131}')
 }
 
-#[allow(dead_code)]
 pub fn history_icon() -> Text {
     icon('\u{F292}')
 }